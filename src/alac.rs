@@ -0,0 +1,815 @@
+//! the Apple Lossless (ALAC) decoder.
+//!
+//! Apple Music serves lossless audio as ALAC in fragmented MP4. Once the samples are decrypted,
+//! `decode_mp4` turns the file into interleaved PCM samples, e.g. for `flac::encode`.
+
+use crate::error::{Error, Result};
+use crate::mp4::{find_box, parse_boxes};
+use crate::remux::{remux, sample_entry_fields_len, track_samples};
+
+/// The element types of an ALAC frame.
+const ID_SCE: u32 = 0;
+const ID_CPE: u32 = 1;
+const ID_CCE: u32 = 2;
+const ID_LFE: u32 = 3;
+const ID_DSE: u32 = 4;
+const ID_PCE: u32 = 5;
+const ID_FIL: u32 = 6;
+const ID_END: u32 = 7;
+
+/// The parameters of the adaptive Golomb-Rice coding.
+const QBSHIFT: u32 = 9;
+const QB: u32 = 1 << QBSHIFT;
+const MMULSHIFT: u32 = 2;
+const MDENSHIFT: u32 = QBSHIFT - MMULSHIFT - 1;
+const MOFF: u32 = 1 << (MDENSHIFT - 2);
+const BITOFF: u32 = 24;
+const N_MAX_MEAN_CLAMP: u32 = 0xffff;
+const N_MEAN_CLAMP_VAL: u32 = 0xffff;
+const MAX_PREFIX_16: u32 = 9;
+const MAX_PREFIX_32: u32 = 9;
+const MAX_DATATYPE_BITS_16: u32 = 16;
+
+/// The decoder configuration of an ALAC stream, the "magic cookie" of the `alac` box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlacConfig {
+    /// The number of samples per channel of a frame.
+    pub frame_length: u32,
+    pub compatible_version: u8,
+    pub bit_depth: u8,
+    /// The Rice coding parameters.
+    pub pb: u8,
+    pub mb: u8,
+    pub kb: u8,
+    pub channels: u8,
+    pub max_run: u16,
+    pub max_frame_bytes: u32,
+    pub avg_bit_rate: u32,
+    pub sample_rate: u32,
+}
+
+impl AlacConfig {
+    /// Parses a magic cookie. The cookie may be preceded by the `frma` and `alac` box headers,
+    /// as in QuickTime files.
+    pub fn from_magic_cookie(mut cookie: &[u8]) -> Result<Self> {
+        if cookie.get(4..8) == Some(b"frma") {
+            cookie = cookie.get(12..).unwrap_or_default();
+        }
+        if cookie.get(4..8) == Some(b"alac") {
+            cookie = cookie.get(12..).unwrap_or_default();
+        }
+        if cookie.len() < 24 {
+            return Err(Error::Alac("Truncated magic cookie".to_string()));
+        }
+        let config = Self {
+            frame_length: u32::from_be_bytes(cookie[0..4].try_into().unwrap()),
+            compatible_version: cookie[4],
+            bit_depth: cookie[5],
+            pb: cookie[6],
+            mb: cookie[7],
+            kb: cookie[8],
+            channels: cookie[9],
+            max_run: u16::from_be_bytes(cookie[10..12].try_into().unwrap()),
+            max_frame_bytes: u32::from_be_bytes(cookie[12..16].try_into().unwrap()),
+            avg_bit_rate: u32::from_be_bytes(cookie[16..20].try_into().unwrap()),
+            sample_rate: u32::from_be_bytes(cookie[20..24].try_into().unwrap()),
+        };
+        if config.compatible_version != 0 {
+            return Err(Error::Alac(format!(
+                "Unsupported compatible version {}",
+                config.compatible_version
+            )));
+        }
+        if !(1..=32).contains(&config.bit_depth) || !(1..=8).contains(&config.channels) {
+            return Err(Error::Alac(
+                "Invalid bit depth or channel count".to_string(),
+            ));
+        }
+        // `kb` is a shift amount of the Rice decoding.
+        if !(1..=31).contains(&config.kb) {
+            return Err(Error::Alac(format!(
+                "Invalid Rice parameter kb {}",
+                config.kb
+            )));
+        }
+        Ok(config)
+    }
+
+    /// Reads the configuration of the first track of an MP4 file, from its `alac` sample entry.
+    pub fn from_mp4(mp4: &[u8]) -> Result<Self> {
+        let stsd = find_box(mp4, &[b"moov", b"trak", b"mdia", b"minf", b"stbl", b"stsd"])?
+            .ok_or_else(|| Error::Mp4("stsd box not found".to_string()))?;
+        let entry = parse_boxes(stsd.payload().get(8..).unwrap_or_default())?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Mp4("Empty stsd box".to_string()))?;
+        if &entry.name != b"alac" {
+            return Err(Error::Alac(format!(
+                "The track is {}, not ALAC",
+                String::from_utf8_lossy(&entry.name)
+            )));
+        }
+        // The `alac` box follows the fields of the audio sample entry, longer from version 1.
+        let fields_len = sample_entry_fields_len(&entry)?;
+        let alac = parse_boxes(entry.payload().get(fields_len..).unwrap_or_default())?
+            .into_iter()
+            .find(|b| &b.name == b"alac")
+            .ok_or_else(|| Error::Alac("alac box not found".to_string()))?;
+        Self::from_magic_cookie(alac.payload().get(4..).unwrap_or_default())
+    }
+}
+
+/// Interleaved PCM samples.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pcm {
+    pub sample_rate: u32,
+    pub channels: u8,
+    /// The number of significant bits of each sample.
+    pub bits_per_sample: u8,
+    /// The samples, interleaved by channel, e.g. `L R L R …`.
+    pub samples: Vec<i32>,
+}
+
+impl Pcm {
+    /// Returns the number of samples per channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+}
+
+/// An ALAC decoder.
+#[derive(Debug, Clone)]
+pub struct AlacDecoder {
+    config: AlacConfig,
+}
+
+impl AlacDecoder {
+    pub fn new(config: AlacConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &AlacConfig {
+        &self.config
+    }
+
+    /// Decodes a frame into interleaved samples.
+    /// Channels are returned in the order of the frame elements.
+    pub fn decode_frame(&self, frame: &[u8]) -> Result<Vec<i32>> {
+        let channels = self.config.channels as usize;
+        let mut bits = BitReader::new(frame);
+        let mut output: Vec<Vec<i32>> = Vec::with_capacity(channels);
+        while output.len() < channels {
+            match bits.read(3) {
+                ID_SCE | ID_LFE => output.extend(self.decode_element(&mut bits, 1)?),
+                ID_CPE => {
+                    if output.len() + 2 > channels {
+                        break;
+                    }
+                    output.extend(self.decode_element(&mut bits, 2)?)
+                }
+                ID_DSE => {
+                    let _tag = bits.read(4);
+                    let align = bits.read(1) == 1;
+                    let mut count = bits.read(8);
+                    if count == 255 {
+                        count += bits.read(8);
+                    }
+                    if align {
+                        bits.align();
+                    }
+                    bits.skip(count * 8);
+                }
+                ID_FIL => {
+                    let mut count = bits.read(4);
+                    if count == 15 {
+                        count = count + bits.read(8) - 1;
+                    }
+                    bits.skip(count * 8);
+                }
+                ID_END => break,
+                tag @ (ID_CCE | ID_PCE) => {
+                    return Err(Error::Alac(format!("Unsupported element {tag}")))
+                }
+                _ => unreachable!(),
+            }
+            if bits.is_overrun() {
+                return Err(Error::Alac("Truncated frame".to_string()));
+            }
+        }
+        let frames = output.first().map_or(0, Vec::len);
+        if output.iter().any(|channel| channel.len() != frames) {
+            return Err(Error::Alac("Mismatched element lengths".to_string()));
+        }
+        let mut samples = Vec::with_capacity(frames * output.len());
+        for i in 0..frames {
+            samples.extend(output.iter().map(|channel| channel[i]));
+        }
+        Ok(samples)
+    }
+
+    /// Decodes a mono (SCE/LFE) or stereo (CPE) element into one sample vector per channel.
+    fn decode_element(&self, bits: &mut BitReader, channels: usize) -> Result<Vec<Vec<i32>>> {
+        let config = &self.config;
+        let _tag = bits.read(4);
+        if bits.read(12) != 0 {
+            return Err(Error::Alac("Unused header bits are not 0".to_string()));
+        }
+        let header = bits.read(4);
+        let partial_frame = header >> 3;
+        let mut bytes_shifted = (header >> 1) & 0x3;
+        let escape = header & 0x1;
+        if bytes_shifted == 3 || bytes_shifted * 8 >= config.bit_depth as u32 {
+            return Err(Error::Alac("Invalid shift".to_string()));
+        }
+        let mut num_samples = config.frame_length as usize;
+        if partial_frame != 0 {
+            num_samples = bits.read(32) as usize;
+        }
+        if num_samples > config.frame_length as usize {
+            return Err(Error::Alac(
+                "Frame longer than the frame length".to_string(),
+            ));
+        }
+
+        let mut mix_bits = 0;
+        let mut mix_res = 0;
+        let mut shift_values = Vec::new();
+        let mut mix: Vec<Vec<i32>> = Vec::with_capacity(channels);
+        if escape == 0 {
+            let chan_bits = config.bit_depth as u32 - bytes_shifted * 8 + channels as u32 - 1;
+            if chan_bits > 32 {
+                return Err(Error::Alac("Unsupported bit depth".to_string()));
+            }
+            mix_bits = bits.read(8);
+            if mix_bits >= 32 {
+                return Err(Error::Alac(format!("Invalid mix bits {mix_bits}")));
+            }
+            mix_res = bits.read(8) as u8 as i8 as i32;
+            let mut predictors = Vec::with_capacity(channels);
+            for _ in 0..channels {
+                let header = bits.read(8);
+                let mode = header >> 4;
+                let den_shift = header & 0xf;
+                let header = bits.read(8);
+                let pb_factor = header >> 5;
+                let num = (header & 0x1f) as usize;
+                let coefs: Vec<i16> = (0..num).map(|_| bits.read(16) as u16 as i16).collect();
+                predictors.push((mode, den_shift, pb_factor, coefs));
+            }
+            // The low bytes of the samples precede the compressed data.
+            if bytes_shifted != 0 {
+                let shift = bytes_shifted * 8;
+                shift_values = (0..num_samples * channels)
+                    .map(|_| bits.read(shift))
+                    .collect();
+            }
+            for (mode, den_shift, pb_factor, mut coefs) in predictors {
+                let params = AgParams {
+                    mb: config.mb as u32,
+                    pb: config.pb as u32 * pb_factor / 4,
+                    kb: config.kb as u32,
+                    wb: (1 << config.kb) - 1,
+                };
+                let mut pc = vec![0; num_samples];
+                dyn_decomp(&params, bits, &mut pc, chan_bits)?;
+                if mode != 0 {
+                    pc = unpc_block(&pc, &mut [], 31, chan_bits, 0);
+                }
+                let num = coefs.len();
+                mix.push(unpc_block(&pc, &mut coefs, num, chan_bits, den_shift));
+            }
+        } else {
+            let chan_bits = if channels == 1 {
+                config.bit_depth as u32 - bytes_shifted * 8
+            } else {
+                config.bit_depth as u32
+            };
+            mix = vec![Vec::with_capacity(num_samples); channels];
+            for _ in 0..num_samples {
+                for channel in &mut mix {
+                    channel.push(sign_extend(bits.read(chan_bits), chan_bits));
+                }
+            }
+            bytes_shifted = 0;
+        }
+
+        if channels == 2 && mix_res != 0 {
+            let (u, v) = mix.split_at_mut(1);
+            for (u, v) in u[0].iter_mut().zip(v[0].iter_mut()) {
+                let l = u
+                    .wrapping_add(*v)
+                    .wrapping_sub(mix_res.wrapping_mul(*v) >> mix_bits);
+                *v = l.wrapping_sub(*v);
+                *u = l;
+            }
+        }
+        let shift = bytes_shifted * 8;
+        let bit_depth = config.bit_depth as u32;
+        for (index, channel) in mix.iter_mut().enumerate() {
+            for (i, sample) in channel.iter_mut().enumerate() {
+                if shift != 0 {
+                    *sample = (*sample << shift) | shift_values[i * channels + index] as i32;
+                }
+                *sample = sign_extend(*sample as u32, bit_depth);
+            }
+        }
+        Ok(mix)
+    }
+}
+
+/// Decodes the first track of an ALAC MP4 file, fragmented or not.
+/// The samples must already be decrypted.
+pub fn decode_mp4(mp4: &[u8]) -> Result<Pcm> {
+    let mp4 = remux(mp4)?;
+    let config = AlacConfig::from_mp4(&mp4)?;
+    let decoder = AlacDecoder::new(config);
+    let samples = track_samples(&mp4)?;
+    let mut pcm = Pcm {
+        sample_rate: config.sample_rate,
+        channels: config.channels,
+        bits_per_sample: config.bit_depth,
+        samples: Vec::with_capacity(samples.len() * config.frame_length as usize * 2),
+    };
+    for sample in samples {
+        pcm.samples.extend(decoder.decode_frame(sample)?);
+    }
+    Ok(pcm)
+}
+
+struct AgParams {
+    mb: u32,
+    pb: u32,
+    kb: u32,
+    wb: u32,
+}
+
+/// Decodes the adaptive Golomb-Rice coded prediction residuals of a channel.
+fn dyn_decomp(
+    params: &AgParams,
+    bits: &mut BitReader,
+    pc: &mut [i32],
+    max_size: u32,
+) -> Result<()> {
+    let num_samples = pc.len();
+    let mut mb = params.mb;
+    let mut zmode = 0u32;
+    let mut c = 0;
+    while c < num_samples {
+        if bits.is_at_end() {
+            return Err(Error::Alac("Truncated residuals".to_string()));
+        }
+        let k = lg3a(mb >> QBSHIFT).min(params.kb);
+        let m = (1 << k) - 1;
+        let n = dyn_get_32bit(bits, m, k, max_size);
+
+        // The least significant bit is the sign bit.
+        let ndecode = n.wrapping_add(zmode);
+        let multiplier = -((ndecode & 1) as i32) | 1;
+        pc[c] = ((ndecode.wrapping_add(1) >> 1) as i32).wrapping_mul(multiplier);
+        c += 1;
+
+        mb = params
+            .pb
+            .wrapping_mul(n.wrapping_add(zmode))
+            .wrapping_add(mb)
+            .wrapping_sub(params.pb.wrapping_mul(mb) >> QBSHIFT);
+        if n > N_MAX_MEAN_CLAMP {
+            mb = N_MEAN_CLAMP_VAL;
+        }
+        zmode = 0;
+
+        if (mb << MMULSHIFT) < QB && c < num_samples {
+            zmode = 1;
+            let k = (mb.leading_zeros() + ((mb + MOFF) >> MDENSHIFT)).saturating_sub(BITOFF);
+            let mz = ((1 << k) - 1) & params.wb;
+            let n = dyn_get(bits, mz, k) as usize;
+            if c + n > num_samples {
+                return Err(Error::Alac("Zero run out of the frame".to_string()));
+            }
+            pc[c..c + n].fill(0);
+            c += n;
+            if n >= 65535 {
+                zmode = 0;
+            }
+            mb = 0;
+        }
+    }
+    if bits.is_overrun() {
+        return Err(Error::Alac("Truncated residuals".to_string()));
+    }
+    Ok(())
+}
+
+/// Reads a Golomb-Rice code with a 16-bit escape, used for zero runs.
+fn dyn_get(bits: &mut BitReader, m: u32, k: u32) -> u32 {
+    let prefix = (!bits.peek()).leading_zeros();
+    if prefix >= MAX_PREFIX_16 {
+        bits.skip(MAX_PREFIX_16);
+        return bits.read(MAX_DATATYPE_BITS_16);
+    }
+    bits.skip(prefix + 1);
+    let v = if k == 0 { 0 } else { bits.peek() >> (32 - k) };
+    if v < 2 {
+        bits.skip(k.saturating_sub(1));
+        prefix.wrapping_mul(m)
+    } else {
+        bits.skip(k);
+        prefix.wrapping_mul(m).wrapping_add(v - 1)
+    }
+}
+
+/// Reads a Golomb-Rice code with a `max_bits` escape, used for residuals.
+fn dyn_get_32bit(bits: &mut BitReader, m: u32, k: u32, max_bits: u32) -> u32 {
+    let prefix = (!bits.peek()).leading_zeros();
+    if prefix >= MAX_PREFIX_32 {
+        bits.skip(MAX_PREFIX_32);
+        return bits.read(max_bits);
+    }
+    bits.skip(prefix + 1);
+    if k == 1 {
+        return prefix;
+    }
+    let v = bits.peek() >> (32 - k);
+    if v < 2 {
+        bits.skip(k - 1);
+        prefix.wrapping_mul(m)
+    } else {
+        bits.skip(k);
+        prefix.wrapping_mul(m).wrapping_add(v - 1)
+    }
+}
+
+/// Runs the adaptive linear predictor over the residuals of a channel.
+/// `num_active == 31` is the first-order difference mode.
+fn unpc_block(
+    pc: &[i32],
+    coefs: &mut [i16],
+    num_active: usize,
+    chan_bits: u32,
+    den_shift: u32,
+) -> Vec<i32> {
+    let num = pc.len();
+    let mut out = vec![0; num];
+    if num == 0 {
+        return out;
+    }
+    let chan_shift = 32 - chan_bits;
+    let clip = |value: i32| (value << chan_shift) >> chan_shift;
+    out[0] = pc[0];
+    if num_active == 0 {
+        out.copy_from_slice(pc);
+        return out;
+    }
+    if num_active == 31 {
+        for j in 1..num {
+            out[j] = clip(pc[j].wrapping_add(out[j - 1]));
+        }
+        return out;
+    }
+    for j in 1..=num_active.min(num - 1) {
+        out[j] = clip(pc[j].wrapping_add(out[j - 1]));
+    }
+    let lim = num_active + 1;
+    let den_half = if den_shift == 0 {
+        0
+    } else {
+        1 << (den_shift - 1)
+    };
+    for j in lim..num {
+        let top = out[j - lim];
+        let sum = (0..num_active).fold(0i32, |sum, k| {
+            sum.wrapping_add((coefs[k] as i32).wrapping_mul(out[j - 1 - k].wrapping_sub(top)))
+        });
+        let del = pc[j];
+        let mut del0 = del;
+        let sg = del.signum();
+        out[j] = clip(
+            del.wrapping_add(top)
+                .wrapping_add(sum.wrapping_add(den_half) >> den_shift),
+        );
+        if sg > 0 {
+            for k in (0..num_active).rev() {
+                let dd = top.wrapping_sub(out[j - 1 - k]);
+                let sgn = dd.signum();
+                coefs[k] = coefs[k].wrapping_sub(sgn as i16);
+                del0 = del0.wrapping_sub(
+                    ((num_active - k) as i32).wrapping_mul(sgn.wrapping_mul(dd) >> den_shift),
+                );
+                if del0 <= 0 {
+                    break;
+                }
+            }
+        } else if sg < 0 {
+            for k in (0..num_active).rev() {
+                let dd = top.wrapping_sub(out[j - 1 - k]);
+                let sgn = dd.signum();
+                coefs[k] = coefs[k].wrapping_add(sgn as i16);
+                del0 = del0.wrapping_sub(
+                    ((num_active - k) as i32).wrapping_mul((-sgn).wrapping_mul(dd) >> den_shift),
+                );
+                if del0 >= 0 {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+fn lg3a(x: u32) -> u32 {
+    31 - (x + 3).leading_zeros()
+}
+
+fn sign_extend(value: u32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    ((value << shift) as i32) >> shift
+}
+
+/// A big-endian bit reader. Reads past the end return zeros and are reported by `is_overrun`.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Returns the next 32 bits without consuming them.
+    fn peek(&self) -> u32 {
+        let byte = self.position / 8;
+        let word = (0..5).fold(0u64, |word, i| {
+            word << 8 | *self.data.get(byte + i).unwrap_or(&0) as u64
+        });
+        ((word << (self.position % 8)) >> 8) as u32
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        let value = self.peek() >> (32 - count);
+        self.position += count as usize;
+        value
+    }
+
+    fn skip(&mut self, count: u32) {
+        self.position += count as usize;
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.position >= self.data.len() * 8
+    }
+
+    fn is_overrun(&self) -> bool {
+        self.position > self.data.len() * 8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlacConfig, AlacDecoder};
+    use crate::error::Error;
+    use crate::mp4::{mp4_box, mp4_full_box};
+
+    #[derive(Default)]
+    struct BitWriter {
+        bits: Vec<bool>,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, value: u32, count: u32) {
+            self.bits
+                .extend((0..count).rev().map(|i| (value >> i) & 1 == 1));
+        }
+
+        fn bytes(&self) -> Vec<u8> {
+            self.bits
+                .chunks(8)
+                .map(|bits| {
+                    (0..8).fold(0, |byte, i| byte << 1 | (bits.get(i) == Some(&true)) as u8)
+                })
+                .collect()
+        }
+    }
+
+    /// Writes a Golomb-Rice code as read by `dyn_get` and `dyn_get_32bit`.
+    fn write_code(writer: &mut BitWriter, n: u32, m: u32, k: u32, escape_bits: u32) {
+        let prefix = n / m;
+        if prefix >= 9 {
+            writer.write(0x1ff, 9);
+            writer.write(n, escape_bits);
+            return;
+        }
+        writer.write((1 << prefix) - 1, prefix);
+        writer.write(0, 1);
+        match n % m {
+            0 => writer.write(0, k - 1),
+            r => writer.write(r + 1, k),
+        }
+    }
+
+    /// Writes residuals with the adaptive coding of `dyn_decomp`, for pb = 40, mb = 10, kb = 14.
+    fn write_residuals(writer: &mut BitWriter, residuals: &[i32], chan_bits: u32) {
+        let (mut mb, mut zmode, mut c) = (10u32, 0u32, 0);
+        while c < residuals.len() {
+            let k = super::lg3a(mb >> 9).min(14);
+            let del = residuals[c];
+            let n = if del >= 0 {
+                2 * del as u32
+            } else {
+                (-2 * del - 1) as u32
+            } - zmode;
+            write_code(writer, n, (1 << k) - 1, k, chan_bits);
+            c += 1;
+            mb = 40 * (n + zmode) + mb - ((40 * mb) >> 9);
+            if n > 0xffff {
+                mb = 0xffff;
+            }
+            zmode = 0;
+            if (mb << 2) < 512 && c < residuals.len() {
+                zmode = 1;
+                let run = residuals[c..].iter().take_while(|&&r| r == 0).count() as u32;
+                c += run as usize;
+                let k = mb.leading_zeros() - 24 + ((mb + 16) >> 6);
+                write_code(writer, run, ((1 << k) - 1) & 0x3fff, k, 16);
+                mb = 0;
+            }
+        }
+    }
+
+    /// A magic cookie for 16-bit stereo at 44.1 kHz, frames of 16 samples.
+    fn cookie() -> Vec<u8> {
+        let mut cookie = 16u32.to_be_bytes().to_vec();
+        cookie.extend([0, 16, 40, 10, 14, 2, 0, 255]);
+        cookie.extend([0; 8]);
+        cookie.extend(44100u32.to_be_bytes());
+        cookie
+    }
+
+    #[test]
+    fn test_config() {
+        let config = AlacConfig::from_magic_cookie(&cookie()).unwrap();
+        assert_eq!(config.channels, 2);
+        // A cookie with only the header of a `frma` box is truncated.
+        let frma = [&12u32.to_be_bytes()[..], b"frma", b"alac"].concat();
+        assert!(AlacConfig::from_magic_cookie(&frma[..10]).is_err());
+        // The Rice parameter `kb` is a shift amount.
+        for kb in [0, 32] {
+            let mut cookie = cookie();
+            cookie[8] = kb;
+            assert!(matches!(
+                AlacConfig::from_magic_cookie(&cookie),
+                Err(Error::Alac(_))
+            ));
+        }
+
+        // The `alac` box of a version 1 audio sample entry follows 16 more bytes of fields.
+        let mut fields = vec![0; 28];
+        fields[8..10].copy_from_slice(&1u16.to_be_bytes());
+        fields.extend([0; 16]);
+        let alac = mp4_full_box(b"alac", 0, 0, &cookie());
+        let entry = mp4_box(b"alac", &[fields, alac].concat());
+        let stsd = mp4_full_box(b"stsd", 0, 0, &[&1u32.to_be_bytes()[..], &entry].concat());
+        let mut mp4 = stsd;
+        for name in [b"stbl", b"minf", b"mdia", b"trak", b"moov"] {
+            mp4 = mp4_box(name, &mp4);
+        }
+        assert_eq!(AlacConfig::from_mp4(&mp4).unwrap(), config);
+    }
+
+    /// Reads the magic cookie and the packets of a CAF file.
+    fn read_caf(caf: &[u8]) -> (&[u8], Vec<&[u8]>) {
+        let mut chunks = std::collections::HashMap::new();
+        let mut offset = 8;
+        while offset + 12 <= caf.len() {
+            let size = u64::from_be_bytes(caf[offset + 4..offset + 12].try_into().unwrap());
+            let data = &caf[offset + 12..offset + 12 + size as usize];
+            chunks.insert(&caf[offset..offset + 4], data);
+            offset += 12 + size as usize;
+        }
+        // The packet table: the number of packets and frames, then the variable-length packet sizes.
+        let pakt = chunks[&b"pakt"[..]];
+        let count = u64::from_be_bytes(pakt[..8].try_into().unwrap());
+        let mut sizes = Vec::new();
+        let mut size = 0;
+        for &byte in &pakt[24..] {
+            size = size << 7 | (byte & 0x7f) as usize;
+            if byte & 0x80 == 0 {
+                sizes.push(size);
+                size = 0;
+            }
+        }
+        assert_eq!(sizes.len() as u64, count);
+        // The audio data starts with an edit count.
+        let mut data = &chunks[&b"data"[..]][4..];
+        let packets = sizes
+            .iter()
+            .map(|&size| {
+                let (packet, rest) = data.split_at(size);
+                data = rest;
+                packet
+            })
+            .collect();
+        (chunks[&b"kuki"[..]], packets)
+    }
+
+    #[test]
+    fn test_decode_afconvert_file() {
+        let (cookie, packets) = read_caf(include_bytes!("../tests/fixtures/sine_440hz_stereo.caf"));
+        let config = AlacConfig::from_magic_cookie(cookie).unwrap();
+        assert_eq!(config.frame_length, 4096);
+        assert_eq!(config.channels, 2);
+        assert_eq!(config.bit_depth, 16);
+        let decoder = AlacDecoder::new(config);
+        let samples: Vec<i32> = packets
+            .iter()
+            .flat_map(|packet| decoder.decode_frame(packet).unwrap())
+            .collect();
+
+        let flac = include_bytes!("../tests/fixtures/sine_440hz_stereo.flac");
+        let mut reader = claxon::FlacReader::new(&flac[..]).unwrap();
+        let expected: Vec<i32> = reader.samples().map(Result::unwrap).collect();
+        // One second of audio: the last packet is a partial frame.
+        assert_eq!(samples.len(), 44100 * 2);
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn test_decode_frame() {
+        let config = AlacConfig::from_magic_cookie(&cookie()).unwrap();
+        assert_eq!(config.frame_length, 16);
+        assert_eq!(config.sample_rate, 44100);
+        let decoder = AlacDecoder::new(config);
+
+        let left: Vec<i32> = vec![
+            0, 0, 0, 0, 0, 0, 0, 0, 5, -3, 32767, -32768, 1200, 1100, 900, 0,
+        ];
+        let right: Vec<i32> = (0..16).map(|i| i * 1000 - 8000).collect();
+        // Mixed stereo with mix_bits = 1 and mix_res = 1: v = l - r, u = r + (v >> 1).
+        let v: Vec<i32> = left.iter().zip(&right).map(|(l, r)| l - r).collect();
+        let u: Vec<i32> = right.iter().zip(&v).map(|(r, v)| r + (v >> 1)).collect();
+        let mut writer = BitWriter::default();
+        writer.write(1, 3);
+        writer.write(0, 4 + 12);
+        writer.write(0, 4);
+        writer.write(1, 8);
+        writer.write(1, 8);
+        // U is coded as is; V as its first-order difference (mode 1, 31 coefficients).
+        writer.write(0, 8);
+        writer.write(4 << 5, 8);
+        writer.write(1 << 4, 8);
+        writer.write(4 << 5, 8);
+        write_residuals(&mut writer, &u, 17);
+        // The differences wrap around the 17 bits of a mixed channel.
+        let diff: Vec<i32> = (0..16)
+            .map(|i| {
+                if i == 0 {
+                    v[0]
+                } else {
+                    (v[i] - v[i - 1]) << 15 >> 15
+                }
+            })
+            .collect();
+        write_residuals(&mut writer, &diff, 17);
+        writer.write(7, 3);
+        let samples = decoder.decode_frame(&writer.bytes()).unwrap();
+        let expected: Vec<i32> = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(l, r)| [*l, *r])
+            .collect();
+        assert_eq!(samples, expected);
+
+        // An uncompressed partial frame.
+        let mut writer = BitWriter::default();
+        writer.write(1, 3);
+        writer.write(0, 4 + 12);
+        writer.write(0b1001, 4);
+        writer.write(3, 32);
+        for sample in [1, -1, 2, -2, -32768, 32767] {
+            writer.write(sample as u32 & 0xffff, 16);
+        }
+        writer.write(7, 3);
+        let samples = decoder.decode_frame(&writer.bytes()).unwrap();
+        assert_eq!(samples, [1, -1, 2, -2, -32768, 32767]);
+
+        // The mix bits of a malformed frame are a shift amount out of range.
+        let mut writer = BitWriter::default();
+        writer.write(1, 3);
+        writer.write(0, 4 + 12);
+        writer.write(0, 4);
+        writer.write(32, 8);
+        writer.write(1, 8);
+        writer.write(7, 3);
+        assert!(matches!(
+            decoder.decode_frame(&writer.bytes()),
+            Err(Error::Alac(_))
+        ));
+    }
+}
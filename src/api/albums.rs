@@ -1,22 +1,17 @@
+use super::artists::Artists;
 use super::artwork::Artwork;
 use super::editorial_notes::EditorialNotes;
+use super::editorial_video::EditorialVideo;
+use super::genres::Genre;
+use super::library_albums::LibraryAlbums;
+use super::music_videos::MusicVideos;
 use super::play_parameters::PlayParameters;
+use super::playlists::{Playlists, TrackData};
+use super::record_labels::RecordLabels;
+use super::resource::{Relationship, Resource, View};
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Albums {
-    /// The identifier for the album.
-    pub id: String,
-    /// This value is always albums.
-    #[serde(rename = "type")]
-    pub type_: String,
-    /// The relative location for the album resource.
-    pub href: String,
-    /// The attributes for the album.
-    pub attributes: Option<Attributes>,
-    /// The relationships for the album.
-    pub relationships: Option<Relationships>,
-}
+/// A resource object that represents an album. The type is always albums.
+pub type Albums = Resource<Attributes, Relationships, Views>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +22,8 @@ pub struct Attributes {
     pub artist_url: Option<String>,
     /// The artwork for the album.
     pub artwork: Artwork,
+    /// The audio traits of the album, e.g. lossless, lossy-stereo or atmos.
+    pub audio_traits: Option<Vec<String>>,
     /// Indicates the specific audio variant for the album.
     pub audio_variants: Option<Vec<String>>,
     /// The Recording Industry Association of America (RIAA) rating of the content.
@@ -35,6 +32,8 @@ pub struct Attributes {
     pub copyright: Option<String>,
     /// The notes about the album that appear in the iTunes Store.
     pub editorial_notes: Option<EditorialNotes>,
+    /// (Extended) The motion artwork of the album.
+    pub editorial_video: Option<EditorialVideo>,
     /// The names of the genres associated with the album.
     pub genre_names: Vec<String>,
     /// Indicates whether the album is marked as a compilation.
@@ -43,6 +42,8 @@ pub struct Attributes {
     pub is_complete: bool,
     /// Indicates whether the response delivered the album as an Apple Digital Master.
     pub is_mastered_for_itunes: bool,
+    /// Indicates whether the album is a pre-release.
+    pub is_prerelease: Option<bool>,
     /// Indicates whether the album contains a single song.
     pub is_single: bool,
     /// The localized name of the album.
@@ -64,78 +65,112 @@ pub struct Attributes {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Relationships {
-    // /// The artists associated with the album. By default, artists includes identifiers only.
-    // pub artists: Option<AlbumsArtistsRelationship>,
-    // /// The genres for the album. By default, genres not included.
-    // pub genres: Option<AlbumsGenresRelationship>,
-    // /// The songs and music videos on the album. By default, tracks includes objects.
-    // pub tracks: Option<AlbumsTracksRelationship>,
-    // /// The album in the user’s library for the catalog album, if any.
-    // pub library: Option<AlbumsLibraryRelationship>,
-    // /// The record labels for the album.
-    // pub record_labels: Option<AlbumsRecordLabelsRelationship>,
+    /// The artists associated with the album. By default, artists includes identifiers only.
+    pub artists: Option<Relationship<Artists>>,
+    /// The genres for the album. By default, genres not included.
+    pub genres: Option<Relationship<Genre>>,
+    /// The songs and music videos on the album. By default, tracks includes objects.
+    pub tracks: Option<Relationship<TrackData>>,
+    /// The album in the user’s library for the catalog album, if any.
+    pub library: Option<Relationship<LibraryAlbums>>,
+    /// The record labels for the album.
+    #[serde(rename = "record-labels")]
+    pub record_labels: Option<Relationship<RecordLabels>>,
 }
 
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub struct AlbumsArtistsRelationship {
-//     /// The relative location to fetch the relationship directly.
-//     pub href: String,
-//     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-//     pub next: Option<String>,
-//     /// The artists for the album.
-//     pub data: Vec<Artist>,
-// }
-
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub struct AlbumsGenresRelationship {
-//     /// The relative location to fetch the relationship directly.
-//     pub href: String,
-//     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-//     pub next: Option<String>,
-//     /// The album’s associated genre.
-//     pub data: Vec<Genre>,
-// }
-
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub struct AlbumsTracksRelationship {
-//     /// The relative location to fetch the relationship directly.
-//     pub href: String,
-//     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-//     pub next: Option<String>,
-//     /// The tracks for the album.
-//     pub data: Vec<TrackData>,
-// }
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Views {
+    /// The playlists that contain songs of the album.
+    pub appears_on: Option<View<Playlists>>,
+    /// The other versions of the album, e.g. deluxe or clean editions.
+    pub other_versions: Option<View<Albums>>,
+    /// The albums related to the album.
+    pub related_albums: Option<View<Albums>>,
+    /// The music videos related to the album.
+    pub related_videos: Option<View<MusicVideos>>,
+}
 
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub enum TrackData {
-//     #[serde(rename = "songs")]
-//     Songs(Songs),
-//     #[serde(rename = "music-videos")]
-//     MusicVideos(MusicVideos),
-// }
+impl Albums {
+    /// Returns the artwork of the album, if the attributes are included.
+    /// Use `AppleMusicDownloader::get_artwork` to download it.
+    pub fn artwork(&self) -> Option<&Artwork> {
+        Some(&self.attributes.as_ref()?.artwork)
+    }
+}
 
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub struct AlbumsLibraryRelationship {
-//     /// The relative location to fetch the relationship directly.
-//     pub href: String,
-//     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-//     pub next: Option<String>,
-//     /// The library content this album is associated with if added to the user’s library.
-//     pub data: Vec<LibraryAlbums>,
-// }
+#[cfg(test)]
+mod tests {
+    use crate::api::playlists::TrackData;
+    use crate::api::query::{Include, Query, View};
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
 
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub struct AlbumsRecordLabelsRelationship {
-//     /// The relative location to fetch the relationship directly.
-//     pub href: String,
-//     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-//     pub next: Option<String>,
-//     /// The album’s associated record label.
-//     pub data: Vec<RecordLabels>,
-// }
+    #[tokio::test]
+    async fn test_album_relationships_and_views() {
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "data": [{
+                    "id": "1214782644",
+                    "type": "albums",
+                    "href": "/v1/catalog/us/albums/1214782644",
+                    "relationships": {
+                        "artists": {
+                            "href": "/v1/catalog/us/albums/1214782644/artists",
+                            "data": [{ "id": "1198478003", "type": "artists", "href": "/v1/catalog/us/artists/1198478003" }]
+                        },
+                        "genres": {
+                            "data": [{
+                                "id": "7",
+                                "type": "genres",
+                                "href": "/v1/catalog/us/genres/7",
+                                "attributes": { "name": "Electronic", "parentId": "34", "parentName": "Music" }
+                            }]
+                        },
+                        "tracks": {
+                            "data": [{ "id": "1214782673", "type": "songs", "href": "/v1/catalog/us/songs/1214782673" }]
+                        }
+                    },
+                    "views": {
+                        "other-versions": {
+                            "href": "/v1/catalog/us/albums/1214782644/view/other-versions",
+                            "attributes": { "title": "Other Versions" },
+                            "data": [{ "id": "1214782645", "type": "albums", "href": "/v1/catalog/us/albums/1214782645" }]
+                        }
+                    }
+                }]
+            }),
+        )])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        let query = Query::new()
+            .include(Include::Artists)
+            .include(Include::Genres)
+            .view(View::OtherVersions)
+            .view(View::RelatedAlbums);
+        let album = apple_music.get_album("1214782644", &query).await.unwrap();
+        assert_eq!(
+            server.requests()[0].path,
+            "/v1/catalog/us/albums/1214782644?include=artists,genres&views=other-versions,related-albums"
+        );
+        let relationships = album.relationships.unwrap();
+        assert_eq!(relationships.artists.unwrap().data[0].id, "1198478003");
+        assert_eq!(
+            relationships.genres.unwrap().data[0]
+                .attributes
+                .as_ref()
+                .unwrap()
+                .name,
+            "Electronic"
+        );
+        assert!(matches!(
+            relationships.tracks.unwrap().data[0],
+            TrackData::Songs(_)
+        ));
+        let views = album.views.unwrap();
+        assert_eq!(views.other_versions.unwrap().data[0].id, "1214782645");
+        assert!(views.related_albums.is_none());
+    }
+}
@@ -1 +1,38 @@
+use super::albums::Albums;
+use super::artwork::Artwork;
+use super::editorial_notes::EditorialNotes;
+use super::resource::{Relationship, Resource};
 
+/// A resource object that represents an artist. The type is always artists.
+pub type Artists = Resource<Attributes, Relationships>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The artwork for the artist image.
+    pub artwork: Option<Artwork>,
+    /// The notes about the artist that appear in the Apple Music catalog.
+    pub editorial_notes: Option<EditorialNotes>,
+    /// The names of the genres associated with this artist.
+    #[serde(default)]
+    pub genre_names: Vec<String>,
+    /// The localized name of the artist.
+    pub name: String,
+    /// The URL for sharing the artist in Apple Music.
+    pub url: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The albums associated with the artist. By default, albums includes identifiers only.
+    pub albums: Option<Relationship<Albums>>,
+}
+
+impl Artists {
+    /// Returns the image of the artist, if the attributes are included.
+    /// Use `AppleMusicDownloader::get_artwork` to download it.
+    pub fn artwork(&self) -> Option<&Artwork> {
+        self.attributes.as_ref()?.artwork.as_ref()
+    }
+}
@@ -19,3 +19,199 @@ pub struct Artwork {
     pub url: String,
     pub has_p3: Option<bool>,
 }
+
+/// The image format of a rendered artwork URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtworkFormat {
+    Jpg,
+    Png,
+    Webp,
+    /// The original uploaded image, at its original size and format.
+    Original,
+}
+
+impl ArtworkFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtworkFormat::Jpg => "jpg",
+            ArtworkFormat::Png => "png",
+            ArtworkFormat::Webp => "webp",
+            ArtworkFormat::Original => "original",
+        }
+    }
+}
+
+impl Artwork {
+    /// Creates an `Artwork` from an URL of a rendered image, e.g. `…/600x600bb.jpg`,
+    /// replacing the size and format with the `{w}x{h}` and `{f}` placeholders.
+    /// The maximum size is unknown and left at 0.
+    pub fn from_rendered_url(url: &str) -> Self {
+        Self {
+            bg_color: None,
+            height: 0,
+            width: 0,
+            text_color1: None,
+            text_color2: None,
+            text_color3: None,
+            text_color4: None,
+            url: template_url(url),
+            has_p3: None,
+        }
+    }
+
+    /// Renders the URL of the artwork at a size and format.
+    /// The size is clamped to the maximum size of the artwork, when known.
+    pub fn url(&self, width: u32, height: u32, format: ArtworkFormat) -> String {
+        if format == ArtworkFormat::Original {
+            if let Some(url) = original_url(&self.url) {
+                return url;
+            }
+        }
+        let clamp = |size: u32, max: i32| if max > 0 { size.min(max as u32) } else { size };
+        let format = match format {
+            ArtworkFormat::Original => ArtworkFormat::Jpg,
+            format => format,
+        };
+        self.url
+            .replace("{w}", &clamp(width, self.width).to_string())
+            .replace("{h}", &clamp(height, self.height).to_string())
+            .replace("{c}", "bb")
+            .replace("{f}", format.as_str())
+    }
+
+    /// Renders the URL of the artwork at its maximum size.
+    pub fn max_url(&self, format: ArtworkFormat) -> String {
+        let width = if self.width > 0 {
+            self.width as u32
+        } else {
+            100000
+        };
+        let height = if self.height > 0 {
+            self.height as u32
+        } else {
+            100000
+        };
+        self.url(width, height, format)
+    }
+}
+
+/// Replaces the size and format of the last path segment of a rendered URL with placeholders.
+fn template_url(url: &str) -> String {
+    let Some((base, name)) = url.rsplit_once('/') else {
+        return url.to_string();
+    };
+    let Some((size, rest)) = name.split_once('x') else {
+        return url.to_string();
+    };
+    let height_len = rest.chars().take_while(char::is_ascii_digit).count();
+    let Some((suffix, _)) = rest[height_len..].rsplit_once('.') else {
+        return url.to_string();
+    };
+    if size.is_empty() || !size.chars().all(|c| c.is_ascii_digit()) || height_len == 0 {
+        return url.to_string();
+    }
+    format!("{base}/{{w}}x{{h}}{suffix}.{{f}}")
+}
+
+/// Returns the URL of the original image of a mzstatic.com thumbnail URL, on the host of the thumbnail.
+fn original_url(url: &str) -> Option<String> {
+    let (host, path) = url.split_once("/image/thumb/")?;
+    let (path, _) = path.rsplit_once('/')?;
+    Some(format!("{host}/us/r1000/0/{path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Artwork, ArtworkFormat};
+
+    #[test]
+    fn test_artwork_url() {
+        let mut artwork = Artwork::from_rendered_url(
+            "https://is1-ssl.mzstatic.com/image/thumb/Music126/v4/aa/bb/cc/source/600x600bb.jpg",
+        );
+        assert_eq!(
+            artwork.url,
+            "https://is1-ssl.mzstatic.com/image/thumb/Music126/v4/aa/bb/cc/source/{w}x{h}bb.{f}"
+        );
+        artwork.width = 3000;
+        artwork.height = 3000;
+        assert_eq!(
+            artwork.url(5000, 1400, ArtworkFormat::Png),
+            "https://is1-ssl.mzstatic.com/image/thumb/Music126/v4/aa/bb/cc/source/3000x1400bb.png"
+        );
+        assert_eq!(
+            artwork.max_url(ArtworkFormat::Webp),
+            "https://is1-ssl.mzstatic.com/image/thumb/Music126/v4/aa/bb/cc/source/3000x3000bb.webp"
+        );
+        assert_eq!(
+            artwork.url(600, 600, ArtworkFormat::Original),
+            "https://is1-ssl.mzstatic.com/us/r1000/0/Music126/v4/aa/bb/cc/source"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_artwork_cached() {
+        let server =
+            crate::mock_server::MockServer::start(vec![crate::mock_server::MockResponse {
+                status: 200,
+                headers: vec![("Content-Type", "image/jpeg".to_string())],
+                body: b"\xff\xd8jpeg".to_vec(),
+            }])
+            .await;
+        let artwork = Artwork {
+            width: 1000,
+            height: 1000,
+            ..Artwork::from_rendered_url(&format!(
+                "{}/image/thumb/a/source/100x100bb.jpg",
+                server.url
+            ))
+        };
+        let apple_music_downloader =
+            crate::AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        for _ in 0..2 {
+            let image = apple_music_downloader
+                .get_artwork(&artwork, 3000, 3000, ArtworkFormat::Jpg)
+                .await
+                .unwrap();
+            assert_eq!(image, b"\xff\xd8jpeg");
+        }
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/image/thumb/a/source/1000x1000bb.jpg");
+    }
+
+    #[tokio::test]
+    async fn test_get_artwork_evicted() {
+        let responses = (0..crate::ARTWORK_CACHE_SIZE + 2)
+            .map(|_| crate::mock_server::MockResponse {
+                status: 200,
+                headers: vec![("Content-Type", "image/jpeg".to_string())],
+                body: b"\xff\xd8jpeg".to_vec(),
+            })
+            .collect();
+        let server = crate::mock_server::MockServer::start(responses).await;
+        let apple_music_downloader =
+            crate::AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        let artwork = |i| Artwork {
+            width: 1000,
+            height: 1000,
+            ..Artwork::from_rendered_url(&format!(
+                "{}/image/thumb/{i}/source/100x100bb.jpg",
+                server.url
+            ))
+        };
+        for i in (0..=crate::ARTWORK_CACHE_SIZE).chain([0]) {
+            apple_music_downloader
+                .get_artwork(&artwork(i), 100, 100, ArtworkFormat::Jpg)
+                .await
+                .unwrap();
+        }
+        // The first artwork is evicted by the later ones and downloaded again.
+        let requests = server.requests();
+        assert_eq!(requests.len(), crate::ARTWORK_CACHE_SIZE + 2);
+        assert_eq!(
+            requests[0].path,
+            requests[crate::ARTWORK_CACHE_SIZE + 1].path
+        );
+    }
+}
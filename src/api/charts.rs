@@ -0,0 +1,120 @@
+use super::albums::Albums;
+use super::music_videos::MusicVideos;
+use super::playlists::Playlists;
+use super::songs::Songs;
+
+/// The resource types a chart can be requested for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartType {
+    Albums,
+    MusicVideos,
+    Playlists,
+    Songs,
+}
+
+impl ChartType {
+    /// The value of the `types` query parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChartType::Albums => "albums",
+            ChartType::MusicVideos => "music-videos",
+            ChartType::Playlists => "playlists",
+            ChartType::Songs => "songs",
+        }
+    }
+}
+
+/// The charts for each requested resource type.
+/// A storefront may return several charts per type, e.g. most-played and daily top charts.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct Charts {
+    #[serde(default)]
+    pub songs: Vec<Chart<Songs>>,
+    #[serde(default)]
+    pub albums: Vec<Chart<Albums>>,
+    #[serde(default)]
+    pub playlists: Vec<Chart<Playlists>>,
+    #[serde(default, rename = "music-videos")]
+    pub music_videos: Vec<Chart<MusicVideos>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Chart<T> {
+    /// The identifier for the chart, e.g. most-played.
+    pub chart: String,
+    /// The localized name for the chart.
+    pub name: String,
+    /// The identifier of the chart used for ordering.
+    pub order_id: Option<String>,
+    /// The relative location to fetch the chart directly.
+    pub href: Option<String>,
+    /// The relative location to request the next page of the chart, if more resources exist.
+    pub next: Option<String>,
+    /// The resources of the chart, in chart order.
+    pub data: Vec<T>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChartType;
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_charts() {
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "results": {
+                    "albums": [{
+                        "chart": "most-played",
+                        "name": "Top Albums",
+                        "orderId": "most-played:albums",
+                        "href": "/v1/catalog/jp/charts?chart=most-played&genre=27&limit=1&types=albums",
+                        "next": "/v1/catalog/jp/charts?chart=most-played&genre=27&offset=1&types=albums",
+                        "data": [{ "id": "1214782644", "type": "albums", "href": "/v1/catalog/jp/albums/1214782644" }]
+                    }]
+                }
+            }),
+        ), (200, serde_json::json!({ "results": {} }))])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        apple_music.set_store_front("jp");
+        let charts = apple_music
+            .get_charts(
+                &[ChartType::Albums],
+                Some("most-played"),
+                Some("27"),
+                None,
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert!(charts.songs.is_empty());
+        assert_eq!(charts.albums[0].data[0].id, "1214782644");
+        assert!(charts.albums[0].next.is_some());
+        assert_eq!(
+            server.requests()[0].path,
+            "/v1/catalog/jp/charts?types=albums&offset=0&limit=1&chart=most-played&genre=27"
+        );
+
+        // The parameters are encoded.
+        let charts = apple_music
+            .get_charts(
+                &[ChartType::Albums, ChartType::Songs],
+                Some("a&b c"),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(charts.albums.is_empty());
+        assert_eq!(
+            server.requests()[1].path,
+            "/v1/catalog/jp/charts?types=albums%2Csongs&offset=0&limit=20&chart=a%26b+c"
+        );
+    }
+}
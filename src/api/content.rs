@@ -0,0 +1,120 @@
+use super::albums::Albums;
+use super::curators::{AppleCurators, Curators};
+use super::library_albums::LibraryAlbums;
+use super::library_music_video::LibraryMusicVideos;
+use super::library_playlists::LibraryPlaylists;
+use super::library_songs::LibrarySongs;
+use super::music_videos::MusicVideos;
+use super::playlists::Playlists;
+use super::record_labels::RecordLabels;
+use super::songs::Songs;
+use super::stations::Stations;
+
+/// A resource in a collection that mixes resource types, such as recently played resources.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
+pub enum Content {
+    #[serde(rename = "albums")]
+    Albums(Albums),
+    #[serde(rename = "playlists")]
+    Playlists(Playlists),
+    #[serde(rename = "songs")]
+    Songs(Songs),
+    #[serde(rename = "music-videos")]
+    MusicVideos(MusicVideos),
+    #[serde(rename = "stations")]
+    Stations(Stations),
+    #[serde(rename = "record-labels")]
+    RecordLabels(RecordLabels),
+    #[serde(rename = "curators")]
+    Curators(Curators),
+    #[serde(rename = "apple-curators")]
+    AppleCurators(AppleCurators),
+    #[serde(rename = "library-albums")]
+    LibraryAlbums(LibraryAlbums),
+    #[serde(rename = "library-playlists")]
+    LibraryPlaylists(LibraryPlaylists),
+    #[serde(rename = "library-songs")]
+    LibrarySongs(LibrarySongs),
+    #[serde(rename = "library-music-videos")]
+    LibraryMusicVideos(LibraryMusicVideos),
+    /// A resource type that is not modelled, e.g. uploaded-videos.
+    #[serde(other)]
+    Other,
+}
+
+/// A song or music video in a collection that mixes catalog and library tracks.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
+pub enum Track {
+    #[serde(rename = "songs")]
+    Songs(Songs),
+    #[serde(rename = "music-videos")]
+    MusicVideos(MusicVideos),
+    #[serde(rename = "library-songs")]
+    LibrarySongs(LibrarySongs),
+    #[serde(rename = "library-music-videos")]
+    LibraryMusicVideos(LibraryMusicVideos),
+}
+
+impl Track {
+    /// Returns the catalog identifier of the track, which is what `get_webplayback` and
+    /// `get_songs` expect. Library tracks without a catalog counterpart, e.g. uploads, return `None`.
+    pub fn catalog_id(&self) -> Option<String> {
+        match self {
+            Track::Songs(song) => Some(song.id.clone()),
+            Track::MusicVideos(music_video) => Some(music_video.id.clone()),
+            Track::LibrarySongs(song) => song
+                .attributes
+                .as_ref()
+                .and_then(|attributes| attributes.play_params.as_ref())
+                .and_then(|play_params| play_params.catalog_id.clone()),
+            Track::LibraryMusicVideos(music_video) => music_video
+                .attributes
+                .as_ref()
+                .and_then(|attributes| attributes.play_params.as_ref())
+                .and_then(|play_params| play_params.catalog_id.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Content, Track};
+
+    #[test]
+    fn test_track_catalog_id() {
+        let tracks: Vec<Track> = serde_json::from_value(serde_json::json!([
+            { "id": "1214782673", "type": "songs", "href": "/v1/catalog/jp/songs/1214782673" },
+            {
+                "id": "i.4YaW8xQu2Nx3m",
+                "type": "library-songs",
+                "href": "/v1/me/library/songs/i.4YaW8xQu2Nx3m",
+                "attributes": {
+                    "artistName": "TORIENA",
+                    "artwork": { "width": 1200, "height": 1200, "url": "https://example.com/{w}x{h}bb.jpg" },
+                    "discNumber": 1,
+                    "durationInMillis": 201000,
+                    "genreNames": ["Electronic"],
+                    "hasLyrics": true,
+                    "name": "サイレンは彼方より",
+                    "playParams": { "id": "i.4YaW8xQu2Nx3m", "kind": "song", "isLibrary": true, "catalogId": "1214782673" },
+                    "trackNumber": 1
+                }
+            }
+        ]))
+        .unwrap();
+        assert_eq!(tracks[0].catalog_id().as_deref(), Some("1214782673"));
+        assert_eq!(tracks[1].catalog_id().as_deref(), Some("1214782673"));
+
+        let contents: Vec<Content> = serde_json::from_value(serde_json::json!([
+            { "id": "ra.985484166", "type": "stations", "href": "/v1/catalog/us/stations/ra.985484166" },
+            { "id": "1234", "type": "uploaded-videos", "href": "/v1/catalog/us/uploaded-videos/1234" }
+        ]))
+        .unwrap();
+        assert!(matches!(contents[0], Content::Stations(_)));
+        assert!(matches!(contents[1], Content::Other));
+    }
+}
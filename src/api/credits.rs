@@ -0,0 +1,77 @@
+use super::artwork::Artwork;
+use super::resource::{Relationship, Resource};
+
+/// A resource object that represents a section of song credits, e.g. Performers. The type is always credits.
+pub type Credits = Resource<Attributes, Relationships>;
+
+/// A resource object that represents a person in the credits of a song. The type is always credit-artists.
+pub type CreditArtists = Resource<CreditArtistAttributes>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The localized title of the credits section, e.g. Performers or Production & Engineering.
+    pub title: String,
+    /// The kind of the credits section, e.g. performer, composer or production.
+    pub kind: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The people credited in the section.
+    #[serde(rename = "credit-artists")]
+    pub credit_artists: Option<Relationship<CreditArtists>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreditArtistAttributes {
+    /// The name of the person.
+    pub name: String,
+    /// The localized roles of the person, e.g. Vocals, Producer or Mixing Engineer.
+    #[serde(default)]
+    pub role_names: Vec<String>,
+    /// The artwork of the person, when linked to an artist.
+    pub artwork: Option<Artwork>,
+    /// The identifier of the catalog artist, when linked to an artist.
+    pub linked_artist_id: Option<String>,
+}
+
+/// A person of the song credits with their roles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credit {
+    /// The title of the credits section, e.g. Performers.
+    pub section: String,
+    /// The name of the person.
+    pub name: String,
+    /// The roles of the person, e.g. Vocals or Producer.
+    pub roles: Vec<String>,
+}
+
+impl Credits {
+    /// Returns the people credited in the section.
+    pub fn people(&self) -> Vec<Credit> {
+        let section = self
+            .attributes
+            .as_ref()
+            .map(|attributes| attributes.title.clone())
+            .unwrap_or_default();
+        self.relationships
+            .as_ref()
+            .and_then(|relationships| relationships.credit_artists.as_ref())
+            .map(|credit_artists| {
+                credit_artists
+                    .data
+                    .iter()
+                    .filter_map(|credit_artist| credit_artist.attributes.as_ref())
+                    .map(|attributes| Credit {
+                        section: section.clone(),
+                        name: attributes.name.clone(),
+                        roles: attributes.role_names.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
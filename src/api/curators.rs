@@ -0,0 +1,93 @@
+use super::artwork::Artwork;
+use super::editorial_notes::EditorialNotes;
+use super::playlists::Playlists;
+use super::resource::{Relationship, Resource};
+
+/// A resource object that represents a curator, e.g. a brand or an external publisher. The type is always curators.
+pub type Curators = Resource<Attributes, Relationships>;
+
+/// A resource object that represents an Apple curator, e.g. an Apple Music editor, genre or show. The type is always apple-curators.
+pub type AppleCurators = Resource<AppleCuratorAttributes, Relationships>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The curator artwork.
+    pub artwork: Option<Artwork>,
+    /// The notes about the curator.
+    pub editorial_notes: Option<EditorialNotes>,
+    /// The localized name of the curator.
+    pub name: String,
+    /// The URL for sharing the curator in Apple Music.
+    pub url: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AppleCuratorAttributes {
+    /// The curator artwork.
+    pub artwork: Option<Artwork>,
+    /// The notes about the curator that appear in the Apple Music catalog.
+    pub editorial_notes: Option<EditorialNotes>,
+    /// The type of curator.
+    /// Possible Values: Curator, Genre, Show
+    pub kind: Option<String>,
+    /// The localized name of the curator.
+    pub name: String,
+    /// The localized shortened name of the curator.
+    pub short_name: Option<String>,
+    /// The name of the show’s host, if the curator is a show.
+    pub show_host_name: Option<String>,
+    /// The URL for sharing the curator in Apple Music.
+    pub url: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The playlists of the curator. By default, playlists includes identifiers only.
+    pub playlists: Option<Relationship<Playlists>>,
+}
+
+/// The curator of a playlist.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum Curator {
+    #[serde(rename = "curators")]
+    Curators(Curators),
+    #[serde(rename = "apple-curators")]
+    AppleCurators(AppleCurators),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_apple_curator_playlists() {
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "data": [{
+                    "id": "pl.f4d106fed2bd41149aaacabb233eb5eb",
+                    "type": "playlists",
+                    "href": "/v1/catalog/us/playlists/pl.f4d106fed2bd41149aaacabb233eb5eb"
+                }]
+            }),
+        )])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        let playlists = apple_music
+            .get_apple_curator_playlists("976439548", None, None)
+            .await
+            .unwrap();
+        assert_eq!(playlists.data.len(), 1);
+        assert!(playlists.next.is_none());
+        assert_eq!(
+            server.requests()[0].path,
+            "/v1/catalog/us/apple-curators/976439548/playlists?offset=0&limit=25"
+        );
+    }
+}
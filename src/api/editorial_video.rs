@@ -0,0 +1,120 @@
+use super::artwork::Artwork;
+
+/// (Extended) The motion artwork of an album or playlist, requested with `extend=editorialVideo`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EditorialVideo {
+    /// The square motion artwork shown on the detail page.
+    pub motion_detail_square: Option<MotionVideo>,
+    /// The tall motion artwork shown on the detail page.
+    pub motion_detail_tall: Option<MotionVideo>,
+    /// The square motion artwork, in a 1:1 aspect ratio.
+    #[serde(rename = "motionSquareVideo1x1")]
+    pub motion_square_video_1x1: Option<MotionVideo>,
+    /// The tall motion artwork, in a 3:4 aspect ratio.
+    #[serde(rename = "motionTallVideo3x4")]
+    pub motion_tall_video_3x4: Option<MotionVideo>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MotionVideo {
+    /// The URL of the HLS master playlist of the video.
+    pub video: String,
+    /// The first frame of the video.
+    pub preview_frame: Option<Artwork>,
+}
+
+/// The shape of a motion artwork.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionArtworkVariant {
+    Square,
+    Tall,
+}
+
+impl MotionArtworkVariant {
+    /// Returns the file name of the motion artwork, e.g. `square_animated_artwork.mp4`.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            MotionArtworkVariant::Square => "square_animated_artwork.mp4",
+            MotionArtworkVariant::Tall => "tall_animated_artwork.mp4",
+        }
+    }
+}
+
+impl EditorialVideo {
+    /// Returns the motion artwork of a shape, preferring the detail page variant.
+    pub fn get(&self, variant: MotionArtworkVariant) -> Option<&MotionVideo> {
+        match variant {
+            MotionArtworkVariant::Square => self
+                .motion_detail_square
+                .as_ref()
+                .or(self.motion_square_video_1x1.as_ref()),
+            MotionArtworkVariant::Tall => self
+                .motion_detail_tall
+                .as_ref()
+                .or(self.motion_tall_video_3x4.as_ref()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MotionArtworkVariant;
+    use crate::api::albums::Albums;
+    use crate::mock_server::{MockResponse, MockServer};
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_download_motion_artwork() {
+        let response = |body: &str| MockResponse {
+            status: 200,
+            headers: vec![],
+            body: body.as_bytes().to_vec(),
+        };
+        let server = MockServer::start(vec![
+            response("#EXTM3U\n#EXT-X-TARGETDURATION:4\n#EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:4.0,\nseg1.m4s\n#EXTINF:4.0,\nseg2.m4s\n#EXT-X-ENDLIST\n"),
+            response("ftyp"),
+            response("moof1"),
+            response("moof2"),
+        ])
+        .await;
+        let album: Albums = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "type": "albums",
+            "href": "/v1/catalog/us/albums/1",
+            "attributes": {
+                "artistName": "Artist",
+                "artwork": { "width": 3000, "height": 3000, "url": "https://example.com/{w}x{h}bb.jpg" },
+                "editorialVideo": {
+                    "motionDetailSquare": {
+                        "video": format!("{}/square/P.m3u8", server.url),
+                        "previewFrame": { "width": 3840, "height": 3840, "url": "https://example.com/{w}x{h}bb.{f}" }
+                    }
+                },
+                "genreNames": [],
+                "isCompilation": false,
+                "isComplete": true,
+                "isMasteredForItunes": false,
+                "isSingle": false,
+                "name": "Album",
+                "trackCount": 1,
+                "url": "https://music.apple.com/us/album/1"
+            }
+        }))
+        .unwrap();
+        let editorial_video = album.attributes.unwrap().editorial_video.unwrap();
+        assert!(editorial_video.get(MotionArtworkVariant::Tall).is_none());
+
+        let dir = std::env::temp_dir().join(format!("ramdl-motion-{}", std::process::id()));
+        let apple_music_downloader = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        let path = apple_music_downloader
+            .download_motion_artwork(&editorial_video, MotionArtworkVariant::Square, &dir)
+            .await
+            .unwrap();
+        assert_eq!(path, dir.join("square_animated_artwork.mp4"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"ftypmoof1moof2");
+        assert_eq!(server.requests()[1].path, "/square/init.mp4");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,16 +1,7 @@
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Genre {
-    /// The identifier for the genre.
-    pub id: String,
-    /// This value must always be genres.
-    #[serde(rename = "type")]
-    pub type_field: String,
-    /// The relative location for the genre resource.
-    pub href: String,
-    /// The attributes for the genre.
-    pub attributes: Attributes,
-}
+use super::resource::Resource;
+
+/// A resource object that represents a music genre. The type is always genres.
+pub type Genre = Resource<Attributes>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
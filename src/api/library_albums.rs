@@ -1,24 +1,13 @@
 use super::albums::Albums;
 use super::artwork::Artwork;
-use super::play_parameters::PlayParameters;
 use super::library_artists::LibraryArtists;
-use super::library_songs::LibrarySongs;
 use super::library_music_video::LibraryMusicVideos;
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct LibraryAlbums {
-    /// The identifier for the library album.
-    pub id: String,
-    /// This value is always library-albums.
-    #[serde(rename = "type")]
-    pub type_: String,
-    /// The relative location for the library album resource.
-    pub href: String,
-    /// The attributes for the library album.
-    pub attributes: Option<Attributes>,
-    /// The relationships for the library album.
-    pub relationships: Option<Relationships>,
-}
+use super::library_songs::LibrarySongs;
+use super::play_parameters::PlayParameters;
+use super::resource::{Relationship, Resource};
+
+/// A resource object that represents a library album. The type is always library-albums.
+pub type LibraryAlbums = Resource<Attributes, Relationships>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -49,62 +38,21 @@ pub struct Attributes {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Relationships {
-     /// The library artists associated with the album. By default, artists not included.
-     /// Fetch limits: 10 default, 10 maximum
-     pub artists: Option<LibraryAlbumsArtistsRelationship>,
-     /// The album in the Apple Music catalog the library album is associated with, when known.
-     /// Fetch limits: None (associated with at most one catalog album)
-     pub catalog: Option<LibraryAlbumsCatalogRelationship>,
-     /// The library songs and library music videos on the album. Only available when fetching single library album resource by ID. By default, tracks includes objects.
-     /// Fetch limits: 300 default, 300 maximum.
-     pub tracks: Option<LibraryAlbumsTracksRelationship>,
+    /// The library artists associated with the album. By default, artists not included.
+    /// Fetch limits: 10 default, 10 maximum
+    pub artists: Option<Relationship<LibraryArtists>>,
+    /// The album in the Apple Music catalog the library album is associated with, when known.
+    /// Fetch limits: None (associated with at most one catalog album)
+    pub catalog: Option<Relationship<Albums>>,
+    /// The library songs and library music videos on the album. Only available when fetching single library album resource by ID. By default, tracks includes objects.
+    /// Fetch limits: 300 default, 300 maximum.
+    pub tracks: Option<Relationship<LibraryTracks>>,
 }
 
- #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
- #[serde(rename_all = "camelCase")]
- pub struct LibraryAlbumsArtistsRelationship {
-     /// The relative location to fetch the relationship directly.
-     pub href: String,
-     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-     pub next: Option<String>,
-     /// The library artists for the library album.
-     pub data: Vec<LibraryArtists>,
- }
-
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct LibraryAlbumsCatalogRelationship {
-    /// The relative location to fetch the relationship directly.
-    pub href: String,
-    /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-    pub next: Option<String>,
-    /// The album from the Apple Music catalog associated with the library album, if any.
-    pub data: Vec<Albums>,
-}
-
- #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
- #[serde(rename_all = "camelCase")]
- pub struct LibraryAlbumsTracksRelationship {
-     /// The relative location to fetch the relationship directly.
-     pub href: String,
-     /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
-     pub next: Option<String>,
-     /// The songs and music videos from the library album’s tracklist added to the user’s library.
-     pub data: Vec<LibraryTracks>,
-     pub meta: Meta
- }
-
-
- #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Meta {
-    pub total: i64,
+#[serde(rename_all = "kebab-case")]
+#[serde(tag = "type")]
+pub enum LibraryTracks {
+    LibraryMusicVideos(LibraryMusicVideos),
+    LibrarySongs(LibrarySongs),
 }
-
- #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
- #[serde(rename_all = "kebab-case")]
- #[serde(tag = "type")]
- pub enum LibraryTracks {
-     LibraryMusicVideos(LibraryMusicVideos),
-     LibrarySongs(LibrarySongs),
- }
@@ -1,12 +1,7 @@
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct LibraryArtists {
-    pub id: String, 
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub href: String,
-    pub attributes: Attributes,
-}
+use super::resource::Resource;
+
+/// A resource object that represents a library artist. The type is always library-artists.
+pub type LibraryArtists = Resource<Attributes>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
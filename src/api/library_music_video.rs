@@ -1,21 +1,9 @@
 use super::artwork::Artwork;
-use super::editorial_notes::EditorialNotes;
 use super::play_parameters::PlayParameters;
-use super::previews::Preview;
+use super::resource::Resource;
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct LibraryMusicVideos {
-    /// The identifier for the music video.
-    pub id: String,
-    /// This value is always music-videos.
-    #[serde(rename = "type")]
-    pub type_: String,
-    /// The relative location for the music video resource.
-    pub href: String,
-    /// The attributes for the music video.
-    pub attributes: Attributes,
-}
+/// A resource object that represents a library music video. The type is always library-music-videos.
+pub type LibraryMusicVideos = Resource<Attributes>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
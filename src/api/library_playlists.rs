@@ -0,0 +1,176 @@
+use super::artwork::Artwork;
+use super::library_albums::LibraryTracks;
+use super::play_parameters::PlayParameters;
+use super::playlists::{DescriptionAttribute, Playlists};
+use super::resource::{Relationship, Resource, ResourceIdentifier};
+
+/// A resource object that represents a library playlist. The type is always library-playlists.
+pub type LibraryPlaylists = Resource<Attributes, Relationships>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The playlist artwork.
+    pub artwork: Option<Artwork>,
+    /// Indicates whether the playlist can be edited.
+    #[serde(default)]
+    pub can_edit: bool,
+    /// The date and time the playlist was added to the user’s library.
+    pub date_added: Option<String>,
+    /// A description of the playlist.
+    pub description: Option<DescriptionAttribute>,
+    /// Indicates whether the playlist has a representation in the Apple Music catalog.
+    #[serde(default)]
+    pub has_catalog: bool,
+    /// Indicates whether the playlist is publicly visible.
+    #[serde(default)]
+    pub is_public: bool,
+    /// The localized name of the playlist.
+    pub name: String,
+    /// When present, this attribute indicates that tracks of the playlist are available to play.
+    pub play_params: Option<PlayParameters>,
+    /// (Extended) The resource types that are present in the tracks of the playlist.
+    /// Possible Values: library-music-videos, library-songs
+    pub track_types: Option<Vec<String>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The playlist in the Apple Music catalog the library playlist is associated with, when known.
+    pub catalog: Option<Relationship<Playlists>>,
+    /// The library songs and library music videos included in the playlist. Only available when fetching a single library playlist resource by ID.
+    pub tracks: Option<Relationship<LibraryTracks>>,
+}
+
+/// The request body for creating a library playlist.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct LibraryPlaylistCreationRequest {
+    /// The attributes of the new playlist.
+    pub attributes: CreationAttributes,
+    /// The tracks the playlist is created with, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relationships: Option<CreationRelationships>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CreationAttributes {
+    /// The name of the playlist.
+    pub name: String,
+    /// The description of the playlist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CreationRelationships {
+    /// The tracks of the playlist.
+    pub tracks: LibraryPlaylistTracksRequest,
+}
+
+/// The request body for adding tracks to a library playlist.
+/// Tracks are identified as songs, music-videos, library-songs or library-music-videos.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct LibraryPlaylistTracksRequest {
+    pub data: Vec<ResourceIdentifier>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::resource::ResourceIdentifier;
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    fn apple_music(server: &MockServer) -> AppleMusicDownloader {
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        apple_music
+    }
+
+    #[tokio::test]
+    async fn test_add_to_library() {
+        let server = MockServer::start_json(vec![(202, serde_json::Value::Null)]).await;
+        let apple_music = apple_music(&server);
+        apple_music.add_to_library(&[]).await.unwrap();
+        apple_music
+            .add_to_library(&[
+                ResourceIdentifier::new("songs", "1214782673"),
+                ResourceIdentifier::new("albums", "1214782644"),
+                ResourceIdentifier::new("songs", "1428083880"),
+            ])
+            .await
+            .unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(
+            request.path,
+            "/v1/me/library?ids[songs]=1214782673,1428083880&ids[albums]=1214782644"
+        );
+        assert_eq!(request.header("media-user-token"), Some("Asc+xxx"));
+    }
+
+    #[tokio::test]
+    async fn test_create_library_playlist() {
+        let server = MockServer::start_json(vec![(
+            201,
+            serde_json::json!({
+                "data": [{
+                    "id": "p.MoGJYM3CYXW09B",
+                    "type": "library-playlists",
+                    "href": "/v1/me/library/playlists/p.MoGJYM3CYXW09B",
+                    "attributes": { "name": "Imported", "canEdit": true, "isPublic": false }
+                }]
+            }),
+        )])
+        .await;
+        let playlist = apple_music(&server)
+            .create_library_playlist(
+                "Imported",
+                Some("From another service"),
+                &[ResourceIdentifier::new("songs", "1214782673")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(playlist.id, "p.MoGJYM3CYXW09B");
+        assert!(playlist.attributes.unwrap().can_edit);
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/v1/me/library/playlists");
+        assert_eq!(
+            request.json(),
+            serde_json::json!({
+                "attributes": { "name": "Imported", "description": "From another service" },
+                "relationships": { "tracks": { "data": [{ "id": "1214782673", "type": "songs" }] } }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_tracks_to_library_playlist() {
+        let server = MockServer::start_json(vec![
+            (204, serde_json::Value::Null),
+            (403, serde_json::json!({ "errors": [] })),
+        ])
+        .await;
+        let apple_music = apple_music(&server);
+        let tracks = [ResourceIdentifier::new("library-songs", "i.4YaW8xQu2Nx3m")];
+        apple_music
+            .add_tracks_to_library_playlist("p.MoGJYM3CYXW09B", &tracks)
+            .await
+            .unwrap();
+        assert!(apple_music
+            .add_tracks_to_library_playlist("p.MoGJYM3CYXW09B", &tracks)
+            .await
+            .is_err());
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.path,
+            "/v1/me/library/playlists/p.MoGJYM3CYXW09B/tracks"
+        );
+        assert_eq!(
+            request.json(),
+            serde_json::json!({ "data": [{ "id": "i.4YaW8xQu2Nx3m", "type": "library-songs" }] })
+        );
+    }
+}
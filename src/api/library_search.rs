@@ -0,0 +1,107 @@
+use super::library_albums::LibraryAlbums;
+use super::library_artists::LibraryArtists;
+use super::library_music_video::LibraryMusicVideos;
+use super::library_playlists::LibraryPlaylists;
+use super::library_songs::LibrarySongs;
+use super::resource::Relationship;
+
+/// The resource types that can be searched for in the user’s library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LibrarySearchType {
+    Albums,
+    Artists,
+    MusicVideos,
+    Playlists,
+    Songs,
+}
+
+impl LibrarySearchType {
+    /// Every library resource type, in the order used by `search_library`.
+    pub const ALL: [LibrarySearchType; 5] = [
+        LibrarySearchType::Songs,
+        LibrarySearchType::Albums,
+        LibrarySearchType::Artists,
+        LibrarySearchType::Playlists,
+        LibrarySearchType::MusicVideos,
+    ];
+
+    /// The value of the `types` query parameter, which is also the key of the results.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LibrarySearchType::Albums => "library-albums",
+            LibrarySearchType::Artists => "library-artists",
+            LibrarySearchType::MusicVideos => "library-music-videos",
+            LibrarySearchType::Playlists => "library-playlists",
+            LibrarySearchType::Songs => "library-songs",
+        }
+    }
+}
+
+/// The results of a library search. A type without matches is `None`.
+/// Each result carries `next`, the relative location of the next page, when more matches exist.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct LibrarySearchResults {
+    #[serde(rename = "library-songs")]
+    pub songs: Option<Relationship<LibrarySongs>>,
+    #[serde(rename = "library-albums")]
+    pub albums: Option<Relationship<LibraryAlbums>>,
+    #[serde(rename = "library-artists")]
+    pub artists: Option<Relationship<LibraryArtists>>,
+    #[serde(rename = "library-playlists")]
+    pub playlists: Option<Relationship<LibraryPlaylists>>,
+    #[serde(rename = "library-music-videos")]
+    pub music_videos: Option<Relationship<LibraryMusicVideos>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LibrarySearchType;
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_search_library() {
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "results": {
+                    "library-artists": {
+                        "href": "/v1/me/library/search?limit=1&term=miku&types=library-artists",
+                        "next": "/v1/me/library/search?offset=6&term=miku&types=library-artists",
+                        "data": [{
+                            "id": "r.5a2CKHn",
+                            "type": "library-artists",
+                            "href": "/v1/me/library/artists/r.5a2CKHn",
+                            "attributes": { "name": "Hatsune Miku" }
+                        }]
+                    }
+                },
+                "meta": { "results": { "order": ["library-artists"], "rawOrder": ["library-artists"] } }
+            }),
+        )])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        let results = apple_music
+            .search_library(
+                "miku",
+                &[LibrarySearchType::Artists, LibrarySearchType::Songs],
+                Some(5),
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            server.requests()[0].path,
+            "/v1/me/library/search?term=miku&types=library-artists%2Clibrary-songs&offset=5&limit=1"
+        );
+        assert!(results.songs.is_none());
+        let artists = results.artists.unwrap();
+        assert_eq!(artists.data[0].id, "r.5a2CKHn");
+        assert_eq!(
+            artists.data[0].attributes.as_ref().unwrap().name,
+            "Hatsune Miku"
+        );
+        assert!(artists.next.is_some());
+    }
+}
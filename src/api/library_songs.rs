@@ -1,22 +1,10 @@
 use super::artwork::Artwork;
 use super::editorial_notes::EditorialNotes;
 use super::play_parameters::PlayParameters;
+use super::resource::Resource;
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct LibrarySongs {
-    /// The identifier for the song.
-    pub id: String,
-    /// This value is always library-songs.
-    #[serde(rename = "type")]
-    pub type_: Option<String>,
-    /// The relative location for the song resource.
-    pub href: String,
-    /// The attributes for the song.
-    pub attributes: Attributes,
-    ///// The relationships for the library music vide.
-    //pub relationships: Option<Relationships>,
-}
+/// A resource object that represents a library song. The type is always library-songs.
+pub type LibrarySongs = Resource<Attributes>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -6,22 +6,38 @@ pub mod albums;
 pub mod artists;
 /// An object that represents artwork.
 pub mod artwork;
+/// /v1/catalog/:store_front/charts
+pub mod charts;
+/// Resources of mixed types, e.g. recently played resources or tracks.
+pub mod content;
+/// A resource object that represents the credits of a song.
+pub mod credits;
+/// /v1/catalog/:store_front/curators/:id and /v1/catalog/:store_front/apple-curators/:id
+pub mod curators;
 /// An object that represents a notes attribute.
 /// ## Discussion
 /// Notes may include XML tags for formatting (&lt;b&gt; for bold, &lt;i&gt; for italic, or &lt;br&gt; for line break) and special characters (&amp;amp; for &, &amp;lt; for <, &amp;gt; for >, &amp;apos; for ‘, and &amp;quot; for “).
 pub mod editorial_notes;
+/// An object that represents the motion artwork of an album or playlist.
+pub mod editorial_video;
 /// A resource object that represents a music genre.
 pub mod genres;
 /// A resource object that represents a library album.
 pub mod library_albums;
 /// A resource object that represents a library artist.
 pub mod library_artists;
-/// A resource object that represents a library song.
-pub mod library_songs;
 /// A resource object that represents a library music video.
 pub mod library_music_video;
+/// A resource object that represents a library playlist.
+pub mod library_playlists;
+/// /v1/me/library/search
+pub mod library_search;
+/// A resource object that represents a library song.
+pub mod library_songs;
 /// /v1/catalog/:store_front/songs/:song_id?include=lyrics,syllable-lyrics
 pub mod lyrics;
+/// /v1/me/music-summaries
+pub mod music_summaries;
 /// /v1/catalog/:store_front/music-videos/:id
 pub mod music_videos;
 /// An object that represents play parameters for resources.
@@ -30,9 +46,21 @@ pub mod play_parameters;
 pub mod playlists;
 /// An object that represents a preview for resources.
 pub mod previews;
+/// The include, extend and views query parameters of resource requests.
+pub mod query;
+/// /v1/catalog/:store_front/radio-shows/:id
+pub mod radio_shows;
+/// /v1/me/recommendations
+pub mod recommendations;
+/// /v1/catalog/:store_front/record-labels/:id
+pub mod record_labels;
+/// The resource and relationship objects shared by every resource type.
+pub mod resource;
 /// /v1/catalog/:store_front/search
 pub mod search;
 /// /v1/catalog/:store_front/songs/:id?include=albums
 pub mod songs;
+/// /v1/catalog/:store_front/stations/:id
+pub mod stations;
 /// WEBPLAYBACK_API_URL
 pub mod webplayback;
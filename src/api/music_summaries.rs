@@ -0,0 +1,21 @@
+use super::playlists::Playlists;
+use super::resource::{Relationship, Resource};
+
+/// A resource object that represents a user's Apple Music Replay summary. The type is always music-summaries.
+pub type MusicSummaries = Resource<Attributes, Relationships>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The period of the summary, e.g. year.
+    pub period: Option<String>,
+    /// The year of the summary.
+    pub year: Option<i32>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The Replay playlist of the summary.
+    pub playlist: Option<Relationship<Playlists>>,
+}
@@ -1,22 +1,16 @@
+use super::albums::Albums;
+use super::artists::Artists;
 use super::artwork::Artwork;
 use super::editorial_notes::EditorialNotes;
+use super::genres::Genre;
+use super::library_music_video::LibraryMusicVideos;
 use super::play_parameters::PlayParameters;
 use super::previews::Preview;
+use super::resource::{Relationship, Resource, View};
+use super::songs::Songs;
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct MusicVideos {
-    /// The identifier for the music video.
-    pub id: String,
-    /// This value is always music-videos.
-    pub type_: String,
-    /// The relative location for the music video resource.
-    pub href: String,
-    /// The attributes for the music video.
-    pub attributes: Attributes,
-    /// The relationships for the music video.
-    pub relationships: Option<Relationships>,
-}
+/// A resource object that represents a music video. The type is always music-videos.
+pub type MusicVideos = Resource<Attributes, Relationships, Views>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -39,8 +33,10 @@ pub struct Attributes {
     /// The music video’s associated genres.
     pub genre_names: Vec<String>,
     /// Whether the music video has 4K content.
+    #[serde(default)]
     pub has_4k: bool,
     ///  Whether the music video has HDR10-encoded content.
+    #[serde(default)]
     pub has_hdr: bool,
     /// The International Standard Recording Code (ISRC) for the music video.
     pub isrc: Option<String>,
@@ -50,6 +46,7 @@ pub struct Attributes {
     /// The value map may be used to initiate playback. Previews of the music video may be available with or without an Apple Music subscription.
     pub play_params: Option<PlayParameters>,
     /// The preview assets for the music video.
+    #[serde(default)]
     pub previews: Vec<Preview>,
     /// The release date of the music video, when known, in YYYY-MM-DD or YYYY format.
     /// Prerelease music videos may have an expected release date in the future.
@@ -70,26 +67,31 @@ pub struct Attributes {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Relationships {
-    // /// The albums associated with the music video. By default, albums includes identifiers only.
-    // pub albums: Option<MusicVideosAlbumsRelationship>,
-    // /// The artists associated with the music video. By default, artists includes identifiers only.
-    // pub artists: Option<MusicVideosArtistsRelationship>,
-    // /// The genres associated with the music video. By default, genres not included.
-    // pub genres: Option<MusicVideosGenresRelationship>,
-    // /// The library of a music video if added to library.
-    // pub library: Option<MusicVideosLibraryRelationship>,
-    // /// The songs associated with the music video.
-    // pub songs: Option<MusicVideosSongsRelationship>,
+    /// The albums associated with the music video. By default, albums includes identifiers only.
+    pub albums: Option<Relationship<Albums>>,
+    /// The artists associated with the music video. By default, artists includes identifiers only.
+    pub artists: Option<Relationship<Artists>>,
+    /// The genres associated with the music video. By default, genres not included.
+    pub genres: Option<Relationship<Genre>>,
+    /// The library of a music video if added to library.
+    pub library: Option<Relationship<LibraryMusicVideos>>,
+    /// The songs associated with the music video.
+    pub songs: Option<Relationship<Songs>>,
 }
 
-// /// A relationship from the music video to its albums.
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// #[serde(rename_all = "camelCase")]
-// pub struct MusicVideosAlbumsRelationship {
-//     /// A relative location for the relationship.
-//     pub href: Option<String>,
-//     /// A relative cursor to fetch the next paginated collection of resources in the relationship if more exist.
-//     pub next: Option<String>,
-//     /// The albums associated with the music video, if any.
-//     pub data: Vec<Albums>,
-// }
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Views {
+    /// The other music videos of the artist.
+    pub more_by_artist: Option<View<MusicVideos>>,
+    /// The other music videos in the genre of the music video.
+    pub more_in_genre: Option<View<MusicVideos>>,
+}
+
+impl MusicVideos {
+    /// Returns the artwork of the music video, if the attributes are included.
+    /// Use `AppleMusicDownloader::get_artwork` to download it.
+    pub fn artwork(&self) -> Option<&Artwork> {
+        Some(&self.attributes.as_ref()?.artwork)
+    }
+}
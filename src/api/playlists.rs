@@ -1,22 +1,14 @@
 use super::artwork::Artwork;
+use super::curators::Curator;
+use super::editorial_notes::EditorialNotes;
+use super::editorial_video::EditorialVideo;
 use super::music_videos::MusicVideos;
 use super::play_parameters::PlayParameters;
+use super::resource::{Relationship, Resource};
 use super::songs::Songs;
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Playlists {
-    /// The identifier for the playlist.
-    pub id: String,
-    /// This value is always playlists.
-    #[serde(rename = "type")]
-    pub type_: String,
-    /// The relative location for the playlist resource.
-    pub href: String,
-    /// The attributes for the playlist.
-    pub attributes: Attributes,
-    // /// The relationships for the playlist.
-    pub relationships: Relationships,
-}
+/// A resource object that represents a playlist. The type is always playlists.
+pub type Playlists = Resource<Attributes, Relationships>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -27,6 +19,10 @@ pub struct Attributes {
     pub curator_name: String,
     /// A description of the playlist.
     pub description: Option<DescriptionAttribute>,
+    /// The notes about the playlist that appear in the Apple Music catalog.
+    pub editorial_notes: Option<EditorialNotes>,
+    /// (Extended) The motion artwork of the playlist.
+    pub editorial_video: Option<EditorialVideo>,
     /// Indicates whether the playlist represents a popularity chart.
     pub is_chart: bool,
     /// The date the playlist was last modified.
@@ -51,7 +47,10 @@ pub struct Attributes {
     pub url: String,
     /// (Extended) The resource types that are present in the tracks of the playlists.  
     /// Possible Values: music-videos, songs
+    #[serde(default)]
     pub track_types: Vec<String>,
+    /// The audio traits of the playlist, e.g. lossless or atmos.
+    pub audio_traits: Option<Vec<String>>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -66,24 +65,14 @@ pub struct DescriptionAttribute {
 #[serde(rename_all = "camelCase")]
 pub struct Relationships {
     /// The songs and music videos included in the playlist. By default, tracks includes objects
-    pub tracks: Option<Tracks>,
-    // /// The curator that created the playlist. By default, curator includes identifiers only.
-    // pub curator: Option<Curator>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Tracks {
-    /// A relative location for the relationship.
-    pub href: Option<String>,
-    /// A relative cursor to fetch the next paginated collection of resources in the relationship if more exist.
-    pub next: Option<String>,
-    /// The data for the included tracks.
-    pub data: Vec<TrackData>,
+    pub tracks: Option<Relationship<TrackData>>,
+    /// The curator that created the playlist. By default, curator includes identifiers only.
+    pub curator: Option<Relationship<Curator>>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
 pub enum TrackData {
     #[serde(rename = "songs")]
     Songs(Songs),
@@ -91,12 +80,10 @@ pub enum TrackData {
     MusicVideos(MusicVideos),
 }
 
-// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-// pub struct Curator {
-//     /// A relative location for the relationship.
-//     pub href: Option<String>,
-//     /// A relative cursor to fetch the next paginated collection of resources in the relationship if more exist.
-//     pub next: Option<String>,
-//     /// The curator for the playlist.
-//     pub data: Vec<CuratorData>,
-// }
+impl Playlists {
+    /// Returns the artwork of the playlist, if the attributes are included.
+    /// Use `AppleMusicDownloader::get_artwork` to download it.
+    pub fn artwork(&self) -> Option<&Artwork> {
+        self.attributes.as_ref()?.artwork.as_ref()
+    }
+}
@@ -10,3 +10,148 @@ pub struct Preview {
     /// The HLS preview URL for the content.
     pub hls_url: Option<String>,
 }
+
+/// The source of a preview to download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewSource {
+    /// The preview file: an AAC file for songs or an MP4 file for music videos.
+    File,
+    /// The HLS preview, downloaded at its highest bandwidth with its audio rendition and remuxed
+    /// into a MP4 file. Only fragmented MP4 streams are supported.
+    Hls,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Preview, PreviewSource};
+    use crate::mock_server::{MockResponse, MockServer};
+    use crate::mp4::fixtures::{fragment, init_segment};
+    use crate::mp4::{find_box, mp4_box, parse_boxes};
+    use crate::tags::Tags;
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_download_preview() {
+        let mut m4a = mp4_box(b"ftyp", b"M4A \0\0\0\0");
+        m4a.extend(mp4_box(b"moov", &mp4_box(b"mvhd", &[0; 100])));
+        m4a.extend(mp4_box(b"mdat", b"aac"));
+        let server = MockServer::start(vec![MockResponse {
+            status: 200,
+            headers: vec![("Content-Type", "audio/x-m4a".to_string())],
+            body: m4a,
+        }])
+        .await;
+        let preview = Preview {
+            artwork: None,
+            url: format!("{}/preview.m4a", server.url),
+            hls_url: None,
+        };
+        let tags = Tags {
+            title: Some("Song".to_string()),
+            ..Default::default()
+        };
+        let path = std::env::temp_dir()
+            .join(format!("ramdl-preview-{}", std::process::id()))
+            .join("preview.m4a");
+        let apple_music_downloader = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music_downloader
+            .download_preview(&preview, PreviewSource::File, &tags, &path)
+            .await
+            .unwrap();
+        assert!(apple_music_downloader
+            .download_preview(&preview, PreviewSource::Hls, &tags, &path)
+            .await
+            .is_err());
+
+        let data = std::fs::read(&path).unwrap();
+        let ilst = find_box(&data, &[b"moov", b"udta", b"meta", b"ilst"])
+            .unwrap()
+            .unwrap();
+        assert!(ilst.data.windows(4).any(|w| w == b"Song"));
+        assert!(data.ends_with(b"mdataac"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_hls_preview() {
+        let master = "#EXTM3U\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio\",NAME=\"English\",DEFAULT=YES,URI=\"audio.m3u8\"\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=900000,CODECS=\"avc1.640028,mp4a.40.2\",AUDIO=\"audio\"\n\
+            video.m3u8\n";
+        let media = "#EXTM3U\n\
+            #EXT-X-TARGETDURATION:6\n\
+            #EXT-X-MAP:URI=\"init.mp4\"\n\
+            #EXTINF:6.0,\n\
+            segment.mp4\n\
+            #EXT-X-ENDLIST\n";
+        let ts_media = "#EXTM3U\n\
+            #EXT-X-TARGETDURATION:6\n\
+            #EXTINF:6.0,\n\
+            segment.ts\n\
+            #EXT-X-ENDLIST\n";
+        let response = |body: &[u8]| MockResponse {
+            status: 200,
+            headers: vec![],
+            body: body.to_vec(),
+        };
+        let init = init_segment(b"cenc", None);
+        let server = MockServer::start(vec![
+            response(master.as_bytes()),
+            response(media.as_bytes()),
+            response(&init),
+            response(&fragment(1, &[b"video"], None)),
+            response(media.as_bytes()),
+            response(&init),
+            response(&fragment(1, &[b"audio"], None)),
+            response(ts_media.as_bytes()),
+            response(b"\x47\x40\x00\x10"),
+        ])
+        .await;
+        let preview = Preview {
+            artwork: None,
+            url: format!("{}/preview.m4v", server.url),
+            hls_url: Some(format!("{}/P.m3u8", server.url)),
+        };
+        let tags = Tags {
+            title: Some("Music Video".to_string()),
+            ..Default::default()
+        };
+        let path = std::env::temp_dir()
+            .join(format!("ramdl-hls-preview-{}", std::process::id()))
+            .join("preview.m4v");
+        let apple_music_downloader = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music_downloader
+            .download_preview(&preview, PreviewSource::Hls, &tags, &path)
+            .await
+            .unwrap();
+        assert_eq!(server.requests()[4].path, "/audio.m3u8");
+
+        // The audio rendition is a second track of the remuxed file.
+        let data = std::fs::read(&path).unwrap();
+        let moov = find_box(&data, &[b"moov"]).unwrap().unwrap();
+        let track_ids: Vec<u8> = parse_boxes(moov.payload())
+            .unwrap()
+            .into_iter()
+            .filter(|b| &b.name == b"trak")
+            .map(|trak| trak.child(b"tkhd").unwrap().unwrap().payload()[15])
+            .collect();
+        assert_eq!(track_ids, [1, 2]);
+        let ilst = find_box(&data, &[b"moov", b"udta", b"meta", b"ilst"])
+            .unwrap()
+            .unwrap();
+        assert!(ilst.data.windows(11).any(|w| w == b"Music Video"));
+        assert!(data.ends_with(b"videoaudio"));
+
+        // MPEG-TS segments are rejected.
+        let preview = Preview {
+            hls_url: Some(format!("{}/ts.m3u8", server.url)),
+            ..preview
+        };
+        let error = apple_music_downloader
+            .download_preview(&preview, PreviewSource::Hls, &tags, &path)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("not fragmented MP4"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
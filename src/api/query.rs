@@ -0,0 +1,147 @@
+/// The relationships, extended attributes and views to request with a resource.
+/// # Examples
+/// ```rust
+/// # use ramdl::api::query::{Extend, Include, Query, View};
+/// let query = Query::new()
+///     .include(Include::Artists)
+///     .include(Include::Credits)
+///     .extend(Extend::ExtendedAssetUrls)
+///     .view(View::OtherVersions);
+/// assert_eq!(
+///     query.to_query_string(),
+///     "include=artists,credits&extend=extendedAssetUrls&views=other-versions"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub include: Vec<Include>,
+    pub extend: Vec<Extend>,
+    pub views: Vec<View>,
+}
+
+impl Query {
+    /// Creates an empty `Query`, which returns the default relationships of the resource.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests a relationship to be included with its objects.
+    pub fn include(mut self, include: Include) -> Self {
+        if !self.include.contains(&include) {
+            self.include.push(include);
+        }
+        self
+    }
+
+    /// Requests an extended attribute.
+    pub fn extend(mut self, extend: Extend) -> Self {
+        if !self.extend.contains(&extend) {
+            self.extend.push(extend);
+        }
+        self
+    }
+
+    /// Requests a view.
+    pub fn view(mut self, view: View) -> Self {
+        if !self.views.contains(&view) {
+            self.views.push(view);
+        }
+        self
+    }
+
+    /// Returns the query parameters, without the leading `?`.
+    pub fn to_query_string(&self) -> String {
+        let mut params = Vec::new();
+        if !self.include.is_empty() {
+            let include = self.include.iter().map(|i| i.as_str()).collect::<Vec<_>>();
+            params.push(format!("include={}", include.join(",")));
+        }
+        if !self.extend.is_empty() {
+            let extend = self.extend.iter().map(|e| e.as_str()).collect::<Vec<_>>();
+            params.push(format!("extend={}", extend.join(",")));
+        }
+        if !self.views.is_empty() {
+            let views = self.views.iter().map(|v| v.as_str()).collect::<Vec<_>>();
+            params.push(format!("views={}", views.join(",")));
+        }
+        params.join("&")
+    }
+}
+
+/// The relationships that can be included with a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Include {
+    Albums,
+    Artists,
+    Composers,
+    Credits,
+    Genres,
+    Library,
+    MusicVideos,
+    RecordLabels,
+    Songs,
+    Tracks,
+}
+
+impl Include {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Include::Albums => "albums",
+            Include::Artists => "artists",
+            Include::Composers => "composers",
+            Include::Credits => "credits",
+            Include::Genres => "genres",
+            Include::Library => "library",
+            Include::MusicVideos => "music-videos",
+            Include::RecordLabels => "record-labels",
+            Include::Songs => "songs",
+            Include::Tracks => "tracks",
+        }
+    }
+}
+
+/// The extended attributes that can be requested for a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extend {
+    ArtistUrl,
+    AudioVariants,
+    EditorialVideo,
+    ExtendedAssetUrls,
+}
+
+impl Extend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Extend::ArtistUrl => "artistUrl",
+            Extend::AudioVariants => "audioVariants",
+            Extend::EditorialVideo => "editorialVideo",
+            Extend::ExtendedAssetUrls => "extendedAssetUrls",
+        }
+    }
+}
+
+/// The views that can be requested for a resource.
+/// Album views: appears-on, other-versions, related-albums, related-videos.
+/// Music video views: more-by-artist, more-in-genre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    AppearsOn,
+    MoreByArtist,
+    MoreInGenre,
+    OtherVersions,
+    RelatedAlbums,
+    RelatedVideos,
+}
+
+impl View {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            View::AppearsOn => "appears-on",
+            View::MoreByArtist => "more-by-artist",
+            View::MoreInGenre => "more-in-genre",
+            View::OtherVersions => "other-versions",
+            View::RelatedAlbums => "related-albums",
+            View::RelatedVideos => "related-videos",
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use super::artwork::Artwork;
+use super::editorial_notes::EditorialNotes;
+use super::resource::{Relationship, Resource};
+use super::stations::Stations;
+
+/// A resource object that represents a radio show, e.g. a show on Apple Music 1. The type is always radio-shows.
+pub type RadioShows = Resource<Attributes, Relationships>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The radio show artwork.
+    pub artwork: Option<Artwork>,
+    /// The notes about the radio show that appear in Apple Music.
+    pub editorial_notes: Option<EditorialNotes>,
+    /// The localized name of the radio show.
+    pub name: String,
+    /// The URL for sharing the radio show in Apple Music.
+    pub url: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The episodes of the radio show. Each episode is a station.
+    pub episodes: Option<Relationship<Stations>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_radio_show_episodes() {
+        let episode = |id: &str, is_live: bool| {
+            serde_json::json!({
+                "id": id,
+                "type": "stations",
+                "href": format!("/v1/catalog/us/stations/{id}"),
+                "attributes": {
+                    "artwork": { "width": 1000, "height": 1000, "url": "https://example.com/{w}x{h}sr.jpg" },
+                    "durationInMillis": 3600000,
+                    "episodeNumber": "12",
+                    "isLive": is_live,
+                    "mediaKind": "audio",
+                    "name": id,
+                    "playParams": { "id": id, "kind": "radioStation" },
+                    "url": format!("https://music.apple.com/us/station/{id}")
+                }
+            })
+        };
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "next": "/v1/catalog/us/radio-shows/1461224745/episodes?offset=2",
+                "data": [episode("ra.1597442117", false), episode("ra.978194965", true)]
+            }),
+        )])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        let episodes = apple_music
+            .get_radio_show_episodes("1461224745", None, Some(2))
+            .await
+            .unwrap();
+        assert_eq!(episodes[0].on_demand_id().as_deref(), Some("ra.1597442117"));
+        assert_eq!(episodes[1].on_demand_id(), None);
+        assert_eq!(
+            server.requests()[0].path,
+            "/v1/catalog/us/radio-shows/1461224745/episodes?offset=0&limit=2"
+        );
+    }
+}
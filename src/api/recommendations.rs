@@ -0,0 +1,121 @@
+use super::content::Content;
+use super::resource::{Relationship, Resource};
+
+/// A resource object that represents recommended resources for a user. The type is always personal-recommendation.
+pub type PersonalRecommendation = Resource<Attributes, Relationships>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The type of recommendation.
+    /// Possible Values: music-recommendations, recently-played, unknown
+    pub kind: Option<String>,
+    /// The next date in UTC format for updating the recommendation.
+    pub next_update_date: Option<String>,
+    /// The localized reason for the recommendation.
+    pub reason: Option<Title>,
+    /// The resource types supported by the recommendation.
+    #[serde(default)]
+    pub resource_types: Vec<String>,
+    /// The localized title for the recommendation.
+    pub title: Option<Title>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Title {
+    /// The localized string to display.
+    pub string_for_display: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationships {
+    /// The contents associated with the recommendation, e.g. albums, playlists and personal mixes.
+    pub contents: Option<Relationship<Content>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    fn playlist(id: &str, playlist_type: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "type": "playlists",
+            "href": format!("/v1/catalog/us/playlists/{id}"),
+            "attributes": {
+                "curatorName": "Apple Music",
+                "isChart": false,
+                "lastModifiedDate": "2024-12-01T00:00:00Z",
+                "name": id,
+                "playlistType": playlist_type,
+                "playParams": { "id": id, "kind": "playlist" },
+                "url": format!("https://music.apple.com/us/playlist/{id}")
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_personal_mixes() {
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "data": [{
+                    "id": "6-27s5hU6azhJY",
+                    "type": "personal-recommendation",
+                    "href": "/v1/me/recommendations/6-27s5hU6azhJY",
+                    "attributes": {
+                        "kind": "music-recommendations",
+                        "resourceTypes": ["playlists"],
+                        "title": { "stringForDisplay": "Made for You" }
+                    },
+                    "relationships": {
+                        "contents": {
+                            "href": "/v1/me/recommendations/6-27s5hU6azhJY/contents",
+                            "data": [
+                                playlist("pl.pm-20e9f373919da6e0aaf2", "personal-mix"),
+                                playlist("pl.f4d106fed2bd41149aaacabb233eb5eb", "editorial"),
+                                { "id": "ra.985484166", "type": "stations", "href": "/v1/catalog/us/stations/ra.985484166" }
+                            ]
+                        }
+                    }
+                }],
+                "next": "/v1/me/recommendations?offset=30&limit=30"
+            }),
+        ), (
+            200,
+            serde_json::json!({
+                "data": [{
+                    "id": "6-27s5hU6azhJZ",
+                    "type": "personal-recommendation",
+                    "href": "/v1/me/recommendations/6-27s5hU6azhJZ",
+                    "relationships": {
+                        "contents": {
+                            "href": "/v1/me/recommendations/6-27s5hU6azhJZ/contents",
+                            "data": [playlist("pl.pm-9e1d9b5e8d5b0fa3c57f", "personal-mix")]
+                        }
+                    }
+                }],
+                // A page linking to itself ends the pagination.
+                "next": "/v1/me/recommendations?offset=30&limit=30"
+            }),
+        )])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        let mixes = apple_music.get_personal_mixes().await.unwrap();
+        // The mixes past the first page are included.
+        assert_eq!(mixes.len(), 2);
+        assert_eq!(mixes[0].id, "pl.pm-20e9f373919da6e0aaf2");
+        assert_eq!(mixes[1].id, "pl.pm-9e1d9b5e8d5b0fa3c57f");
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/v1/me/recommendations?offset=0&limit=30");
+        assert_eq!(
+            requests[1].path,
+            "/v1/me/recommendations?offset=30&limit=30"
+        );
+        assert_eq!(requests.len(), 2);
+    }
+}
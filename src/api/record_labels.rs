@@ -0,0 +1,74 @@
+use super::artwork::Artwork;
+use super::playlists::DescriptionAttribute;
+use super::resource::Resource;
+
+/// A resource object that represents a record label. The type is always record-labels.
+pub type RecordLabels = Resource<Attributes>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The artwork for the record label.
+    pub artwork: Option<Artwork>,
+    /// The notes about the record label that appear in Apple Music.
+    pub description: Option<DescriptionAttribute>,
+    /// The localized name of the record label.
+    pub name: String,
+    /// The URL for sharing the record label in Apple Music.
+    pub url: String,
+}
+
+/// The views of a record label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordLabelView {
+    /// The latest albums released by the record label.
+    LatestReleases,
+    /// The top albums of the record label.
+    TopReleases,
+}
+
+impl RecordLabelView {
+    /// The name of the view in the request path.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordLabelView::LatestReleases => "latest-releases",
+            RecordLabelView::TopReleases => "top-releases",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordLabelView;
+    use crate::mock_server::MockServer;
+    use crate::AppleMusicDownloader;
+
+    #[tokio::test]
+    async fn test_record_label_releases() {
+        let server = MockServer::start_json(vec![(
+            200,
+            serde_json::json!({
+                "next": "/v1/catalog/us/record-labels/1543411840/view/latest-releases?offset=1",
+                "data": [{ "id": "1214782644", "type": "albums", "href": "/v1/catalog/us/albums/1214782644" }]
+            }),
+        )])
+        .await;
+        let mut apple_music = AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music.set_amp_api_url(&server.url);
+        let releases = apple_music
+            .get_record_label_releases(
+                "1543411840",
+                RecordLabelView::LatestReleases,
+                Some(1),
+                Some(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(releases.data[0].id, "1214782644");
+        assert!(releases.next.is_some());
+        assert_eq!(
+            server.requests()[0].path,
+            "/v1/catalog/us/record-labels/1543411840/view/latest-releases?offset=1&limit=1"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource<A, R = serde_json::Value, V = serde_json::Value> {
+    /// The identifier for the resource.
+    pub id: String,
+    /// The type of the resource, e.g. songs, albums or library-songs.
+    /// Empty when the resource was deserialized through a type-tagged enum such as `TrackData`.
+    #[serde(rename = "type", default, skip_serializing_if = "String::is_empty")]
+    pub type_: String,
+    /// The relative location for the resource.
+    pub href: String,
+    /// The attributes for the resource. Not included when the resource is only referenced by identifier.
+    pub attributes: Option<A>,
+    /// The relationships for the resource.
+    pub relationships: Option<R>,
+    /// The views for the resource. Only included when requested with `views`.
+    pub views: Option<V>,
+    /// Information about the request or response.
+    pub meta: Option<Meta>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationship<T> {
+    /// The relative location to fetch the relationship directly.
+    pub href: Option<String>,
+    /// The relative location to request the next page of resources in the collection, if additional resources are available for fetching.
+    pub next: Option<String>,
+    /// The resources of the relationship.
+    pub data: Vec<T>,
+    /// Information about the relationship, such as the total number of resources.
+    pub meta: Option<RelationshipMeta>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct View<T> {
+    /// The relative location to fetch the view directly.
+    pub href: Option<String>,
+    /// The relative location to request the next page of resources in the view, if additional resources are available for fetching.
+    pub next: Option<String>,
+    /// The attributes for the view.
+    pub attributes: Option<ViewAttributes>,
+    /// The resources of the view.
+    pub data: Vec<T>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewAttributes {
+    /// The localized title of the view.
+    pub title: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    pub content_version: Option<ContentVersion>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ContentVersion {
+    pub rtci: Option<u64>,
+    pub mz_indexer: Option<u64>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RelationshipMeta {
+    /// The total number of resources in the relationship.
+    pub total: Option<i64>,
+}
+
+/// A reference to a resource by type and identifier, as used in request bodies and relationships.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResourceIdentifier {
+    /// The identifier for the resource.
+    pub id: String,
+    /// The type of the resource, e.g. songs, albums, playlists or library-songs.
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+impl ResourceIdentifier {
+    /// Creates a new `ResourceIdentifier` from a resource type and identifier.
+    pub fn new(type_: &str, id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            type_: type_.to_string(),
+        }
+    }
+}
+
+impl<A, R, V> Resource<A, R, V> {
+    /// Returns the identifier of the resource.
+    /// The type is empty if the resource was deserialized through a type-tagged enum.
+    pub fn identifier(&self) -> ResourceIdentifier {
+        ResourceIdentifier::new(&self.type_, &self.id)
+    }
+}
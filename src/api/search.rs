@@ -1,170 +1,14 @@
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct SearchResults {
-    pub songs: Vec<Song>,
-    pub albums: Vec<Album>,
-    pub artists: Vec<Artist>,
-    pub playlists: Vec<Playlist>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Song {
-    pub id: String,
-    pub href: String,
-    pub attributes: SongAttributes,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct SongAttributes {
-    pub has_time_synced_lyrics: bool,
-    pub album_name: String,
-    pub genre_names: Vec<String>,
-    pub track_number: u32,
-    pub release_date: String,
-    pub duration_in_millis: u32,
-    pub is_vocal_attenuation_allowed: bool,
-    pub is_mastered_for_itunes: bool,
-    pub isrc: String,
-    pub artwork: Artwork,
-    pub audio_locale: String,
-    pub composer_name: Option<String>,
-    pub play_params: PlayParams,
-    pub url: String,
-    pub disc_number: u32,
-    pub has_lyrics: bool,
-    pub is_apple_digital_master: bool,
-    pub audio_traits: Vec<String>,
-    pub name: String,
-    pub previews: Vec<Preview>,
-    pub artist_name: String,
-}
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Artwork {
-    pub width: u32,
-    pub url: String,
-    pub height: u32,
-    pub text_color3: String,
-    pub text_color2: String,
-    pub text_color4: String,
-    pub text_color1: String,
-    pub bg_color: String,
-    pub has_p3: bool,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct PlayParams {
-    pub id: String,
-    pub kind: String,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Preview {
-    pub url: String,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Album {
-    pub id: String,
-    pub href: String,
-    pub attributes: AlbumAttributes,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct AlbumAttributes {
-    pub copyright: String,
-    pub genre_names: Vec<String>,
-    pub release_date: String,
-    pub upc: String,
-    pub is_mastered_for_itunes: bool,
-    pub artwork: Artwork,
-    pub url: String,
-    pub play_params: PlayParams,
-    pub record_label: String,
-    pub is_compilation: bool,
-    pub track_count: u32,
-    pub is_prerelease: bool,
-    pub audio_traits: Vec<String>,
-    pub is_single: bool,
-    pub name: String,
-    pub artist_name: String,
-    pub is_complete: bool,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Playlist {
-    pub id: String,
-    pub href: String,
-    pub attributes: PlaylistAttributes,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct PlaylistAttributes {
-    pub last_modified_date: String,
-    pub supports_sing: bool,
-    pub description: Description,
-    pub artwork: Artwork,
-    pub play_params: PlayParams,
-    pub url: String,
-    pub has_collaboration: bool,
-    pub curator_name: String,
-    pub audio_traits: Vec<String>,
-    pub name: String,
-    pub is_chart: bool,
-    pub playlist_type: String,
-    pub editorial_notes: EditorialNotes,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Description {
-    pub standard: String,
-    pub short: String,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct EditorialNotes {
-    pub name: String,
-    pub standard: String,
-    pub short: String,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Artist {
-    pub id: String,
-    pub href: String,
-    pub attributes: ArtistAttributes,
-    pub relationships: ArtistRelationships,
-}
+use super::albums::Albums;
+use super::artists::Artists;
+use super::playlists::Playlists;
+use super::songs::Songs;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct ArtistAttributes {
-    pub name: String,
-    pub genre_names: Vec<String>,
-    pub artwork: Artwork,
-    pub url: String,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct ArtistRelationships {
-    pub albums: AlbumsRelationship,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct AlbumsRelationship {
-    pub href: String,
-    pub next: Option<String>,
-    pub data: Vec<AlbumReference>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct AlbumReference {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub href: String,
+pub struct SearchResults {
+    pub songs: Vec<Songs>,
+    pub albums: Vec<Albums>,
+    pub artists: Vec<Artists>,
+    pub playlists: Vec<Playlists>,
 }
 
 #[cfg(test)]
@@ -179,4 +23,27 @@ mod tests {
             .unwrap();
         apple_music.search("サイレンは彼方より").await.unwrap();
     }
+
+    #[test]
+    fn test_search_hit_is_catalog_song() {
+        let hit = serde_json::json!({
+            "id": "1214782673",
+            "type": "songs",
+            "href": "/v1/catalog/jp/songs/1214782673",
+            "attributes": {
+                "albumName": "サイレンは彼方より",
+                "artistName": "TORIENA",
+                "artwork": { "width": 3000, "height": 3000, "url": "https://example.com/{w}x{h}bb.{f}" },
+                "discNumber": 1,
+                "durationInMillis": 201000,
+                "genreNames": ["Electronic"],
+                "name": "サイレンは彼方より (feat. Hatsune Miku)",
+                "previews": [{ "url": "https://example.com/preview.m4a" }],
+                "url": "https://music.apple.com/jp/album/1214782644?i=1214782673"
+            }
+        });
+        let song: super::Songs = serde_json::from_value(hit).unwrap();
+        assert_eq!(song.type_, "songs");
+        assert_eq!(song.attributes.unwrap().disc_number, 1);
+    }
 }
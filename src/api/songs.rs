@@ -1,25 +1,17 @@
+use super::albums::Albums;
+use super::artists::Artists;
 use super::artwork::Artwork;
+use super::credits::Credits;
 use super::editorial_notes::EditorialNotes;
+use super::genres::Genre;
+use super::library_songs::LibrarySongs;
+use super::music_videos::MusicVideos;
 use super::play_parameters::PlayParameters;
 use super::previews::Preview;
+use super::resource::{Relationship, Resource};
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct Songs {
-    /// The identifier for the song.
-    pub id: String,
-    /// This value is always songs.
-    #[serde(rename = "type")]
-    pub type_: String,
-    /// The relative location for the song resource.
-    pub href: String,
-    /// The attributes for the song.
-    pub attributes: Attributes,
-    /// The relationships for the song.
-    pub relationships: Relationships,
-    /// Information about the request or response.
-    pub meta: Option<Meta>,
-}
+/// A resource object that represents a song. The type is always songs.
+pub type Songs = Resource<Attributes, Relationships>;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -52,8 +44,10 @@ pub struct Attributes {
     /// The genre names the song is associated with.
     pub genre_names: Vec<String>,
     /// Indicates whether the song has lyrics available in the Apple Music catalog. If true, the song has lyrics available; otherwise, it doesn't.
+    #[serde(default)]
     pub has_lyrics: bool,
     /// Indicates whether the response delivered the song as an [Apple Digital Master](https://www.apple.com/apple-music/apple-digital-masters/).
+    #[serde(default)]
     pub is_apple_digital_master: bool,
     /// The International Standard Recording Code (ISRC) for the song.
     pub isrc: Option<String>,
@@ -68,6 +62,7 @@ pub struct Attributes {
     /// When present, this attribute indicates that the song is available to play with an Apple Music subscription. The value map may be used to initiate playback. Previews of the song audio may be available with or without an Apple Music subscription.
     pub play_params: Option<PlayParameters>,
     /// The preview assets for the song.
+    #[serde(default)]
     pub previews: Vec<Preview>,
     /// The release date of the song, when known, in YYYY-MM-DD or YYYY format. Prerelease songs may have an expected release date in the future.
     pub release_date: Option<String>,
@@ -78,7 +73,9 @@ pub struct Attributes {
     /// (Classical music only) The name of the associated work.
     pub work_name: Option<String>,
     pub has_time_synced_lyrics: Option<bool>,
+    #[serde(default)]
     pub is_vocal_attenuation_allowed: bool,
+    #[serde(default)]
     pub is_mastered_for_itunes: bool,
 
     pub audio_locale: Option<String>,
@@ -99,35 +96,28 @@ pub struct ExtendedAssetUrls {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct Relationships {
     /// The albums associated with the song. By default, albums includes identifiers only.
-    pub albums: Option<RelationshipData>,
+    pub albums: Option<Relationship<Albums>>,
     /// The artists associated with the song. By default, artists includes identifiers only.
-    pub artists: Option<RelationshipData>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct RelationshipData {
-    pub href: Option<String>,
-    pub next: Option<String>,
-    pub data: Vec<RelationshipItem>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct RelationshipItem {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub href: String,
+    pub artists: Option<Relationship<Artists>>,
+    /// The composers for the song. Only included when requested with `include=composers`.
+    pub composers: Option<Relationship<Artists>>,
+    /// The credits for the song. Only included when requested with `include=credits`.
+    pub credits: Option<Relationship<Credits>>,
+    /// The genres for the song. Only included when requested with `include=genres`.
+    pub genres: Option<Relationship<Genre>>,
+    /// The song in the user’s library for the catalog song, if any.
+    pub library: Option<Relationship<LibrarySongs>>,
+    /// The music videos for the song. Only included when requested with `include=music-videos`.
+    #[serde(rename = "music-videos")]
+    pub music_videos: Option<Relationship<MusicVideos>>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct Meta {
-    pub content_version: Option<ContentVersion>,
-}
-
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
-pub struct ContentVersion {
-    pub rtci: u64,
-    pub mz_indexer: u64,
+impl Songs {
+    /// Returns the album artwork of the song, if the attributes are included.
+    /// Use `AppleMusicDownloader::get_artwork` to download it.
+    pub fn artwork(&self) -> Option<&Artwork> {
+        Some(&self.attributes.as_ref()?.artwork)
+    }
 }
 
 #[cfg(test)]
@@ -143,7 +133,7 @@ mod tests {
         let song = apple_music.get_songs("1214782673").await.unwrap();
         println!("{:?}", song);
         assert_eq!(
-            song.attributes.name,
+            song.attributes.unwrap().name,
             "サイレンは彼方より (feat. Hatsune Miku)"
         )
     }
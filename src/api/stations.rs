@@ -0,0 +1,52 @@
+use super::artwork::Artwork;
+use super::editorial_notes::EditorialNotes;
+use super::play_parameters::PlayParameters;
+use super::resource::Resource;
+
+/// A resource object that represents a station, e.g. Apple Music 1 or an on-demand radio show episode.
+/// The type is always stations.
+pub type Stations = Resource<Attributes>;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Attributes {
+    /// The radio station artwork.
+    pub artwork: Artwork,
+    /// The Recording Industry Association of America (RIAA) rating of the content.
+    pub content_rating: Option<String>,
+    /// The duration of the stream in milliseconds. Not available for live content.
+    pub duration_in_millis: Option<u64>,
+    /// The notes about the station that appear in Apple Music.
+    pub editorial_notes: Option<EditorialNotes>,
+    /// The episode number of the station. Only available for radio show episodes.
+    pub episode_number: Option<String>,
+    /// Indicates whether the station is a live stream.
+    #[serde(default)]
+    pub is_live: bool,
+    /// The media kind of the station, audio or video.
+    pub media_kind: Option<String>,
+    /// The localized name of the station.
+    pub name: String,
+    /// When present, this attribute indicates that the station is available to play.
+    pub play_params: Option<PlayParameters>,
+    /// The name of the entity that provided the station, when specified.
+    pub station_provider_name: Option<String>,
+    /// Indicates whether playing the station requires an Apple Music subscription.
+    #[serde(default)]
+    pub requires_subscription: bool,
+    /// The URL for sharing the station in Apple Music.
+    pub url: String,
+}
+
+impl Stations {
+    /// Returns the play parameters identifier of an on-demand station, e.g. a past radio show
+    /// episode (`ra.` identifiers). Live stations return `None`.
+    pub fn on_demand_id(&self) -> Option<String> {
+        let attributes = self.attributes.as_ref()?;
+        if attributes.is_live {
+            return None;
+        }
+        let play_params = attributes.play_params.as_ref()?;
+        Some(play_params.id.clone())
+    }
+}
@@ -1,3 +1,5 @@
+use super::artwork::Artwork;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WebPlayBack {
@@ -97,6 +99,13 @@ pub struct ArtworkUrls {
     pub image_type: String,
 }
 
+impl ArtworkUrls {
+    /// Returns the artwork with the `{w}x{h}` and `{f}` placeholders, to render it at another size or format.
+    pub fn artwork(&self) -> Artwork {
+        Artwork::from_rendered_url(&self.defaultx2.url)
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct ArtWorkUrlDefault {
     pub url: String,
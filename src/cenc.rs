@@ -0,0 +1,531 @@
+//! the Common Encryption (CENC) decryption of fragmented MP4 files.
+//!
+//! The samples of the streams are encrypted with the `cenc` (AES-CTR) or `cbcs` (AES-CBC with
+//! an encryption pattern) scheme of ISO/IEC 23001-7. The decryption keeps the layout of the file;
+//! the protection boxes of the sample entries are removed afterwards by `remux::remux`.
+
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Block};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{Error, Result};
+use crate::mp4::{find_box, parse_boxes, Mp4Box};
+use crate::remux::{read_u16, read_u32, sample_entry_fields_len, track_fragments};
+
+/// The user type of the PIFF sample encryption box, a `uuid` box used instead of `senc`.
+const PIFF_SAMPLE_ENCRYPTION: [u8; 16] = [
+    0xa2, 0x39, 0x4f, 0x52, 0x5a, 0x9b, 0x4f, 0x14, 0xa2, 0x44, 0x6c, 0x42, 0x7c, 0x64, 0x8d, 0xf4,
+];
+/// `senc` flags.
+const SENC_OVERRIDE_TRACK_ENCRYPTION: u32 = 0x1;
+const SENC_USE_SUBSAMPLE_ENCRYPTION: u32 = 0x2;
+
+/// The protection scheme and default encryption parameters of a track, from the `sinf` box of
+/// its sample entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackEncryption {
+    pub track_id: u32,
+    /// The 1-based index of the sample entry in the `stsd` box of the track.
+    pub sample_description_index: u32,
+    /// The protection scheme, e.g. `cenc` or `cbcs`.
+    pub scheme: [u8; 4],
+    pub is_protected: bool,
+    /// The size of the per-sample IVs, 0 when the constant IV is used.
+    pub iv_size: u8,
+    /// The default key id of the samples.
+    pub kid: [u8; 16],
+    pub constant_iv: Option<Vec<u8>>,
+    /// The number of encrypted and skipped blocks of the encryption pattern.
+    pub crypt_byte_block: u8,
+    pub skip_byte_block: u8,
+}
+
+/// The encryption parameters of a sample, from the `senc` box of its track fragment.
+#[derive(Debug, Clone, Default)]
+struct SampleEncryption {
+    /// The IV of the sample, empty when the constant IV is used.
+    iv: Vec<u8>,
+    /// The clear and protected byte counts of the subsamples.
+    subsamples: Vec<(usize, usize)>,
+}
+
+/// Returns the encryption parameters of the protected sample entries of a MP4 file,
+/// e.g. to request the content key of their key ids. A track may have several protected
+/// sample entries, e.g. with a different key for its first fragments.
+pub fn track_encryptions(data: &[u8]) -> Result<Vec<TrackEncryption>> {
+    let moov =
+        find_box(data, &[b"moov"])?.ok_or_else(|| Error::Mp4("moov box not found".to_string()))?;
+    let mut encryptions = Vec::new();
+    for trak in moov.children()?.into_iter().filter(|b| &b.name == b"trak") {
+        let tkhd = trak
+            .child(b"tkhd")?
+            .ok_or_else(|| Error::Mp4("tkhd box not found".to_string()))?;
+        let tkhd = tkhd.payload();
+        let track_id = read_u32(tkhd, if tkhd.first() == Some(&1) { 20 } else { 12 })?;
+        let Some(stsd) = find_box(trak.payload(), &[b"mdia", b"minf", b"stbl", b"stsd"])? else {
+            continue;
+        };
+        let entries = stsd
+            .payload()
+            .get(8..)
+            .ok_or_else(|| Error::Mp4("Truncated stsd box".to_string()))?;
+        for (index, entry) in parse_boxes(entries)?.into_iter().enumerate() {
+            if &entry.name != b"enca" && &entry.name != b"encv" {
+                continue;
+            }
+            let fields_len = sample_entry_fields_len(&entry)?;
+            let children = entry
+                .payload()
+                .get(fields_len..)
+                .ok_or_else(|| Error::Mp4("Truncated sample entry".to_string()))?;
+            if let Some(sinf) = parse_boxes(children)?
+                .into_iter()
+                .find(|b| &b.name == b"sinf")
+            {
+                encryptions.push(read_sinf(track_id, index as u32 + 1, &sinf)?);
+            }
+        }
+    }
+    Ok(encryptions)
+}
+
+/// Reads the `schm` and `tenc` boxes of a `sinf` box.
+fn read_sinf(
+    track_id: u32,
+    sample_description_index: u32,
+    sinf: &Mp4Box,
+) -> Result<TrackEncryption> {
+    let schm = sinf
+        .child(b"schm")?
+        .ok_or_else(|| Error::Mp4("schm box not found".to_string()))?;
+    let scheme = schm
+        .payload()
+        .get(4..8)
+        .ok_or_else(|| Error::Mp4("Truncated schm box".to_string()))?
+        .try_into()
+        .unwrap();
+    let tenc = match sinf.child(b"schi")? {
+        Some(schi) => schi.child(b"tenc")?,
+        None => None,
+    }
+    .ok_or_else(|| Error::Mp4("tenc box not found".to_string()))?;
+    let tenc = tenc.payload();
+    let truncated = || Error::Mp4("Truncated tenc box".to_string());
+    let version = tenc.first().copied().ok_or_else(truncated)?;
+    // The pattern is only defined from version 1.
+    let pattern = if version > 0 {
+        *tenc.get(5).ok_or_else(truncated)?
+    } else {
+        0
+    };
+    let is_protected = *tenc.get(6).ok_or_else(truncated)? != 0;
+    let iv_size = *tenc.get(7).ok_or_else(truncated)?;
+    let kid = tenc.get(8..24).ok_or_else(truncated)?.try_into().unwrap();
+    let constant_iv = if is_protected && iv_size == 0 {
+        let size = *tenc.get(24).ok_or_else(truncated)? as usize;
+        Some(tenc.get(25..25 + size).ok_or_else(truncated)?.to_vec())
+    } else {
+        None
+    };
+    Ok(TrackEncryption {
+        track_id,
+        sample_description_index,
+        scheme,
+        is_protected,
+        iv_size,
+        kid,
+        constant_iv,
+        crypt_byte_block: pattern >> 4,
+        skip_byte_block: pattern & 0xf,
+    })
+}
+
+/// Decrypts the samples of a fragmented MP4 file with a 16-byte content key, e.g. the key of
+/// `AppleMusicDownloader::get_decryption_key` decoded from hex. Fragments without sample
+/// encryption information are left as is.
+pub fn decrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
+    let cipher = new_cipher(key)?;
+    let encryptions = track_encryptions(data)?;
+    let ciphers = encryptions
+        .iter()
+        .map(|encryption| (encryption.kid, cipher.clone()))
+        .collect();
+    decrypt_tracks(data, &encryptions, ciphers)
+}
+
+/// Decrypts the samples of a fragmented MP4 file with the content keys by key id, e.g. from
+/// `decrypter::get_decrypt_keys`, when the tracks are protected with different keys.
+/// Each track fragment is decrypted with the key of the default key id of its sample entry.
+pub fn decrypt_with_keys(data: &[u8], keys: &BTreeMap<Vec<u8>, Vec<u8>>) -> Result<Vec<u8>> {
+    let encryptions = track_encryptions(data)?;
+    let mut ciphers = HashMap::new();
+    for encryption in encryptions
+        .iter()
+        .filter(|encryption| encryption.is_protected)
+    {
+        if let Some(key) = keys.get(&encryption.kid[..]) {
+            ciphers.insert(encryption.kid, new_cipher(key)?);
+        }
+    }
+    decrypt_tracks(data, &encryptions, ciphers)
+}
+
+fn new_cipher(key: &[u8]) -> Result<Aes128> {
+    Aes128::new_from_slice(key)
+        .map_err(|_| Error::Decrypt(format!("Invalid key length {}", key.len())))
+}
+
+/// Decrypts the samples of the protected track fragments with the cipher of the key id of their
+/// sample entry.
+fn decrypt_tracks(
+    data: &[u8],
+    encryptions: &[TrackEncryption],
+    ciphers: HashMap<[u8; 16], Aes128>,
+) -> Result<Vec<u8>> {
+    let mut output = data.to_vec();
+    for fragment in track_fragments(data)? {
+        let Some(encryption) = encryptions.iter().find(|e| {
+            e.track_id == fragment.track_id
+                && e.sample_description_index == fragment.sample_description_index
+                && e.is_protected
+        }) else {
+            continue;
+        };
+        let cipher = ciphers.get(&encryption.kid).ok_or_else(|| {
+            Error::Decrypt(format!(
+                "No key for the key id {} of track {}",
+                hex::encode(encryption.kid),
+                encryption.track_id
+            ))
+        })?;
+        let sample_encryptions = match read_senc(&fragment.traf, encryption)? {
+            Some(sample_encryptions) => sample_encryptions,
+            // Without per-sample IVs nor subsamples, the whole samples are encrypted with the constant IV.
+            None if encryption.constant_iv.is_some() => {
+                vec![SampleEncryption::default(); fragment.samples.len()]
+            }
+            None => continue,
+        };
+        if sample_encryptions.len() != fragment.samples.len() {
+            return Err(Error::Decrypt(format!(
+                "{} samples but {} sample encryption entries",
+                fragment.samples.len(),
+                sample_encryptions.len()
+            )));
+        }
+        for (&(offset, size), sample_encryption) in fragment.samples.iter().zip(&sample_encryptions)
+        {
+            decrypt_sample(
+                &mut output[offset..offset + size],
+                cipher,
+                encryption,
+                sample_encryption,
+            )?;
+        }
+    }
+    Ok(output)
+}
+
+/// Reads the `senc` box, or the PIFF sample encryption box, of a `traf` box.
+fn read_senc(traf: &Mp4Box, encryption: &TrackEncryption) -> Result<Option<Vec<SampleEncryption>>> {
+    let Some(payload) = traf.children()?.into_iter().find_map(|b| match &b.name {
+        b"senc" => Some(b.payload()),
+        b"uuid" if b.payload().starts_with(&PIFF_SAMPLE_ENCRYPTION) => Some(&b.payload()[16..]),
+        _ => None,
+    }) else {
+        return Ok(None);
+    };
+    let flags = read_u32(payload, 0)? & 0xffffff;
+    let mut iv_size = encryption.iv_size as usize;
+    let mut position = 4;
+    if flags & SENC_OVERRIDE_TRACK_ENCRYPTION != 0 {
+        // The algorithm id, the IV size and the key id.
+        iv_size = read_u32(payload, position)? as usize & 0xff;
+        position += 20;
+    }
+    let sample_count = read_u32(payload, position)?;
+    position += 4;
+    let mut sample_encryptions = Vec::with_capacity(sample_count as usize);
+    for _ in 0..sample_count {
+        let iv = payload
+            .get(position..position + iv_size)
+            .ok_or_else(|| Error::Mp4("Truncated senc box".to_string()))?
+            .to_vec();
+        position += iv_size;
+        let mut subsamples = Vec::new();
+        if flags & SENC_USE_SUBSAMPLE_ENCRYPTION != 0 {
+            let subsample_count = read_u16(payload, position)?;
+            position += 2;
+            for _ in 0..subsample_count {
+                subsamples.push((
+                    read_u16(payload, position)? as usize,
+                    read_u32(payload, position + 2)? as usize,
+                ));
+                position += 6;
+            }
+        }
+        sample_encryptions.push(SampleEncryption { iv, subsamples });
+    }
+    Ok(Some(sample_encryptions))
+}
+
+/// Decrypts a sample in place.
+fn decrypt_sample(
+    sample: &mut [u8],
+    cipher: &Aes128,
+    encryption: &TrackEncryption,
+    sample_encryption: &SampleEncryption,
+) -> Result<()> {
+    let iv = match (&sample_encryption.iv, &encryption.constant_iv) {
+        (iv, _) if !iv.is_empty() => iv,
+        (_, Some(constant_iv)) => constant_iv,
+        _ => return Err(Error::Decrypt("No IV for the sample".to_string())),
+    };
+    let mut block = [0; 16];
+    block
+        .get_mut(..iv.len())
+        .ok_or_else(|| Error::Decrypt(format!("Invalid IV length {}", iv.len())))?
+        .copy_from_slice(iv);
+
+    let mut ranges = Vec::new();
+    if sample_encryption.subsamples.is_empty() {
+        ranges.push(0..sample.len());
+    } else {
+        let mut position = 0;
+        for &(clear, protected) in &sample_encryption.subsamples {
+            let start = position + clear;
+            position = start + protected;
+            if position > sample.len() {
+                return Err(Error::Decrypt("Subsample out of the sample".to_string()));
+            }
+            ranges.push(start..position);
+        }
+    }
+
+    match &encryption.scheme {
+        b"cenc" | b"piff" => {
+            // The key stream continues across the subsamples.
+            let mut ctr = Ctr::new(u128::from_be_bytes(block));
+            for range in ranges {
+                ctr.apply(cipher, &mut sample[range]);
+            }
+        }
+        b"cbcs" => {
+            // The IV is reset for each subsample.
+            for range in ranges {
+                decrypt_cbc(
+                    cipher,
+                    &mut block.clone(),
+                    &mut sample[range],
+                    encryption.crypt_byte_block,
+                    encryption.skip_byte_block,
+                );
+            }
+        }
+        b"cbc1" => {
+            let mut chain = block;
+            for range in ranges {
+                decrypt_cbc(cipher, &mut chain, &mut sample[range], 1, 0);
+            }
+        }
+        scheme => {
+            return Err(Error::Decrypt(format!(
+                "Unsupported protection scheme {}",
+                String::from_utf8_lossy(scheme)
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// The AES-CTR key stream of a sample.
+struct Ctr {
+    counter: u128,
+    key_stream: [u8; 16],
+    position: usize,
+}
+
+impl Ctr {
+    fn new(counter: u128) -> Self {
+        Self {
+            counter,
+            key_stream: [0; 16],
+            position: 16,
+        }
+    }
+
+    fn apply(&mut self, cipher: &Aes128, data: &mut [u8]) {
+        for byte in data {
+            if self.position == 16 {
+                let mut block = Block::from(self.counter.to_be_bytes());
+                cipher.encrypt_block(&mut block);
+                self.key_stream = block.into();
+                self.counter = self.counter.wrapping_add(1);
+                self.position = 0;
+            }
+            *byte ^= self.key_stream[self.position];
+            self.position += 1;
+        }
+    }
+}
+
+/// Decrypts the complete blocks of `data` with AES-CBC, following a pattern of `crypt` encrypted
+/// blocks and `skip` clear blocks. The trailing partial block is clear.
+fn decrypt_cbc(cipher: &Aes128, chain: &mut [u8; 16], data: &mut [u8], crypt: u8, skip: u8) {
+    let (crypt, skip) = match (crypt, skip) {
+        // Without a pattern, every block is encrypted.
+        (0, 0) => (1, 0),
+        pattern => (pattern.0 as usize, pattern.1 as usize),
+    };
+    for (index, chunk) in data.chunks_exact_mut(16).enumerate() {
+        if index % (crypt + skip) >= crypt {
+            continue;
+        }
+        let ciphertext: [u8; 16] = chunk.try_into().unwrap();
+        let mut block = Block::from(ciphertext);
+        cipher.decrypt_block(&mut block);
+        for (byte, (plain, previous)) in chunk.iter_mut().zip(block.iter().zip(chain.iter())) {
+            *byte = plain ^ previous;
+        }
+        *chain = ciphertext;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt, decrypt_with_keys, new_cipher, track_encryptions, Ctr};
+    use crate::mp4::fixtures::{fragment, fragment_of_entry, init_segment, init_segment_entries};
+    use crate::mp4::{mp4_box, mp4_full_box};
+    use std::collections::BTreeMap;
+
+    const KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+
+    fn hex(data: &str) -> Vec<u8> {
+        hex::decode(data).unwrap()
+    }
+
+    #[test]
+    fn test_decrypt_cenc() {
+        let mut kid = vec![0, 0, 1, 16];
+        kid.extend([7; 16]);
+        let init = init_segment(b"cenc", Some(mp4_full_box(b"tenc", 0, 0, &kid)));
+        let encryptions = track_encryptions(&init).unwrap();
+        assert_eq!(encryptions.len(), 1);
+        assert_eq!(&encryptions[0].scheme, b"cenc");
+        assert_eq!(encryptions[0].iv_size, 16);
+        assert_eq!(encryptions[0].kid, [7; 16]);
+        let empty = init_segment(b"cenc", Some(mp4_box(b"tenc", &[])));
+        assert!(track_encryptions(&empty).is_err());
+
+        // The AES-128-CTR test vectors of NIST SP 800-38A, F.5.1.
+        let iv = hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let ciphertext = hex("874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff");
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        // The second sample is split into subsamples, with clear bytes in front of each.
+        let subsamples = [b"abc", &ciphertext[..10], b"de", &ciphertext[10..]].concat();
+        let samples = [&ciphertext[..16], &subsamples[..]];
+        let mut senc = 2u32.to_be_bytes().to_vec();
+        senc.extend(&iv);
+        senc.extend(1u16.to_be_bytes());
+        senc.extend([&0u16.to_be_bytes()[..], &16u32.to_be_bytes()].concat());
+        senc.extend(&iv);
+        senc.extend(2u16.to_be_bytes());
+        senc.extend([&3u16.to_be_bytes()[..], &10u32.to_be_bytes()].concat());
+        senc.extend([&2u16.to_be_bytes()[..], &22u32.to_be_bytes()].concat());
+        let senc = mp4_full_box(b"senc", 0, 2, &senc);
+        let data = [init, fragment(1, &samples, Some(senc))].concat();
+
+        let output = decrypt(&data, &hex(KEY)).unwrap();
+        let expected = [
+            &plaintext[..16],
+            b"abc",
+            &plaintext[..10],
+            b"de",
+            &plaintext[10..],
+        ]
+        .concat();
+        assert!(output.ends_with(&expected));
+        assert_eq!(output.len(), data.len());
+        assert!(decrypt(&data, &[0; 8]).is_err());
+
+        // The key is selected by the key id of the track.
+        let keys = BTreeMap::from([(vec![8; 16], vec![0; 16]), (vec![7; 16], hex(KEY))]);
+        assert_eq!(decrypt_with_keys(&data, &keys).unwrap(), output);
+        let keys = BTreeMap::from([(vec![8; 16], hex(KEY))]);
+        assert!(decrypt_with_keys(&data, &keys).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_cbcs() {
+        // Version 1, a pattern of 1 encrypted and 9 clear blocks, and a constant IV.
+        let iv = hex("000102030405060708090a0b0c0d0e0f");
+        let tenc = [&[0, 0x19, 1, 0][..], &[9; 16], &[16], &iv].concat();
+        let init = init_segment(b"cbcs", Some(mp4_full_box(b"tenc", 1, 0, &tenc)));
+        let encryptions = track_encryptions(&init).unwrap();
+        assert_eq!(encryptions[0].crypt_byte_block, 1);
+        assert_eq!(encryptions[0].skip_byte_block, 9);
+        assert_eq!(encryptions[0].constant_iv, Some(iv));
+
+        // The AES-128-CBC test vectors of NIST SP 800-38A, F.2.2; the chain continues
+        // across the clear blocks.
+        let ciphertext = hex("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2");
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        let clear = vec![5; 144];
+        let sample = [&ciphertext[..16], &clear, &ciphertext[16..], b"tail"].concat();
+        let data = [init, fragment(1, &[&sample], None)].concat();
+
+        let output = decrypt(&data, &hex(KEY)).unwrap();
+        let expected = [&plaintext[..16], &clear, &plaintext[16..], b"tail"].concat();
+        assert!(output.ends_with(&expected));
+    }
+
+    #[test]
+    fn test_decrypt_sample_entries() {
+        // A prefetch key for the fragments of the first sample entry, another for the second one.
+        let tenc = |kid: u8| {
+            let mut tenc = vec![0, 0, 1, 16];
+            tenc.extend([kid; 16]);
+            Some(mp4_full_box(b"tenc", 0, 0, &tenc))
+        };
+        let init = init_segment_entries(&[(b"cenc", tenc(6)), (b"cenc", tenc(7))]);
+        let encryptions = track_encryptions(&init).unwrap();
+        assert_eq!(encryptions.len(), 2);
+        assert_eq!(encryptions[0].kid, [6; 16]);
+        assert_eq!(encryptions[1].kid, [7; 16]);
+        assert_eq!(encryptions[1].sample_description_index, 2);
+
+        let iv = hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff");
+        let plaintext = hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        let prefetch_key = [9; 16];
+        let mut prefetch = plaintext.clone();
+        Ctr::new(u128::from_be_bytes(iv.clone().try_into().unwrap()))
+            .apply(&new_cipher(&prefetch_key).unwrap(), &mut prefetch);
+        let ciphertext = hex("874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff");
+        let senc = || {
+            let mut senc = 1u32.to_be_bytes().to_vec();
+            senc.extend(&iv);
+            Some(mp4_full_box(b"senc", 0, 0, &senc))
+        };
+        let data = [
+            init,
+            fragment_of_entry(1, Some(1), &[&prefetch], senc()),
+            fragment_of_entry(2, Some(2), &[&ciphertext], senc()),
+        ]
+        .concat();
+
+        let keys = BTreeMap::from([
+            (vec![6; 16], prefetch_key.to_vec()),
+            (vec![7; 16], hex(KEY)),
+        ]);
+        let output = decrypt_with_keys(&data, &keys).unwrap();
+        assert_eq!(
+            output
+                .windows(plaintext.len())
+                .filter(|&w| w == &plaintext[..])
+                .count(),
+            2
+        );
+        let keys = BTreeMap::from([(vec![7; 16], hex(KEY))]);
+        assert!(decrypt_with_keys(&data, &keys).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! the work/movement model of classical albums.
+
+use crate::api::albums::Albums;
+use crate::api::playlists::TrackData;
+use crate::api::songs::Songs;
+use crate::tags::Tags;
+
+/// A classical work, made of consecutive tracks of an album sharing the same work name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Work {
+    /// The name of the work.
+    pub name: String,
+    /// The composer of the work, taken from the attribution or the composer name of its first movement.
+    pub composer: Option<String>,
+    /// The movements of the work, in album order.
+    pub movements: Vec<Movement>,
+}
+
+/// A movement of a classical work.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Movement {
+    /// The identifier of the song.
+    pub song_id: String,
+    /// The name of the movement, or the name of the song if the movement name is unknown.
+    pub name: String,
+    /// The number of the movement within the work.
+    pub number: Option<u32>,
+    /// The number of movements of the work, as reported by the catalog.
+    pub count: Option<u32>,
+    pub disc_number: u32,
+    pub track_number: Option<u32>,
+}
+
+impl Work {
+    /// Returns the first and last movement numbers of the work present on the album.
+    pub fn movement_range(&self) -> Option<(u32, u32)> {
+        let mut numbers = self.movements.iter().filter_map(|m| m.number);
+        let first = numbers.next()?;
+        Some(numbers.fold((first, first), |(min, max), n| (min.min(n), max.max(n))))
+    }
+
+    /// Returns the movement range formatted for display, e.g. `I-IV` or `III`.
+    pub fn movement_range_roman(&self) -> Option<String> {
+        let (first, last) = self.movement_range()?;
+        if first == last {
+            Some(roman_numeral(first))
+        } else {
+            Some(format!("{}-{}", roman_numeral(first), roman_numeral(last)))
+        }
+    }
+
+    /// Whether every movement of the work is on the album, with contiguous numbers.
+    pub fn is_complete(&self) -> bool {
+        let count = self.movements.iter().find_map(|m| m.count);
+        match (count, self.movement_range()) {
+            (Some(count), Some((1, last))) => {
+                last == count && self.movements.len() == count as usize
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the movement of a song, if the song belongs to the work.
+    pub fn movement(&self, song_id: &str) -> Option<&Movement> {
+        self.movements.iter().find(|m| m.song_id == song_id)
+    }
+
+    /// Fills the work and movement tags of a song of the work.
+    pub fn apply_to(&self, song_id: &str, tags: &mut Tags) {
+        let Some(movement) = self.movement(song_id) else {
+            return;
+        };
+        tags.work = Some(self.name.clone());
+        tags.movement_name = Some(movement.name.clone());
+        tags.movement_number = movement.number;
+        tags.movement_count = movement.count.or(Some(self.movements.len() as u32));
+        tags.show_work_movement = Some(true);
+        if tags.composer.is_none() {
+            tags.composer.clone_from(&self.composer);
+        }
+    }
+}
+
+/// Groups the songs of an album into works.
+/// Songs without a work name are skipped; a work split by other tracks is returned once per run.
+pub fn group_works<'a>(songs: impl IntoIterator<Item = &'a Songs>) -> Vec<Work> {
+    let mut works: Vec<Work> = Vec::new();
+    let mut last_work: Option<String> = None;
+    for song in songs {
+        let Some(attributes) = &song.attributes else {
+            last_work = None;
+            continue;
+        };
+        let Some(work_name) = &attributes.work_name else {
+            last_work = None;
+            continue;
+        };
+        let movement = Movement {
+            song_id: song.id.clone(),
+            name: attributes
+                .movement_name
+                .clone()
+                .unwrap_or_else(|| attributes.name.clone()),
+            number: attributes.movement_number.map(|n| n as u32),
+            count: attributes.movement_count.map(|n| n as u32),
+            disc_number: attributes.disc_number,
+            track_number: attributes.track_number.map(|n| n as u32),
+        };
+        match works.last_mut() {
+            Some(work) if last_work.as_ref() == Some(work_name) => work.movements.push(movement),
+            _ => works.push(Work {
+                name: work_name.clone(),
+                composer: attributes
+                    .attribution
+                    .clone()
+                    .or_else(|| attributes.composer_name.clone()),
+                movements: vec![movement],
+            }),
+        }
+        last_work = Some(work_name.clone());
+    }
+    works
+}
+
+/// Groups the songs of an album into works. The album must include its tracks.
+pub fn album_works(album: &Albums) -> Vec<Work> {
+    let tracks = album
+        .relationships
+        .as_ref()
+        .and_then(|relationships| relationships.tracks.as_ref());
+    group_works(
+        tracks
+            .into_iter()
+            .flat_map(|tracks| &tracks.data)
+            .filter_map(|track| match track {
+                TrackData::Songs(song) => Some(song),
+                TrackData::MusicVideos(_) => None,
+            }),
+    )
+}
+
+fn roman_numeral(mut number: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut result = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            result.push_str(numeral);
+            number -= value;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::album_works;
+    use crate::api::albums::Albums;
+    use crate::tags::Tags;
+
+    fn track(
+        id: &str,
+        track_number: i32,
+        work: Option<&str>,
+        movement: Option<(i32, &str)>,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "type": "songs",
+            "href": format!("/v1/catalog/us/songs/{id}"),
+            "attributes": {
+                "albumName": "Symphonies",
+                "artistName": "Berliner Philharmoniker",
+                "artwork": { "width": 3000, "height": 3000, "url": "https://example.com/{w}x{h}bb.jpg" },
+                "attribution": "Ludwig van Beethoven",
+                "discNumber": 1,
+                "durationInMillis": 600000,
+                "genreNames": ["Classical"],
+                "name": format!("Track {track_number}"),
+                "trackNumber": track_number,
+                "url": "https://music.apple.com/us/song/x",
+                "workName": work,
+                "movementName": movement.map(|(_, name)| name),
+                "movementNumber": movement.map(|(number, _)| number),
+                "movementCount": movement.map(|_| 4)
+            }
+        })
+    }
+
+    #[test]
+    fn test_album_works() {
+        let symphony = "Symphony No. 5 in C Minor, Op. 67";
+        let album: Albums = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "type": "albums",
+            "href": "/v1/catalog/us/albums/1",
+            "relationships": { "tracks": { "data": [
+                track("10", 1, Some(symphony), Some((1, "I. Allegro con brio"))),
+                track("11", 2, Some(symphony), Some((2, "II. Andante con moto"))),
+                track("12", 3, Some(symphony), Some((3, "III. Scherzo. Allegro"))),
+                track("13", 4, Some(symphony), Some((4, "IV. Allegro"))),
+                track("14", 5, None, None),
+                track("15", 6, Some("Egmont, Op. 84"), Some((1, "Overture")))
+            ] } }
+        }))
+        .unwrap();
+        let works = album_works(&album);
+        assert_eq!(works.len(), 2);
+        assert_eq!(works[0].name, symphony);
+        assert_eq!(works[0].composer.as_deref(), Some("Ludwig van Beethoven"));
+        assert_eq!(works[0].movement_range(), Some((1, 4)));
+        assert_eq!(works[0].movement_range_roman().as_deref(), Some("I-IV"));
+        assert!(works[0].is_complete());
+        assert!(!works[1].is_complete());
+
+        let mut tags = Tags {
+            gapless: Some(false),
+            ..Default::default()
+        };
+        works[0].apply_to("12", &mut tags);
+        assert_eq!(tags.work.as_deref(), Some(symphony));
+        assert_eq!(tags.movement_name.as_deref(), Some("III. Scherzo. Allegro"));
+        assert_eq!(tags.movement_number, Some(3));
+        assert_eq!(tags.movement_count, Some(4));
+        assert_eq!(tags.gapless, Some(false));
+
+        let ilst = tags.to_mp4_ilst();
+        let mvi = ilst.windows(4).position(|w| w == b"\xa9mvi").unwrap();
+        assert_eq!(&ilst[mvi + 20..mvi + 22], &[0, 3]);
+        assert!(ilst.windows(4).any(|w| w == b"shwm"));
+    }
+}
@@ -1,92 +1,216 @@
 //! some decryption functions.
 
-use crate::{error::Result, AppleMusicDownloader};
+use crate::error::{Error, Result};
+use crate::key_store::KeyStore;
+use crate::AppleMusicDownloader;
 use base64::Engine;
-use m3u8_rs::MasterPlaylist;
 use pssh_box::{widevine::WidevinePsshData, PsshBox, PsshData, ToBytes};
+use std::collections::BTreeMap;
 use widevine::{self, Cdm, LicenseType, Pssh};
 
 /// Requests a Widevine license from Apple Music and retrieves the decryption key.
+/// The key store of the downloader is consulted first, and the new keys are added to it.
 pub async fn get_decrypt_key(
     cdm: &Cdm,
     pssh: &str,
     id: &str,
     apple_music_downloader: &AppleMusicDownloader,
 ) -> Result<String> {
-    let session = cdm.open();
-    let key_id = base64::engine::general_purpose::STANDARD
-        .decode(pssh.split(',').collect::<Vec<&str>>().last().unwrap())?;
+    let key_id = pssh_key_id(pssh)?;
+    let keys = get_decrypt_keys(cdm, &[pssh], id, apple_music_downloader).await?;
+    content_key(&keys, &key_id)
+}
+
+/// Returns the hexadecimal content key of a key id. `get_decrypt_keys` also maps the key id
+/// padded by the license to the key id of the PSSH.
+fn content_key(keys: &BTreeMap<Vec<u8>, Vec<u8>>, key_id: &[u8]) -> Result<String> {
+    let key = keys.get(key_id).ok_or_else(|| {
+        Error::Decrypt(format!(
+            "No content key for the key id {}",
+            hex::encode(key_id)
+        ))
+    })?;
+    Ok(hex::encode(key))
+}
+
+/// Requests a Widevine license from Apple Music for the key ids of several PSSH URIs at once,
+/// e.g. the audio and video renditions of a music video, and returns the content keys by key id.
+/// The keys found in the key store of the downloader are not requested; the license is only
+/// requested when some keys are missing, and all its content keys are added to the key store.
+pub async fn get_decrypt_keys(
+    cdm: &Cdm,
+    psshs: &[&str],
+    id: &str,
+    apple_music_downloader: &AppleMusicDownloader,
+) -> Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+    let key_store = apple_music_downloader.key_store();
+    let mut keys = BTreeMap::new();
+    let mut missing = Vec::new();
+    for pssh in psshs {
+        let key_id = pssh_key_id(pssh)?;
+        match key_store.get(&key_id)? {
+            Some(key) => {
+                keys.insert(key_id, key);
+            }
+            None if !missing.iter().any(|(_, missing_id)| *missing_id == key_id) => {
+                missing.push((*pssh, key_id))
+            }
+            None => {}
+        }
+    }
+    let Some((uri, _)) = missing.first() else {
+        return Ok(keys);
+    };
+
+    let mut session = cdm.open();
+    if apple_music_downloader.privacy_mode() {
+        let certificate = apple_music_downloader
+            .get_service_certificate_of(id)
+            .await?;
+        session = session
+            .set_service_certificate(certificate.as_slice())
+            .map_err(|e| Error::ServiceCertificate(e.to_string()))?;
+    }
     let widevine_pssh_data = WidevinePsshData {
-        key_id: vec![key_id],
+        key_id: missing.iter().map(|(_, key_id)| key_id.clone()).collect(),
         algorithm: Some(1),
         ..Default::default()
     };
     let pssh_obj = PsshData::Widevine(widevine_pssh_data);
     let mut pssh_box = PsshBox::new_widevine();
-    // let key_id: DRMKeyId = key_id.try_into().unwrap();
-    // pssh_box.add_key_id(key_id);
     pssh_box.pssh_data = pssh_obj;
     pssh_box.version = 0;
-    let pssh_obj = Pssh::from_bytes(&pssh_box.to_bytes()).unwrap();
-    let cdm_license_request = session
-        .get_license_request(pssh_obj, LicenseType::STREAMING)
-        .unwrap();
+    let pssh_obj = Pssh::from_bytes(&pssh_box.to_bytes())?;
+    let cdm_license_request = session.get_license_request(pssh_obj, LicenseType::STREAMING)?;
 
+    // The license server identifies the asset by the URI of one of the keys.
     let license_message = apple_music_downloader
-        .get_widevine_license(id, pssh, cdm_license_request.challenge().unwrap())
+        .get_widevine_license(id, uri, cdm_license_request.challenge()?)
         .await?;
-    let keys = cdm_license_request.get_keys(&license_message).unwrap();
-    let key = keys.first_of_type(widevine::KeyType::CONTENT).unwrap();
-    let key_hex = hex::encode(key.key.clone());
-    Ok(key_hex)
+    let license_keys = cdm_license_request.get_keys(&license_message)?;
+    let missing: Vec<Vec<u8>> = missing.into_iter().map(|(_, key_id)| key_id).collect();
+    add_license_keys(
+        key_store,
+        license_keys.of_type(widevine::KeyType::CONTENT),
+        &missing,
+        &mut keys,
+    )?;
+    Ok(keys)
 }
 
-/// Retrieves asset information from the provided M3U8 master playlist data.
-pub fn get_assert_info(m3u8_data: &MasterPlaylist) -> Result<serde_json::Value> {
-    let assert_info = m3u8_data
-        .session_data
-        .iter()
-        .find(|data| data.data_id == "com.apple.hls.audioAssetMetadata")
-        .ok_or_else(|| crate::error::Error::Decrypt("DATA-ID not found".to_string()))?;
-    if let m3u8_rs::SessionDataField::Value(value) = &assert_info.field {
-        let assert_info = base64::engine::general_purpose::STANDARD.decode(value)?;
-        Ok(serde_json::from_slice(&assert_info)?)
-    } else {
-        Err(crate::error::Error::Decrypt(
-            "DATA-ID not found".to_string(),
-        ))
+/// Adds the content keys of a license to the key store and to `keys`. The key ids of `missing`
+/// shorter than 16 bytes are also mapped to the key of their padded key id.
+fn add_license_keys<'a>(
+    key_store: &dyn KeyStore,
+    license_keys: impl IntoIterator<Item = &'a widevine::Key>,
+    missing: &[Vec<u8>],
+    keys: &mut BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Result<()> {
+    for key in license_keys {
+        key_store.insert(&key.kid, &key.key)?;
+        keys.insert(key.kid.to_vec(), key.key.clone());
     }
-}
-
-/// Retrieves the PSSH (Protection System Specific Header) from the provided DRM information and DRM IDs.
-pub fn get_pssh(drm_infos: &serde_json::Value, drm_ids: &serde_json::Value) -> Option<String> {
-    for drm_id in drm_ids.as_array()? {
-        if let Some(drm_info) = drm_infos.get(drm_id.as_str()?) {
-            if let Some(uri) = drm_info.get("urn:uuid:edef8ba9-79d6-4ace-a3c8-27dcd51d21ed") {
-                if drm_id != "1" {
-                    return uri
-                        .get("URI")
-                        .and_then(|u| u.as_str().map(|s| s.to_string()));
-                }
-            }
+    for key_id in missing {
+        // Short key ids are padded with zeros in the license.
+        let mut padded = key_id.clone();
+        padded.resize(16, 0);
+        if let Some(key) = keys.get(&padded).filter(|_| padded != *key_id).cloned() {
+            key_store.insert(key_id, &key)?;
+            keys.insert(key_id.clone(), key);
         }
     }
-    None
+    Ok(())
 }
 
-/// Retrieves DRM information from the provided M3U8 master playlist data.
-pub fn get_drm_info(m3u8_data: &MasterPlaylist) -> Result<serde_json::Value> {
-    let drm_info = m3u8_data
-        .session_data
-        .iter()
-        .find(|data| data.data_id == "com.apple.hls.AudioSessionKeyInfo")
-        .ok_or_else(|| crate::error::Error::Decrypt("DATA-ID not found".to_string()))?;
-    if let m3u8_rs::SessionDataField::Value(value) = &drm_info.field {
-        let drm_info = base64::engine::general_purpose::STANDARD.decode(value)?;
-        Ok(serde_json::from_slice(&drm_info)?)
-    } else {
-        Err(crate::error::Error::Decrypt(
-            "DATA-ID not found".to_string(),
-        ))
+/// Returns the key id of a PSSH URI, the base64 data of the URI.
+pub fn pssh_key_id(pssh: &str) -> Result<Vec<u8>> {
+    let data = pssh.rsplit(',').next().unwrap_or(pssh);
+    Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_license_keys, content_key, get_decrypt_key, get_decrypt_keys};
+    use crate::error::Error;
+    use crate::key_store::{KeyStore, MemoryKeyStore};
+    use crate::AppleMusicDownloader;
+    use base64::Engine;
+    use std::collections::BTreeMap;
+    use widevine::{Cdm, Key, KeyType};
+
+    fn pssh(key_id: &[u8]) -> String {
+        format!(
+            "data:text/plain;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(key_id)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_decrypt_keys_from_store() {
+        // Every key id is in the key store: no license is requested.
+        let key_store = MemoryKeyStore::new();
+        key_store.insert(&[1; 16], &[9; 16]).unwrap();
+        key_store.insert(&[2; 16], &[8; 16]).unwrap();
+        let mut apple_music_downloader =
+            AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        apple_music_downloader.set_key_store(key_store);
+        // A license request would need the network and the service certificate.
+        apple_music_downloader.set_privacy_mode(true);
+        let cdm = Cdm::new(apple_music_downloader.device.clone());
+
+        let (first, second) = (pssh(&[1; 16]), pssh(&[2; 16]));
+        let keys = get_decrypt_keys(
+            &cdm,
+            &[&first, &second, &first],
+            "1753050648",
+            &apple_music_downloader,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            keys,
+            BTreeMap::from([(vec![1; 16], vec![9; 16]), (vec![2; 16], vec![8; 16])])
+        );
+        let key = get_decrypt_key(&cdm, &second, "1753050648", &apple_music_downloader)
+            .await
+            .unwrap();
+        assert_eq!(key, "08".repeat(16));
+    }
+
+    #[test]
+    fn test_add_license_keys() {
+        // A license for two key ids, one of them short and padded with zeros.
+        let key_store = MemoryKeyStore::new();
+        let license_keys = [
+            Key {
+                typ: KeyType::CONTENT,
+                kid: [1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                key: vec![9; 16],
+            },
+            Key {
+                typ: KeyType::CONTENT,
+                kid: [5; 16],
+                key: vec![8; 16],
+            },
+        ];
+        let missing = [vec![1, 2, 3, 4], vec![5; 16]];
+        let mut keys = BTreeMap::new();
+        add_license_keys(&key_store, &license_keys, &missing, &mut keys).unwrap();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[&vec![1, 2, 3, 4]], vec![9; 16]);
+        assert_eq!(keys[&vec![5; 16]], vec![8; 16]);
+        assert_eq!(key_store.get(&[1, 2, 3, 4]).unwrap(), Some(vec![9; 16]));
+        assert_eq!(key_store.get(&[5; 16]).unwrap(), Some(vec![8; 16]));
+
+        assert_eq!(content_key(&keys, &[1, 2, 3, 4]).unwrap(), "09".repeat(16));
+        match content_key(&keys, &[7; 16]) {
+            Err(Error::Decrypt(message)) => {
+                assert_eq!(
+                    message,
+                    format!("No content key for the key id {}", "07".repeat(16))
+                )
+            }
+            result => panic!("Unexpected result {result:?}"),
+        }
     }
 }
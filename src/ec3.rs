@@ -0,0 +1,262 @@
+//! the AC-3 and E-AC-3 (Dolby Digital Plus) streams, e.g. of the Dolby Atmos variants.
+//!
+//! Once decrypted by `cenc::decrypt` and remuxed, the stream is either kept as a MP4 file, with its
+//! `dac3` or `dec3` box, or written as a raw `.ac3`/`.ec3` file by `to_raw`.
+
+use crate::error::{Error, Result};
+use crate::mp4::{find_box, parse_boxes};
+use crate::remux::{remux, sample_entry_fields_len, track_samples};
+
+/// The channel counts of the audio coding modes (`acmod`), without the LFE channel.
+const ACMOD_CHANNELS: [u32; 8] = [2, 1, 2, 3, 3, 4, 4, 5];
+/// The bit rates of the AC-3 `bit_rate_code`, in kbit/s.
+const AC3_BIT_RATES: [u16; 19] = [
+    32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384, 448, 512, 576, 640,
+];
+/// The sample rates of the `fscod`.
+const SAMPLE_RATES: [u32; 3] = [48000, 44100, 32000];
+
+/// The configuration of an AC-3 or E-AC-3 stream, from its `dac3` or `dec3` box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ec3Config {
+    /// The data rate, in kbit/s.
+    pub data_rate: u16,
+    /// The independent substreams; AC-3 streams have one.
+    pub substreams: Vec<Ec3Substream>,
+    /// The complexity index of the Joint Object Coding (JOC) extension, set for Dolby Atmos.
+    pub complexity_index: Option<u8>,
+}
+
+/// An independent substream of an E-AC-3 stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ec3Substream {
+    pub fscod: u8,
+    pub bsid: u8,
+    pub asvc: bool,
+    pub bsmod: u8,
+    /// The audio coding mode, the arrangement of the main channels.
+    pub acmod: u8,
+    /// Whether the LFE channel is present.
+    pub lfeon: bool,
+    /// The number of dependent substreams.
+    pub num_dep_sub: u8,
+    /// The locations of the channels of the dependent substreams.
+    pub chan_loc: u16,
+}
+
+impl Ec3Substream {
+    /// Returns the sample rate, in Hz.
+    pub fn sample_rate(&self) -> Option<u32> {
+        SAMPLE_RATES.get(self.fscod as usize).copied()
+    }
+
+    /// Returns the numbers of main and LFE channels, with the channels of the dependent substreams.
+    pub fn channels(&self) -> (u32, u32) {
+        let mut main = ACMOD_CHANNELS[self.acmod as usize & 7];
+        let mut lfe = self.lfeon as u32;
+        if self.num_dep_sub > 0 {
+            // Lc/Rc, Lrs/Rrs, Cs, Ts, Lsd/Rsd, Lw/Rw, Lvh/Rvh, Cvh and LFE2, from the first bit.
+            for (index, count) in [2, 2, 1, 1, 2, 2, 2, 1, 0].into_iter().enumerate() {
+                if self.chan_loc & (0x100 >> index) != 0 {
+                    main += count;
+                    lfe += (count == 0) as u32;
+                }
+            }
+        }
+        (main, lfe)
+    }
+}
+
+impl Ec3Config {
+    /// Parses the payload of a `dec3` box.
+    pub fn from_dec3(payload: &[u8]) -> Result<Self> {
+        let mut bits = BitReader::new(payload);
+        let data_rate = bits.read(13)? as u16;
+        let num_ind_sub = bits.read(3)? + 1;
+        let mut substreams = Vec::with_capacity(num_ind_sub as usize);
+        for _ in 0..num_ind_sub {
+            let fscod = bits.read(2)? as u8;
+            let bsid = bits.read(5)? as u8;
+            bits.read(1)?;
+            let asvc = bits.read(1)? != 0;
+            let bsmod = bits.read(3)? as u8;
+            let acmod = bits.read(3)? as u8;
+            let lfeon = bits.read(1)? != 0;
+            bits.read(3)?;
+            let num_dep_sub = bits.read(4)? as u8;
+            let chan_loc = if num_dep_sub > 0 {
+                bits.read(9)? as u16
+            } else {
+                bits.read(1)?;
+                0
+            };
+            substreams.push(Ec3Substream {
+                fscod,
+                bsid,
+                asvc,
+                bsmod,
+                acmod,
+                lfeon,
+                num_dep_sub,
+                chan_loc,
+            });
+        }
+        // The optional extension of Dolby Atmos.
+        let mut complexity_index = None;
+        if bits.remaining() >= 16 {
+            bits.read(7)?;
+            if bits.read(1)? != 0 {
+                complexity_index = Some(bits.read(8)? as u8);
+            }
+        }
+        Ok(Self {
+            data_rate,
+            substreams,
+            complexity_index,
+        })
+    }
+
+    /// Parses the payload of a `dac3` box.
+    pub fn from_dac3(payload: &[u8]) -> Result<Self> {
+        let mut bits = BitReader::new(payload);
+        let fscod = bits.read(2)? as u8;
+        let bsid = bits.read(5)? as u8;
+        let bsmod = bits.read(3)? as u8;
+        let acmod = bits.read(3)? as u8;
+        let lfeon = bits.read(1)? != 0;
+        let bit_rate_code = bits.read(5)? as usize;
+        Ok(Self {
+            data_rate: AC3_BIT_RATES
+                .get(bit_rate_code)
+                .copied()
+                .unwrap_or_default(),
+            substreams: vec![Ec3Substream {
+                fscod,
+                bsid,
+                asvc: false,
+                bsmod,
+                acmod,
+                lfeon,
+                num_dep_sub: 0,
+                chan_loc: 0,
+            }],
+            complexity_index: None,
+        })
+    }
+
+    /// Reads the configuration of the first AC-3 or E-AC-3 track of a MP4 file,
+    /// encrypted or not.
+    pub fn from_mp4(mp4: &[u8]) -> Result<Self> {
+        let stsd = find_box(mp4, &[b"moov", b"trak", b"mdia", b"minf", b"stbl", b"stsd"])?
+            .ok_or_else(|| Error::Mp4("stsd box not found".to_string()))?;
+        let entries = stsd
+            .payload()
+            .get(8..)
+            .ok_or_else(|| Error::Mp4("Truncated stsd box".to_string()))?;
+        for entry in parse_boxes(entries)? {
+            if !matches!(&entry.name, b"ec-3" | b"ac-3" | b"enca") {
+                continue;
+            }
+            let children = entry
+                .payload()
+                .get(sample_entry_fields_len(&entry)?..)
+                .ok_or_else(|| Error::Mp4("Truncated sample entry".to_string()))?;
+            for child in parse_boxes(children)? {
+                match &child.name {
+                    b"dec3" => return Self::from_dec3(child.payload()),
+                    b"dac3" => return Self::from_dac3(child.payload()),
+                    _ => {}
+                }
+            }
+        }
+        Err(Error::Mp4("dec3 box not found".to_string()))
+    }
+
+    /// Returns whether the stream carries Dolby Atmos, i.e. the JOC extension.
+    pub fn is_atmos(&self) -> bool {
+        self.complexity_index.is_some()
+    }
+
+    /// Returns the channel layout of the first independent substream, e.g. `5.1`.
+    pub fn channel_layout(&self) -> String {
+        let (main, lfe) = self
+            .substreams
+            .first()
+            .map(Ec3Substream::channels)
+            .unwrap_or_default();
+        format!("{main}.{lfe}")
+    }
+}
+
+/// Returns the raw AC-3 or E-AC-3 stream of a decrypted MP4 file, fragmented or not,
+/// i.e. the concatenation of its syncframes.
+pub fn to_raw(mp4: &[u8]) -> Result<Vec<u8>> {
+    let mp4 = remux(mp4)?;
+    Ok(track_samples(&mp4)?.concat())
+}
+
+/// A MSB-first bit reader over the payload of a box.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() * 8 - self.position
+    }
+
+    fn read(&mut self, count: usize) -> Result<u32> {
+        if count > self.remaining() {
+            return Err(Error::Mp4("Truncated AC-3 configuration".to_string()));
+        }
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = self.data[self.position / 8] >> (7 - self.position % 8) & 1;
+            value = value << 1 | bit as u32;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ec3Config;
+    use crate::mp4::{mp4_box, mp4_full_box};
+
+    #[test]
+    fn test_ec3_config() {
+        // 768 kbit/s, 48 kHz, 5.1 with the JOC extension of complexity 16.
+        let dec3 = [0x18, 0x00, 0x20, 0x0f, 0x00, 0x01, 0x10];
+        let config = Ec3Config::from_dec3(&dec3).unwrap();
+        assert_eq!(config.data_rate, 768);
+        assert_eq!(config.substreams.len(), 1);
+        assert_eq!(config.substreams[0].bsid, 16);
+        assert_eq!(config.substreams[0].acmod, 7);
+        assert_eq!(config.substreams[0].sample_rate(), Some(48000));
+        assert!(config.is_atmos());
+        assert_eq!(config.complexity_index, Some(16));
+        assert_eq!(config.channel_layout(), "5.1");
+
+        // A dependent substream with Lrs/Rrs: 7.1.
+        let dec3 = [0x04, 0x00, 0x20, 0x0f, 0x02, 0x80];
+        let config = Ec3Config::from_dec3(&dec3).unwrap();
+        assert!(!config.is_atmos());
+        assert_eq!(config.channel_layout(), "7.1");
+
+        // 448 kbit/s AC-3 in 3/2 with LFE, in a sample entry.
+        let dac3 = [0x10, 0x3d, 0xe0];
+        let entry = mp4_box(b"ac-3", &[vec![0; 28], mp4_box(b"dac3", &dac3)].concat());
+        let stsd = mp4_full_box(b"stsd", 0, 0, &[&1u32.to_be_bytes()[..], &entry].concat());
+        let stbl = mp4_box(b"stbl", &stsd);
+        let trak = mp4_box(b"trak", &mp4_box(b"mdia", &mp4_box(b"minf", &stbl)));
+        let config = Ec3Config::from_mp4(&mp4_box(b"moov", &trak)).unwrap();
+        assert_eq!(config.data_rate, 448);
+        assert_eq!(config.channel_layout(), "5.1");
+    }
+}
@@ -20,12 +20,30 @@ pub enum Error {
     #[error("An error occurred while decrypting: {0}")]
     Decrypt(String),
 
+    #[error("An error occurred in the Widevine CDM: {0}")]
+    Widevine(#[from] widevine::Error),
+
+    #[error("An error occurred with the Widevine service certificate: {0}")]
+    ServiceCertificate(String),
+
     #[error("An error occurred while decode base64: {0}")]
     Base64(#[from] base64::DecodeError),
 
     #[error("An error occurred while initializing ramdl: {0}")]
     Init(String),
 
+    #[error("An error occurred while processing MP4: {0}")]
+    Mp4(String),
+
+    #[error("An error occurred while decoding ALAC: {0}")]
+    Alac(String),
+
+    #[error("An error occurred while encoding FLAC: {0}")]
+    Flac(String),
+
+    #[error("An I/O error occurred: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("An error occurred while joining threads: {0}")]
     JoinError(#[from] JoinError),
 
@@ -0,0 +1,700 @@
+//! the FLAC encoder.
+//!
+//! The encoder writes fixed-size blocks with fixed predictors, Rice-coded residuals and
+//! stereo decorrelation. The metadata is written as Vorbis comments and a front cover picture.
+
+use crate::alac::{self, Pcm};
+use crate::error::{Error, Result};
+use crate::tags::Tags;
+use md5::{Digest, Md5};
+
+/// The number of samples per channel of a frame.
+pub const BLOCK_SIZE: usize = 4096;
+
+const VENDOR: &str = concat!("ramdl ", env!("CARGO_PKG_VERSION"));
+
+/// Metadata block types.
+const STREAMINFO: u8 = 0;
+const VORBIS_COMMENT: u8 = 4;
+const PICTURE: u8 = 6;
+/// The picture type of a front cover.
+const PICTURE_FRONT_COVER: u32 = 3;
+
+/// Channel assignments of a frame.
+const LEFT_SIDE: u32 = 8;
+const SIDE_RIGHT: u32 = 9;
+const MID_SIDE: u32 = 10;
+
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 8;
+const MAX_RICE_PARAMETER: u32 = 30;
+
+/// Encodes PCM samples into a FLAC file with the tags as metadata.
+pub fn encode(pcm: &Pcm, tags: &Tags) -> Result<Vec<u8>> {
+    if !(1..=8).contains(&pcm.channels) {
+        return Err(Error::Flac(format!(
+            "Unsupported channel count {}",
+            pcm.channels
+        )));
+    }
+    if !(4..=24).contains(&pcm.bits_per_sample) {
+        return Err(Error::Flac(format!(
+            "Unsupported bit depth {}",
+            pcm.bits_per_sample
+        )));
+    }
+    if !(1..1 << 20).contains(&pcm.sample_rate) {
+        return Err(Error::Flac(format!(
+            "Unsupported sample rate {}",
+            pcm.sample_rate
+        )));
+    }
+    let channels = pcm.channels as usize;
+    let mut frames = Vec::new();
+    let (mut min_frame_size, mut max_frame_size) = (u32::MAX, 0);
+    for (index, block) in pcm.samples.chunks(BLOCK_SIZE * channels).enumerate() {
+        let frame = encode_frame(pcm, index as u32, block);
+        min_frame_size = min_frame_size.min(frame.len() as u32);
+        max_frame_size = max_frame_size.max(frame.len() as u32);
+        frames.push(frame);
+    }
+
+    let mut stream_info = BitWriter::default();
+    let block_size = BLOCK_SIZE.min(pcm.frames()).max(16) as u64;
+    stream_info.write(block_size, 16);
+    stream_info.write(block_size, 16);
+    stream_info.write(
+        if frames.is_empty() { 0 } else { min_frame_size } as u64,
+        24,
+    );
+    stream_info.write(max_frame_size as u64, 24);
+    stream_info.write(pcm.sample_rate as u64, 20);
+    stream_info.write(pcm.channels as u64 - 1, 3);
+    stream_info.write(pcm.bits_per_sample as u64 - 1, 5);
+    stream_info.write(pcm.frames() as u64 >> 32, 4);
+    stream_info.write(pcm.frames() as u64, 32);
+    let mut stream_info = stream_info.finish();
+    stream_info.extend(md5_signature(pcm));
+
+    let mut blocks = vec![
+        (STREAMINFO, stream_info),
+        (VORBIS_COMMENT, vorbis_comment(tags)),
+    ];
+    if let Some(cover) = &tags.cover {
+        blocks.push((PICTURE, picture(cover)));
+    }
+    let mut flac = b"fLaC".to_vec();
+    let last = blocks.len() - 1;
+    for (index, (block_type, data)) in blocks.into_iter().enumerate() {
+        if data.len() >= 1 << 24 {
+            return Err(Error::Flac("Metadata block too large".to_string()));
+        }
+        flac.push(((index == last) as u8) << 7 | block_type);
+        flac.extend(&(data.len() as u32).to_be_bytes()[1..]);
+        flac.extend(data);
+    }
+    for frame in frames {
+        flac.extend(frame);
+    }
+    Ok(flac)
+}
+
+/// Decodes an ALAC MP4 file, e.g. a decrypted Apple Music song, and encodes it into
+/// a FLAC file with the tags as metadata.
+pub fn encode_alac_mp4(mp4: &[u8], tags: &Tags) -> Result<Vec<u8>> {
+    encode(&to_flac_channel_order(alac::decode_mp4(mp4)?)?, tags)
+}
+
+/// Reorders the channels of decoded ALAC samples into the FLAC channel order. ALAC puts the center
+/// channel first (`C L R …`), FLAC after the front pair (`L R C …`). The 4 and 8 channel layouts
+/// of ALAC (with a back center, and with front center pairs) have no FLAC counterpart.
+fn to_flac_channel_order(mut pcm: Pcm) -> Result<Pcm> {
+    // The ALAC channel of each FLAC channel.
+    let order: &[usize] = match pcm.channels {
+        1 | 2 => return Ok(pcm),
+        // C L R → L R C
+        3 => &[1, 2, 0],
+        // C L R Ls Rs → L R C Ls Rs
+        5 => &[1, 2, 0, 3, 4],
+        // C L R Ls Rs LFE → L R C LFE Ls Rs
+        6 => &[1, 2, 0, 5, 3, 4],
+        // C L R Ls Rs Cs LFE → L R C LFE Cs Ls Rs
+        7 => &[1, 2, 0, 6, 5, 3, 4],
+        channels => {
+            return Err(Error::Flac(format!(
+                "Unsupported ALAC channel layout of {channels} channels"
+            )))
+        }
+    };
+    for frame in pcm.samples.chunks_exact_mut(order.len()) {
+        let alac_frame = frame.to_vec();
+        for (sample, &channel) in frame.iter_mut().zip(order) {
+            *sample = alac_frame[channel];
+        }
+    }
+    Ok(pcm)
+}
+
+/// Serializes the tags as a `VORBIS_COMMENT` block.
+pub fn vorbis_comment(tags: &Tags) -> Vec<u8> {
+    let comments = tags.to_vorbis_comments();
+    let mut data = Vec::new();
+    data.extend((VENDOR.len() as u32).to_le_bytes());
+    data.extend(VENDOR.as_bytes());
+    data.extend((comments.len() as u32).to_le_bytes());
+    for (name, value) in comments {
+        let comment = format!("{name}={value}");
+        data.extend((comment.len() as u32).to_le_bytes());
+        data.extend(comment.as_bytes());
+    }
+    data
+}
+
+/// Serializes a cover image, JPEG or PNG, as a front cover `PICTURE` block.
+pub fn picture(image: &[u8]) -> Vec<u8> {
+    let (mime_type, (width, height)) = if image.starts_with(b"\x89PNG") {
+        ("image/png", png_size(image).unwrap_or_default())
+    } else {
+        ("image/jpeg", jpeg_size(image).unwrap_or_default())
+    };
+    let mut data = Vec::with_capacity(image.len() + 64);
+    data.extend(PICTURE_FRONT_COVER.to_be_bytes());
+    data.extend((mime_type.len() as u32).to_be_bytes());
+    data.extend(mime_type.as_bytes());
+    // An empty description.
+    data.extend(0u32.to_be_bytes());
+    data.extend(width.to_be_bytes());
+    data.extend(height.to_be_bytes());
+    data.extend(24u32.to_be_bytes());
+    data.extend(0u32.to_be_bytes());
+    data.extend((image.len() as u32).to_be_bytes());
+    data.extend(image);
+    data
+}
+
+fn png_size(image: &[u8]) -> Option<(u32, u32)> {
+    if image.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((
+        u32::from_be_bytes(image.get(16..20)?.try_into().ok()?),
+        u32::from_be_bytes(image.get(20..24)?.try_into().ok()?),
+    ))
+}
+
+fn jpeg_size(image: &[u8]) -> Option<(u32, u32)> {
+    let mut offset = 2;
+    while *image.get(offset)? == 0xff {
+        let marker = *image.get(offset + 1)?;
+        let len = u16::from_be_bytes(image.get(offset + 2..offset + 4)?.try_into().ok()?) as usize;
+        // The start of frame markers, except DHT, JPG and DAC.
+        if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+            let height = u16::from_be_bytes(image.get(offset + 5..offset + 7)?.try_into().ok()?);
+            let width = u16::from_be_bytes(image.get(offset + 7..offset + 9)?.try_into().ok()?);
+            return Some((width as u32, height as u32));
+        }
+        offset += 2 + len;
+    }
+    None
+}
+
+/// Returns the MD5 signature of the samples, as little-endian bytes of the sample size.
+fn md5_signature(pcm: &Pcm) -> [u8; 16] {
+    let bytes = (pcm.bits_per_sample as usize).div_ceil(8);
+    let mut md5 = Md5::new();
+    for block in pcm.samples.chunks(BLOCK_SIZE) {
+        let data: Vec<u8> = block
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes().into_iter().take(bytes))
+            .collect();
+        md5.update(&data);
+    }
+    md5.finalize().into()
+}
+
+fn encode_frame(pcm: &Pcm, index: u32, block: &[i32]) -> Vec<u8> {
+    let channels = pcm.channels as usize;
+    let bits_per_sample = pcm.bits_per_sample as u32;
+    let len = block.len() / channels;
+    let mut channel_samples: Vec<Vec<i64>> = (0..channels)
+        .map(|channel| {
+            block
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .map(|&sample| sample as i64)
+                .collect()
+        })
+        .collect();
+
+    // One subframe per channel, or a stereo pair decorrelated with its side channel.
+    let mut assignment = channels as u32 - 1;
+    let mut subframes: Vec<(Vec<i64>, u32, Subframe)> = Vec::with_capacity(channels);
+    if channels == 2 {
+        let right = channel_samples.pop().unwrap();
+        let left = channel_samples.pop().unwrap();
+        let side: Vec<i64> = left.iter().zip(&right).map(|(l, r)| l - r).collect();
+        let mid: Vec<i64> = left.iter().zip(&right).map(|(l, r)| (l + r) >> 1).collect();
+        let plan = |samples: Vec<i64>, bits: u32| {
+            let subframe = Subframe::plan(&samples, bits);
+            (samples, bits, subframe)
+        };
+        let left = plan(left, bits_per_sample);
+        let right = plan(right, bits_per_sample);
+        let side = plan(side, bits_per_sample + 1);
+        let mid = plan(mid, bits_per_sample);
+        let candidates = [
+            (1, left.2.bits + right.2.bits),
+            (LEFT_SIDE, left.2.bits + side.2.bits),
+            (SIDE_RIGHT, side.2.bits + right.2.bits),
+            (MID_SIDE, mid.2.bits + side.2.bits),
+        ];
+        assignment = candidates.iter().min_by_key(|(_, bits)| *bits).unwrap().0;
+        subframes = match assignment {
+            LEFT_SIDE => vec![left, side],
+            SIDE_RIGHT => vec![side, right],
+            MID_SIDE => vec![mid, side],
+            _ => vec![left, right],
+        };
+    } else {
+        for samples in channel_samples {
+            let subframe = Subframe::plan(&samples, bits_per_sample);
+            subframes.push((samples, bits_per_sample, subframe));
+        }
+    }
+
+    let mut writer = BitWriter::default();
+    writer.write(0b11_1111_1111_1110, 14);
+    writer.write(0, 1);
+    // Fixed block size.
+    writer.write(0, 1);
+    let block_size_code = if len == BLOCK_SIZE { 12 } else { 7 };
+    writer.write(block_size_code, 4);
+    writer.write(sample_rate_code(pcm.sample_rate), 4);
+    writer.write(assignment as u64, 4);
+    writer.write(sample_size_code(bits_per_sample), 3);
+    writer.write(0, 1);
+    for byte in utf8_number(index) {
+        writer.write(byte as u64, 8);
+    }
+    if block_size_code == 7 {
+        writer.write(len as u64 - 1, 16);
+    }
+    let crc = crc8(&writer.bytes);
+    writer.write(crc as u64, 8);
+    for (samples, bits, subframe) in &subframes {
+        subframe.write(&mut writer, samples, *bits);
+    }
+    let mut frame = writer.finish();
+    let crc = crc16(&frame);
+    frame.extend(crc.to_be_bytes());
+    frame
+}
+
+/// The encoding of a channel of a frame.
+struct Subframe {
+    kind: SubframeKind,
+    /// The size of the encoded subframe in bits.
+    bits: usize,
+}
+
+enum SubframeKind {
+    Constant,
+    Verbatim,
+    Fixed {
+        order: usize,
+        partition_order: u32,
+        parameters: Vec<u32>,
+    },
+}
+
+impl Subframe {
+    /// Chooses the smallest encoding of the samples of a channel.
+    fn plan(samples: &[i64], bits_per_sample: u32) -> Self {
+        let len = samples.len();
+        if samples.iter().all(|&sample| sample == samples[0]) {
+            return Self {
+                kind: SubframeKind::Constant,
+                bits: 8 + bits_per_sample as usize,
+            };
+        }
+        let mut best = Self {
+            kind: SubframeKind::Verbatim,
+            bits: 8 + len * bits_per_sample as usize,
+        };
+        for order in 0..=MAX_FIXED_ORDER.min(len - 1) {
+            let residual = fixed_residual(samples, order);
+            let (partition_order, parameters, residual_bits) = plan_rice(&residual, order, len);
+            let bits = 8 + order * bits_per_sample as usize + residual_bits;
+            if bits < best.bits {
+                best = Self {
+                    kind: SubframeKind::Fixed {
+                        order,
+                        partition_order,
+                        parameters,
+                    },
+                    bits,
+                };
+            }
+        }
+        best
+    }
+
+    fn write(&self, writer: &mut BitWriter, samples: &[i64], bits_per_sample: u32) {
+        // The zero padding bit, the subframe type and no wasted bits.
+        match &self.kind {
+            SubframeKind::Constant => {
+                writer.write(0, 8);
+                writer.write_signed(samples[0], bits_per_sample);
+            }
+            SubframeKind::Verbatim => {
+                writer.write(0b0000_0010, 8);
+                for &sample in samples {
+                    writer.write_signed(sample, bits_per_sample);
+                }
+            }
+            SubframeKind::Fixed {
+                order,
+                partition_order,
+                parameters,
+            } => {
+                writer.write((0b0000_1000 | *order as u64) << 1, 8);
+                for &sample in &samples[..*order] {
+                    writer.write_signed(sample, bits_per_sample);
+                }
+                let residual = fixed_residual(samples, *order);
+                let wide = parameters.iter().any(|&k| k > 14);
+                // The residual coding method: 4-bit or 5-bit Rice parameters.
+                writer.write(wide as u64, 2);
+                writer.write(*partition_order as u64, 4);
+                let partition_len = samples.len() >> partition_order;
+                let mut start = 0;
+                for (partition, &k) in parameters.iter().enumerate() {
+                    let end = (partition + 1) * partition_len - order;
+                    writer.write(k as u64, if wide { 5 } else { 4 });
+                    for &value in &residual[start..end] {
+                        let value = zigzag(value);
+                        writer.write_unary(value >> k);
+                        writer.write(value, k);
+                    }
+                    start = end;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the residual of a fixed predictor, without the warm-up samples.
+fn fixed_residual(samples: &[i64], order: usize) -> Vec<i64> {
+    let x = samples;
+    (order..samples.len())
+        .map(|i| match order {
+            0 => x[i],
+            1 => x[i] - x[i - 1],
+            2 => x[i] - 2 * x[i - 1] + x[i - 2],
+            3 => x[i] - 3 * x[i - 1] + 3 * x[i - 2] - x[i - 3],
+            _ => x[i] - 4 * x[i - 1] + 6 * x[i - 2] - 4 * x[i - 3] + x[i - 4],
+        })
+        .collect()
+}
+
+/// Chooses the partition order and Rice parameters of a residual.
+/// Returns them with the size in bits of the coded residual.
+fn plan_rice(residual: &[i64], order: usize, len: usize) -> (u32, Vec<u32>, usize) {
+    let mut best: Option<(u32, Vec<u32>, usize)> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1 << partition_order;
+        if !len.is_multiple_of(partitions) || len / partitions <= order {
+            break;
+        }
+        let partition_len = len / partitions;
+        let mut parameters = Vec::with_capacity(partitions);
+        let mut bits = 6;
+        let mut start = 0;
+        for partition in 0..partitions {
+            let end = (partition + 1) * partition_len - order;
+            let count = (end - start) as u64;
+            let sum: u64 = residual[start..end].iter().map(|&r| zigzag(r)).sum();
+            let (k, partition_bits) = (0..=MAX_RICE_PARAMETER)
+                .map(|k| (k, count * (k as u64 + 1) + (sum >> k)))
+                .min_by_key(|(_, bits)| *bits)
+                .unwrap();
+            parameters.push(k);
+            bits += partition_bits as usize;
+            start = end;
+        }
+        let wide = parameters.iter().any(|&k| k > 14);
+        bits += partitions * if wide { 5 } else { 4 };
+        if best
+            .as_ref()
+            .is_none_or(|(_, _, best_bits)| bits < *best_bits)
+        {
+            best = Some((partition_order, parameters, bits));
+        }
+    }
+    best.unwrap_or_default()
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn sample_rate_code(sample_rate: u32) -> u64 {
+    match sample_rate {
+        88200 => 1,
+        176400 => 2,
+        192000 => 3,
+        8000 => 4,
+        16000 => 5,
+        22050 => 6,
+        24000 => 7,
+        32000 => 8,
+        44100 => 9,
+        48000 => 10,
+        96000 => 11,
+        // Taken from the STREAMINFO block.
+        _ => 0,
+    }
+}
+
+fn sample_size_code(bits_per_sample: u32) -> u64 {
+    match bits_per_sample {
+        8 => 1,
+        12 => 2,
+        16 => 4,
+        20 => 5,
+        24 => 6,
+        _ => 0,
+    }
+}
+
+/// Encodes a frame number as in UTF-8.
+fn utf8_number(number: u32) -> Vec<u8> {
+    if number < 0x80 {
+        return vec![number as u8];
+    }
+    let len = match number {
+        0..0x800 => 2,
+        0x800..0x10000 => 3,
+        0x10000..0x200000 => 4,
+        0x200000..0x4000000 => 5,
+        _ => 6,
+    };
+    let mut bytes = vec![(0xff00u32 >> len) as u8 | (number >> (6 * (len - 1))) as u8];
+    for i in (0..len - 1).rev() {
+        bytes.push(0x80 | (number >> (6 * i)) as u8 & 0x3f);
+    }
+    bytes
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                crc << 1 ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| {
+            if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x8005
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// A big-endian bit writer.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    /// Writes the `count` low bits of a value, at most 32.
+    fn write(&mut self, value: u64, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.buffer = self.buffer << count | (value & ((1 << count) - 1));
+        self.len += count;
+        while self.len >= 8 {
+            self.len -= 8;
+            self.bytes.push((self.buffer >> self.len) as u8);
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, count: u32) {
+        self.write(value as u64, count);
+    }
+
+    /// Writes `value` zeros followed by a one.
+    fn write_unary(&mut self, mut value: u64) {
+        while value >= 32 {
+            self.write(0, 32);
+            value -= 32;
+        }
+        self.write(1, value as u32 + 1);
+    }
+
+    /// Pads the last byte with zeros and returns the bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.write(0, 8 - self.len);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16, encode, to_flac_channel_order, utf8_number};
+    use crate::alac::Pcm;
+    use crate::tags::Tags;
+    use md5::{Digest, Md5};
+
+    #[test]
+    fn test_encode() {
+        let samples: Vec<i32> = (0..5000)
+            .flat_map(|i| [(i % 100) * 300 - 15000, 0])
+            .collect();
+        let pcm = Pcm {
+            sample_rate: 44100,
+            channels: 2,
+            bits_per_sample: 16,
+            samples,
+        };
+        let tags = Tags {
+            title: Some("Snow Halation".to_string()),
+            track_number: Some(1),
+            track_count: Some(3),
+            cover: Some(b"\xff\xd8\xff\xc0\x00\x11\x08\x02\x58\x03\x20".to_vec()),
+            ..Default::default()
+        };
+        let flac = encode(&pcm, &tags).unwrap();
+        assert_eq!(&flac[..4], b"fLaC");
+
+        // STREAMINFO: the block sizes, the stream format and the sample count.
+        assert_eq!(flac[4], 0);
+        assert_eq!(&flac[8..12], &[0x10, 0x00, 0x10, 0x00]);
+        assert_eq!(&flac[18..22], &[0x0a, 0xc4, 0x42, 0xf0]);
+        assert_eq!(&flac[22..26], &5000u32.to_be_bytes());
+
+        let mut offset = 42;
+        let mut blocks = Vec::new();
+        loop {
+            let header = flac[offset];
+            let len = u32::from_be_bytes([0, flac[offset + 1], flac[offset + 2], flac[offset + 3]]);
+            blocks.push((header & 0x7f, &flac[offset + 4..offset + 4 + len as usize]));
+            offset += 4 + len as usize;
+            if header & 0x80 != 0 {
+                break;
+            }
+        }
+        assert_eq!(blocks[0].0, 4);
+        let comments = String::from_utf8_lossy(blocks[0].1);
+        assert!(comments.contains("TITLE=Snow Halation"));
+        assert!(comments.contains("TRACKNUMBER=1"));
+        assert!(comments.contains("TRACKTOTAL=3"));
+        assert_eq!(blocks[1].0, 6);
+        let picture = blocks[1].1;
+        assert_eq!(&picture[..4], &3u32.to_be_bytes());
+        assert_eq!(&picture[8..18], b"image/jpeg");
+        // The width and height of the cover.
+        assert_eq!(&picture[22..30], &[0, 0, 3, 0x20, 0, 0, 2, 0x58]);
+
+        // Two frames, each ending with the CRC-16 of its content.
+        let frames = &flac[offset..];
+        assert_eq!(&frames[..2], &[0xff, 0xf8]);
+        let second = frames
+            .windows(5)
+            .position(|w| w[..3] == [0xff, 0xf8, 0x79] && w[4] == 1)
+            .unwrap();
+        assert_eq!(crc16(&frames[..second]), 0);
+        assert_eq!(crc16(&frames[second..]), 0);
+
+        assert_eq!(utf8_number(0x7f), [0x7f]);
+        assert_eq!(utf8_number(0x80), [0xc2, 0x80]);
+        assert_eq!(utf8_number(0x10000), [0xf0, 0x90, 0x80, 0x80]);
+    }
+
+    #[test]
+    fn test_decode() {
+        for (channels, bits_per_sample) in [(1, 16), (2, 16), (3, 24)] {
+            let max = (1 << (bits_per_sample - 1)) - 1;
+            // Independent channels with noise, silence and full scale values, over several blocks.
+            let samples: Vec<i32> = (0..10000i64)
+                .flat_map(|i| {
+                    (0..channels as i64).map(move |channel| {
+                        let noise = (i * 7919 + channel * 104729) % 2000 - 1000;
+                        match (i / 1500 + channel) % 4 {
+                            0 => ((i * (channel + 1) * 37) % (2 * max as i64) - max as i64) as i32,
+                            1 => 0,
+                            2 => {
+                                if i % 2 == 0 {
+                                    max
+                                } else {
+                                    -max - 1
+                                }
+                            }
+                            _ => ((i % 300) * 50 + noise) as i32,
+                        }
+                    })
+                })
+                .collect();
+            let pcm = Pcm {
+                sample_rate: 96000,
+                channels,
+                bits_per_sample,
+                samples,
+            };
+            let flac = encode(&pcm, &Tags::default()).unwrap();
+
+            let mut reader = claxon::FlacReader::new(std::io::Cursor::new(flac)).unwrap();
+            let info = reader.streaminfo();
+            assert_eq!(info.channels, channels as u32);
+            assert_eq!(info.bits_per_sample, bits_per_sample as u32);
+            assert_eq!(info.sample_rate, 96000);
+            assert_eq!(info.samples, Some(10000));
+            let bytes = bits_per_sample as usize / 8;
+            let md5: [u8; 16] = Md5::digest(
+                pcm.samples
+                    .iter()
+                    .flat_map(|sample| sample.to_le_bytes()[..bytes].to_vec())
+                    .collect::<Vec<u8>>(),
+            )
+            .into();
+            assert_eq!(info.md5sum, md5);
+            let decoded: Vec<i32> = reader.samples().map(Result::unwrap).collect();
+            assert_eq!(decoded, pcm.samples);
+        }
+    }
+
+    #[test]
+    fn test_channel_order() {
+        // Two frames of 5.1 in the ALAC order: C L R Ls Rs LFE.
+        let pcm = Pcm {
+            sample_rate: 48000,
+            channels: 6,
+            bits_per_sample: 24,
+            samples: vec![3, 1, 2, 5, 6, 4, 13, 11, 12, 15, 16, 14],
+        };
+        let pcm = to_flac_channel_order(pcm).unwrap();
+        assert_eq!(pcm.samples, [1, 2, 3, 4, 5, 6, 11, 12, 13, 14, 15, 16]);
+        let quad = Pcm {
+            channels: 4,
+            samples: vec![0; 8],
+            ..pcm
+        };
+        assert!(to_flac_channel_order(quad).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! the gapless playback metadata of output files.
+//!
+//! AAC streams start with priming samples (the encoder delay) and end with padding samples that
+//! fill the last frame. Players skip them with the edit list of the track, or with the `iTunSMPB`
+//! tag, so that the tracks of a gapless album play without a gap or a click between them.
+
+use crate::error::{Error, Result};
+use crate::mp4::{find_box, mp4_box, replace_moov, Mp4Box};
+use crate::remux::{build_edts, first_edit, patch_duration, read_u32, read_u64, timescale_offset};
+
+/// The encoder delay and padding of a track, in samples of the media timescale.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GaplessInfo {
+    /// The number of priming samples at the start of the track.
+    pub encoder_delay: u32,
+    /// The number of padding samples at the end of the track.
+    pub padding: u32,
+    /// The number of samples presented, without the priming and padding samples.
+    pub valid_samples: u64,
+}
+
+impl GaplessInfo {
+    /// Reads the gapless info of the first track of a progressive MP4 file, e.g. from `remux::remux`.
+    ///
+    /// The encoder delay is the media time of the edit list, else 0: the `roll` sample group is the
+    /// pre-roll of the decoder, not the priming samples of the encoder. The valid samples are the duration of the edit list; fragmented streams usually have
+    /// no such duration, so `duration_ms`, e.g. the duration of the asset metadata, is used instead.
+    /// Without both, the track is assumed to have no padding.
+    pub fn from_mp4(mp4: &[u8], duration_ms: Option<u64>) -> Result<Self> {
+        let mvhd = find_box(mp4, &[b"moov", b"mvhd"])?
+            .ok_or_else(|| Error::Mp4("mvhd box not found".to_string()))?;
+        let movie_timescale = read_u32(mvhd.payload(), timescale_offset(mvhd.payload()))? as u64;
+        let trak = find_box(mp4, &[b"moov", b"trak"])?
+            .ok_or_else(|| Error::Mp4("trak box not found".to_string()))?;
+        let mdhd = find_box(trak.payload(), &[b"mdia", b"mdhd"])?
+            .ok_or_else(|| Error::Mp4("mdhd box not found".to_string()))?;
+        let media_timescale = read_u32(mdhd.payload(), timescale_offset(mdhd.payload()))? as u64;
+        let stbl = find_box(trak.payload(), &[b"mdia", b"minf", b"stbl"])?
+            .ok_or_else(|| Error::Mp4("stbl box not found".to_string()))?;
+        let durations = sample_durations(&stbl)?;
+        let total: u64 = durations
+            .iter()
+            .map(|(count, delta)| *count as u64 * *delta as u64)
+            .sum();
+
+        let (segment_duration, media_time) = match find_box(trak.payload(), &[b"edts", b"elst"])? {
+            Some(elst) => first_edit(elst.payload())?,
+            None => (0, 0),
+        };
+        let encoder_delay = media_time.max(0) as u64;
+        let remaining = total.saturating_sub(encoder_delay);
+        let valid_samples = if segment_duration > 0 && movie_timescale > 0 {
+            segment_duration * media_timescale / movie_timescale
+        } else if let Some(duration_ms) = duration_ms {
+            duration_ms * media_timescale / 1000
+        } else {
+            remaining
+        }
+        .min(remaining);
+        Ok(Self {
+            encoder_delay: encoder_delay as u32,
+            padding: (remaining - valid_samples) as u32,
+            valid_samples,
+        })
+    }
+
+    /// Parses the value of an `iTunSMPB` tag.
+    pub fn from_itunsmpb(value: &str) -> Option<Self> {
+        let mut fields = value.split_whitespace().skip(1);
+        let encoder_delay = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let padding = u32::from_str_radix(fields.next()?, 16).ok()?;
+        let valid_samples = u64::from_str_radix(fields.next()?, 16).ok()?;
+        Some(Self {
+            encoder_delay,
+            padding,
+            valid_samples,
+        })
+    }
+
+    /// Returns the value of the `iTunSMPB` tag, as written by iTunes.
+    pub fn to_itunsmpb(&self) -> String {
+        format!(
+            " 00000000 {:08X} {:08X} {:016X} 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000",
+            self.encoder_delay, self.padding, self.valid_samples
+        )
+    }
+
+    /// Rewrites the edit list of the first track of a progressive MP4 file to present only the
+    /// valid samples, and the durations of the track and the movie accordingly.
+    pub fn write_edit_list(&self, mp4: &[u8]) -> Result<Vec<u8>> {
+        let moov = find_box(mp4, &[b"moov"])?
+            .ok_or_else(|| Error::Mp4("moov box not found".to_string()))?;
+        let children = moov.children()?;
+        let mvhd = children
+            .iter()
+            .find(|b| &b.name == b"mvhd")
+            .ok_or_else(|| Error::Mp4("mvhd box not found".to_string()))?;
+        let movie_timescale = read_u32(mvhd.payload(), timescale_offset(mvhd.payload()))? as u64;
+        let traks: Vec<&Mp4Box> = children.iter().filter(|b| &b.name == b"trak").collect();
+        let trak = traks
+            .first()
+            .ok_or_else(|| Error::Mp4("trak box not found".to_string()))?;
+        let mdhd = find_box(trak.payload(), &[b"mdia", b"mdhd"])?
+            .ok_or_else(|| Error::Mp4("mdhd box not found".to_string()))?;
+        let media_timescale = read_u32(mdhd.payload(), timescale_offset(mdhd.payload()))? as u64;
+        if media_timescale == 0 {
+            return Err(Error::Mp4("Invalid media timescale".to_string()));
+        }
+        let duration = self.valid_samples * movie_timescale / media_timescale;
+        // The other tracks keep their duration.
+        let mut movie_duration = duration;
+        for trak in &traks[1..] {
+            if let Some(tkhd) = trak.child(b"tkhd")? {
+                movie_duration = movie_duration.max(track_duration(&tkhd)?);
+            }
+        }
+
+        let mut payload = Vec::with_capacity(moov.data.len());
+        for child in &children {
+            match &child.name {
+                b"mvhd" => payload.extend(patch_duration(child, 16, 24, movie_duration)?),
+                b"trak" if child.offset == trak.offset => {
+                    let mut trak_payload = Vec::with_capacity(child.data.len());
+                    for trak_child in child.children()? {
+                        match &trak_child.name {
+                            b"tkhd" => {
+                                trak_payload.extend(patch_duration(&trak_child, 20, 28, duration)?);
+                                trak_payload
+                                    .extend(build_edts(duration, self.encoder_delay as i64));
+                            }
+                            b"edts" => {}
+                            _ => trak_payload.extend(trak_child.data),
+                        }
+                    }
+                    payload.extend(mp4_box(b"trak", &trak_payload));
+                }
+                _ => payload.extend(child.data),
+            }
+        }
+        replace_moov(mp4, &payload)
+    }
+}
+
+/// Returns the sample durations of the `stts` box of a sample table, as (count, delta) pairs.
+fn sample_durations(stbl: &Mp4Box) -> Result<Vec<(u32, u32)>> {
+    let Some(stts) = stbl.child(b"stts")? else {
+        return Ok(Vec::new());
+    };
+    let stts = stts.payload();
+    (0..read_u32(stts, 4)? as usize)
+        .map(|i| Ok((read_u32(stts, 8 + i * 8)?, read_u32(stts, 12 + i * 8)?)))
+        .collect()
+}
+
+/// Returns the duration of a `tkhd` box, in the movie timescale.
+fn track_duration(tkhd: &Mp4Box) -> Result<u64> {
+    let payload = tkhd.payload();
+    if payload.first() == Some(&1) {
+        read_u64(payload, 28)
+    } else {
+        Ok(read_u32(payload, 20)? as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GaplessInfo;
+    use crate::mp4::{find_box, mp4_box, mp4_full_box};
+    use crate::remux::first_edit;
+
+    /// A progressive MP4 file with 10 AAC frames of 1024 samples at 44.1 kHz.
+    /// The sample table holds the `sgpd` box, when given.
+    fn mp4(edts: Option<Vec<u8>>, sgpd: Option<Vec<u8>>) -> Vec<u8> {
+        let mvhd = mp4_full_box(
+            b"mvhd",
+            0,
+            0,
+            &[
+                &[0; 8][..],
+                &44100u32.to_be_bytes(),
+                &10240u32.to_be_bytes(),
+                &[0; 80],
+            ]
+            .concat(),
+        );
+        let tkhd = mp4_full_box(
+            b"tkhd",
+            0,
+            7,
+            &[
+                &[0; 8][..],
+                &1u32.to_be_bytes(),
+                &[0; 4],
+                &10240u32.to_be_bytes(),
+                &[0; 60],
+            ]
+            .concat(),
+        );
+        let mdhd = mp4_full_box(
+            b"mdhd",
+            0,
+            0,
+            &[
+                &[0; 8][..],
+                &44100u32.to_be_bytes(),
+                &10240u32.to_be_bytes(),
+                &[0; 4],
+            ]
+            .concat(),
+        );
+        let stts = mp4_full_box(
+            b"stts",
+            0,
+            0,
+            &[1u32, 10, 1024].map(u32::to_be_bytes).concat(),
+        );
+        let stco = mp4_full_box(b"stco", 0, 0, &[1u32, 0].map(u32::to_be_bytes).concat());
+        let stbl = mp4_box(b"stbl", &[stts, stco, sgpd.unwrap_or_default()].concat());
+        let mdia = mp4_box(b"mdia", &[mdhd, mp4_box(b"minf", &stbl)].concat());
+        let trak = mp4_box(b"trak", &[tkhd, edts.unwrap_or_default(), mdia].concat());
+        mp4_box(b"moov", &[mvhd, trak].concat())
+    }
+
+    fn edts(segment_duration: u32, media_time: u32) -> Vec<u8> {
+        let elst = [1, segment_duration, media_time, 0x10000]
+            .map(u32::to_be_bytes)
+            .concat();
+        mp4_box(b"edts", &mp4_full_box(b"elst", 0, 0, &elst))
+    }
+
+    #[test]
+    fn test_gapless_info() {
+        let info = GaplessInfo::from_mp4(&mp4(Some(edts(7000, 2112)), None), None).unwrap();
+        assert_eq!(
+            info,
+            GaplessInfo {
+                encoder_delay: 2112,
+                padding: 1128,
+                valid_samples: 7000
+            }
+        );
+        assert_eq!(
+            info.to_itunsmpb(),
+            " 00000000 00000840 00000468 0000000000001B58 00000000 00000000 00000000 00000000 00000000 00000000 00000000 00000000"
+        );
+        assert_eq!(GaplessInfo::from_itunsmpb(&info.to_itunsmpb()), Some(info));
+
+        // The edit list of a fragmented stream has no duration; it comes from the asset metadata.
+        let info = GaplessInfo::from_mp4(&mp4(Some(edts(0, 2112)), None), Some(100)).unwrap();
+        assert_eq!(info.valid_samples, 4410);
+        assert_eq!(info.padding, 10240 - 2112 - 4410);
+
+        let output = info.write_edit_list(&mp4(None, None)).unwrap();
+        let elst = find_box(&output, &[b"moov", b"trak", b"edts", b"elst"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(first_edit(elst.payload()).unwrap(), (4410, 2112));
+        assert_eq!(GaplessInfo::from_mp4(&output, None).unwrap(), info);
+        let mvhd = find_box(&output, &[b"moov", b"mvhd"]).unwrap().unwrap();
+        assert_eq!(mvhd.payload()[16..20], 4410u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_roll_is_not_encoder_delay() {
+        // An AAC `roll` sample group with a pre-roll of 1 frame, without an edit list.
+        let sgpd = mp4_full_box(
+            b"sgpd",
+            1,
+            0,
+            &[
+                &b"roll"[..],
+                &2u32.to_be_bytes(),
+                &1u32.to_be_bytes(),
+                &(-1i16).to_be_bytes(),
+            ]
+            .concat(),
+        );
+        let info = GaplessInfo::from_mp4(&mp4(None, Some(sgpd)), None).unwrap();
+        assert_eq!(
+            info,
+            GaplessInfo {
+                encoder_delay: 0,
+                padding: 0,
+                valid_samples: 10240
+            }
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! the HLS playlist and segment download functions.
+
+use crate::error::{Error, Result};
+use crate::mp4::find_box;
+use m3u8_rs::{AlternativeMediaType, ByteRange, MasterPlaylist, MediaPlaylist, Playlist};
+
+/// The downloaded segments of an HLS media playlist.
+#[derive(Debug, Clone, Default)]
+pub struct Segments {
+    /// The initialization segment (`EXT-X-MAP`), if any.
+    pub init: Option<Vec<u8>>,
+    /// The media segments, in playlist order.
+    pub segments: Vec<Vec<u8>>,
+}
+
+impl Segments {
+    /// Concatenates the initialization segment and the media segments,
+    /// e.g. into a fragmented MP4 file.
+    pub fn concat(&self) -> Vec<u8> {
+        let len = self.init.as_ref().map_or(0, Vec::len)
+            + self.segments.iter().map(Vec::len).sum::<usize>();
+        let mut data = Vec::with_capacity(len);
+        if let Some(init) = &self.init {
+            data.extend(init);
+        }
+        for segment in &self.segments {
+            data.extend(segment);
+        }
+        data
+    }
+
+    /// Whether the segments are fragmented MP4, with a `moov` box in the initialization segment
+    /// or the first media segment, rather than e.g. MPEG-TS.
+    pub fn is_fragmented_mp4(&self) -> bool {
+        self.init
+            .as_ref()
+            .or(self.segments.first())
+            .is_some_and(|data| find_box(data, &[b"moov"]).is_ok_and(|moov| moov.is_some()))
+    }
+}
+
+/// Resolves a URI of a playlist against the URL of the playlist.
+pub fn resolve_uri(base_url: &str, uri: &str) -> String {
+    if uri.contains("://") {
+        return uri.to_string();
+    }
+    if uri.starts_with('/') {
+        if let Some(scheme_end) = base_url.find("://") {
+            let host_end = base_url[scheme_end + 3..]
+                .find('/')
+                .map_or(base_url.len(), |index| scheme_end + 3 + index);
+            return format!("{}{uri}", &base_url[..host_end]);
+        }
+    }
+    let base_url = base_url.split(['?', '#']).next().unwrap_or(base_url);
+    match base_url.rfind('/') {
+        Some(index) => format!("{}{uri}", &base_url[..index + 1]),
+        None => uri.to_string(),
+    }
+}
+
+/// Fetches and parses a playlist.
+pub async fn get_playlist(client: &reqwest::Client, url: &str) -> Result<Playlist> {
+    let m3u8 = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    m3u8_rs::parse_playlist_res(&m3u8).map_err(|e| Error::Other(e.to_string()))
+}
+
+/// Returns the variant with the highest bandwidth of a master playlist.
+pub fn highest_bandwidth_variant(master: &MasterPlaylist) -> Option<&m3u8_rs::VariantStream> {
+    master
+        .variants
+        .iter()
+        .filter(|variant| !variant.is_i_frame)
+        .max_by_key(|variant| variant.bandwidth)
+}
+
+/// Fetches a media playlist. A master playlist is resolved to its variant with the highest bandwidth.
+/// Returns the URL of the media playlist, used to resolve its segment URIs, and the playlist.
+pub async fn get_media_playlist(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(String, MediaPlaylist)> {
+    match get_playlist(client, url).await? {
+        Playlist::MediaPlaylist(media) => Ok((url.to_string(), media)),
+        Playlist::MasterPlaylist(master) => {
+            let variant = highest_bandwidth_variant(&master).ok_or_else(|| {
+                Error::Other("No variant found in the master playlist".to_string())
+            })?;
+            let media_url = resolve_uri(url, &variant.uri);
+            Ok((
+                media_url.clone(),
+                get_variant_playlist(client, &media_url).await?,
+            ))
+        }
+    }
+}
+
+/// Fetches the media playlist of a variant or a rendition of a master playlist.
+async fn get_variant_playlist(client: &reqwest::Client, url: &str) -> Result<MediaPlaylist> {
+    match get_playlist(client, url).await? {
+        Playlist::MediaPlaylist(media) => Ok(media),
+        Playlist::MasterPlaylist(_) => Err(Error::Other(
+            "The variant of the master playlist is not a media playlist".to_string(),
+        )),
+    }
+}
+
+/// Downloads the initialization and media segments of a media playlist.
+/// Byte ranges without an offset continue from the end of the previous range of the same URI.
+pub async fn download_segments(
+    client: &reqwest::Client,
+    playlist_url: &str,
+    playlist: &MediaPlaylist,
+) -> Result<Segments> {
+    let mut segments = Segments::default();
+    let mut last_range: Option<(String, u64)> = None;
+    for segment in &playlist.segments {
+        if segments.init.is_none() {
+            if let Some(map) = &segment.map {
+                let url = resolve_uri(playlist_url, &map.uri);
+                let range = map
+                    .byte_range
+                    .as_ref()
+                    .map(|range| (range.offset.unwrap_or(0), range.length));
+                segments.init = Some(download(client, &url, range).await?);
+            }
+        }
+        let url = resolve_uri(playlist_url, &segment.uri);
+        let range = segment.byte_range.as_ref().map(|range| {
+            let offset = byte_range_offset(range, &url, &last_range);
+            (offset, range.length)
+        });
+        if let Some((offset, length)) = range {
+            last_range = Some((url.clone(), offset + length));
+        }
+        segments.segments.push(download(client, &url, range).await?);
+    }
+    Ok(segments)
+}
+
+/// Downloads an HLS stream: the media playlist (or the variant with the highest bandwidth of
+/// a master playlist) and all its segments.
+pub async fn download_stream(client: &reqwest::Client, url: &str) -> Result<Segments> {
+    let (media_url, media) = get_media_playlist(client, url).await?;
+    download_segments(client, &media_url, &media).await
+}
+
+/// Downloads an HLS stream like `download_stream`, with the segments of the audio rendition
+/// (`EXT-X-MEDIA`) of the variant, e.g. of a music video whose audio is not muxed into the video.
+/// The audio is `None` when the variant has no audio rendition with a URI.
+pub async fn download_stream_renditions(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(Segments, Option<Segments>)> {
+    let master = match get_playlist(client, url).await? {
+        Playlist::MediaPlaylist(media) => {
+            return Ok((download_segments(client, url, &media).await?, None))
+        }
+        Playlist::MasterPlaylist(master) => master,
+    };
+    let variant = highest_bandwidth_variant(&master)
+        .ok_or_else(|| Error::Other("No variant found in the master playlist".to_string()))?;
+    let media_url = resolve_uri(url, &variant.uri);
+    let media = get_variant_playlist(client, &media_url).await?;
+    let segments = download_segments(client, &media_url, &media).await?;
+    // The default rendition of the audio group of the variant, else its first one.
+    let rendition = master
+        .alternatives
+        .iter()
+        .filter(|media| {
+            media.media_type == AlternativeMediaType::Audio
+                && Some(&media.group_id) == variant.audio.as_ref()
+                && media.uri.is_some()
+        })
+        .min_by_key(|media| !media.default);
+    let audio = match rendition.and_then(|media| media.uri.as_ref()) {
+        Some(uri) => {
+            let audio_url = resolve_uri(url, uri);
+            let audio = get_variant_playlist(client, &audio_url).await?;
+            Some(download_segments(client, &audio_url, &audio).await?)
+        }
+        None => None,
+    };
+    Ok((segments, audio))
+}
+
+pub(crate) fn byte_range_offset(
+    range: &ByteRange,
+    url: &str,
+    last_range: &Option<(String, u64)>,
+) -> u64 {
+    match (range.offset, last_range) {
+        (Some(offset), _) => offset,
+        (None, Some((last_url, end))) if last_url == url => *end,
+        (None, _) => 0,
+    }
+}
+
+/// Downloads a file, or the (offset, length) byte range of it.
+pub(crate) async fn download(
+    client: &reqwest::Client,
+    url: &str,
+    range: Option<(u64, u64)>,
+) -> Result<Vec<u8>> {
+    let mut request = client.get(url);
+    if let Some((offset, length)) = range {
+        request = request.header(
+            reqwest::header::RANGE,
+            format!("bytes={}-{}", offset, offset + length - 1),
+        );
+    }
+    let data = request.send().await?.error_for_status()?.bytes().await?;
+    Ok(data.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_uri;
+    use crate::mock_server::{MockResponse, MockServer};
+
+    #[test]
+    fn test_resolve_uri() {
+        let base = "https://mvod.itunes.apple.com/itunes-assets/a/b/P.m3u8?token=1";
+        assert_eq!(
+            resolve_uri(base, "P_1.m3u8"),
+            "https://mvod.itunes.apple.com/itunes-assets/a/b/P_1.m3u8"
+        );
+        assert_eq!(
+            resolve_uri(base, "/c/d.mp4"),
+            "https://mvod.itunes.apple.com/c/d.mp4"
+        );
+        assert_eq!(
+            resolve_uri(base, "https://example.com/e.mp4"),
+            "https://example.com/e.mp4"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_stream() {
+        let master = "#EXTM3U\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=100000,CODECS=\"avc1.64001f\",RESOLUTION=640x640\n\
+            low.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=900000,CODECS=\"avc1.640028\",RESOLUTION=2048x2048\n\
+            high.m3u8\n";
+        let media = "#EXTM3U\n\
+            #EXT-X-VERSION:7\n\
+            #EXT-X-TARGETDURATION:6\n\
+            #EXT-X-PLAYLIST-TYPE:VOD\n\
+            #EXT-X-MAP:URI=\"video.mp4\",BYTERANGE=\"4@0\"\n\
+            #EXTINF:6.0,\n\
+            #EXT-X-BYTERANGE:3@4\n\
+            video.mp4\n\
+            #EXTINF:6.0,\n\
+            #EXT-X-BYTERANGE:2\n\
+            video.mp4\n\
+            #EXT-X-ENDLIST\n";
+        let response = |body: &[u8]| MockResponse {
+            status: 200,
+            headers: vec![],
+            body: body.to_vec(),
+        };
+        let server = MockServer::start(vec![
+            response(master.as_bytes()),
+            response(media.as_bytes()),
+            response(b"init"),
+            response(b"moo"),
+            response(b"vs"),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        let segments = super::download_stream(&client, &format!("{}/P.m3u8", server.url))
+            .await
+            .unwrap();
+        assert_eq!(segments.concat(), b"initmoovs");
+
+        let requests = server.requests();
+        assert_eq!(requests[1].path, "/high.m3u8");
+        assert_eq!(requests[2].header("range"), Some("bytes=0-3"));
+        assert_eq!(requests[3].header("range"), Some("bytes=4-6"));
+        assert_eq!(requests[4].header("range"), Some("bytes=7-8"));
+    }
+}
@@ -0,0 +1,302 @@
+//! the integrity verification of downloaded assets.
+//!
+//! The assets of the WebPlayBack API carry the size and the MD5 checksum of their file. A download
+//! that does not match them is repaired by fetching again, by byte range, the chunks of the asset
+//! that are missing or differ.
+
+use crate::api::webplayback::Asset;
+use crate::error::{Error, Result};
+use crate::hls::download;
+use md5::{Digest, Md5};
+use std::path::Path;
+
+/// The expected size, checksum and chunk size of an asset file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Integrity {
+    /// The size of the file in bytes, 0 when unknown.
+    pub file_size: u64,
+    /// The hexadecimal MD5 checksum of the file, empty when unknown.
+    pub md5: String,
+    /// The size of the chunks re-fetched on a mismatch, 0 for the whole file.
+    pub chunk_size: u64,
+}
+
+/// The verification status of a downloaded file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationStatus {
+    /// The size and the checksum match.
+    Verified,
+    /// The size and the checksum match after fetching again the chunks of these indices.
+    Repaired { chunks: Vec<u64> },
+    /// The size of the file differs.
+    SizeMismatch { expected: u64, actual: u64 },
+    /// The MD5 checksum of the file differs.
+    ChecksumMismatch { expected: String, actual: String },
+    /// The asset has neither a size nor a checksum to verify.
+    Unverified,
+}
+
+impl VerificationStatus {
+    /// Whether the file was verified, possibly after a repair.
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified | Self::Repaired { .. })
+    }
+}
+
+/// A downloaded asset file and its verification status.
+#[derive(Debug, Clone)]
+pub struct AssetDownload {
+    /// The URL of the file.
+    pub url: String,
+    pub data: Vec<u8>,
+    pub status: VerificationStatus,
+}
+
+impl Integrity {
+    /// Returns the integrity of a WebPlayBack asset.
+    pub fn of(asset: &Asset) -> Self {
+        Self {
+            file_size: asset.file_size,
+            md5: asset.md5.to_lowercase(),
+            chunk_size: asset.chunks.chunk_size,
+        }
+    }
+
+    /// Verifies the size and the checksum of a file.
+    pub fn verify(&self, data: &[u8]) -> VerificationStatus {
+        if self.file_size == 0 && self.md5.is_empty() {
+            return VerificationStatus::Unverified;
+        }
+        if self.file_size != 0 && data.len() as u64 != self.file_size {
+            return VerificationStatus::SizeMismatch {
+                expected: self.file_size,
+                actual: data.len() as u64,
+            };
+        }
+        if !self.md5.is_empty() {
+            let actual = hex::encode(Md5::digest(data));
+            if !actual.eq_ignore_ascii_case(&self.md5) {
+                return VerificationStatus::ChecksumMismatch {
+                    expected: self.md5.clone(),
+                    actual,
+                };
+            }
+        }
+        VerificationStatus::Verified
+    }
+
+    /// Verifies an existing file, e.g. a file downloaded by an earlier run.
+    pub fn verify_file(&self, path: &Path) -> Result<VerificationStatus> {
+        Ok(self.verify(&std::fs::read(path)?))
+    }
+
+    /// Returns the (offset, length) byte ranges of the chunks of the file.
+    pub fn chunks(&self) -> Vec<(u64, u64)> {
+        let chunk_size = match self.chunk_size {
+            0 => self.file_size.max(1),
+            chunk_size => chunk_size,
+        };
+        (0..self.file_size)
+            .step_by(chunk_size as usize)
+            .map(|offset| (offset, chunk_size.min(self.file_size - offset)))
+            .collect()
+    }
+
+    /// Repairs a downloaded file by fetching again its chunks: first the chunks missing from a
+    /// truncated file, then, if the checksum still differs, every chunk, keeping those that differ.
+    /// Returns the verification status of the repaired file.
+    pub async fn repair(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        data: &mut Vec<u8>,
+    ) -> Result<VerificationStatus> {
+        let status = self.verify(data);
+        if status.is_verified() || status == VerificationStatus::Unverified || self.file_size == 0 {
+            return Ok(status);
+        }
+        data.truncate(self.file_size as usize);
+        let chunks = self.chunks();
+        let mut repaired = Vec::new();
+        let truncated: Vec<usize> = (0..chunks.len())
+            .filter(|&index| {
+                let (offset, length) = chunks[index];
+                (data.len() as u64) < offset + length
+            })
+            .collect();
+        for &index in &truncated {
+            self.fetch_chunk(client, url, data, chunks[index]).await?;
+            repaired.push(index as u64);
+        }
+        if !truncated.is_empty() && self.verify(data).is_verified() {
+            return Ok(VerificationStatus::Repaired { chunks: repaired });
+        }
+        for (index, &chunk) in chunks.iter().enumerate() {
+            if truncated.contains(&index) {
+                continue;
+            }
+            if self.fetch_chunk(client, url, data, chunk).await? {
+                repaired.push(index as u64);
+            }
+        }
+        repaired.sort_unstable();
+        Ok(match self.verify(data) {
+            VerificationStatus::Verified => VerificationStatus::Repaired { chunks: repaired },
+            status => status,
+        })
+    }
+
+    /// Fetches a chunk of the file into `data`. Returns whether the chunk differed.
+    async fn fetch_chunk(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        data: &mut Vec<u8>,
+        (offset, length): (u64, u64),
+    ) -> Result<bool> {
+        let chunk = download(client, url, Some((offset, length))).await?;
+        if chunk.len() as u64 != length {
+            return Err(Error::Other(format!(
+                "Chunk {offset}@{length} of {url} has {} bytes",
+                chunk.len()
+            )));
+        }
+        let (start, end) = (offset as usize, (offset + length) as usize);
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        let differs = data[start..end] != chunk[..];
+        data[start..end].copy_from_slice(&chunk);
+        Ok(differs)
+    }
+}
+
+/// Downloads an asset file and verifies it, repairing it by chunks on a mismatch.
+pub async fn download_asset(
+    client: &reqwest::Client,
+    url: &str,
+    integrity: &Integrity,
+) -> Result<AssetDownload> {
+    let mut data = download(client, url, None).await?;
+    let status = integrity.repair(client, url, &mut data).await?;
+    Ok(AssetDownload {
+        url: url.to_string(),
+        data,
+        status,
+    })
+}
+
+/// Re-checks the file of a WebPlayBack asset downloaded earlier against its size and checksum.
+pub fn verify_asset(asset: &Asset, path: &Path) -> Result<VerificationStatus> {
+    Integrity::of(asset).verify_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{download_asset, Integrity, VerificationStatus};
+    use crate::mock_server::{MockResponse, MockServer};
+    use md5::{Digest, Md5};
+
+    const FILE: &[u8] = b"0123456789";
+
+    fn integrity() -> Integrity {
+        Integrity {
+            file_size: FILE.len() as u64,
+            md5: hex::encode(Md5::digest(FILE)),
+            chunk_size: 4,
+        }
+    }
+
+    fn response(body: &[u8]) -> MockResponse {
+        MockResponse {
+            status: 200,
+            headers: vec![],
+            body: body.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let integrity = integrity();
+        assert_eq!(integrity.chunks(), [(0, 4), (4, 4), (8, 2)]);
+        assert_eq!(integrity.verify(FILE), VerificationStatus::Verified);
+        assert_eq!(
+            integrity.verify(b"012345"),
+            VerificationStatus::SizeMismatch {
+                expected: 10,
+                actual: 6
+            }
+        );
+        assert!(matches!(
+            integrity.verify(b"01234x6789"),
+            VerificationStatus::ChecksumMismatch { .. }
+        ));
+        let unknown = Integrity {
+            file_size: 0,
+            md5: String::new(),
+            chunk_size: 0,
+        };
+        assert_eq!(unknown.verify(b""), VerificationStatus::Unverified);
+
+        let path = std::env::temp_dir().join(format!("ramdl-verify-{}", std::process::id()));
+        std::fs::write(&path, FILE).unwrap();
+        assert!(integrity.verify_file(&path).unwrap().is_verified());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_download_asset() {
+        let client = reqwest::Client::new();
+        let integrity = integrity();
+
+        // A truncated download: the missing chunks are fetched again.
+        let server = MockServer::start(vec![
+            response(b"012345"),
+            response(b"4567"),
+            response(b"89"),
+        ])
+        .await;
+        let url = format!("{}/asset.mp4", server.url);
+        let download = download_asset(&client, &url, &integrity).await.unwrap();
+        assert_eq!(download.data, FILE);
+        assert_eq!(
+            download.status,
+            VerificationStatus::Repaired { chunks: vec![1, 2] }
+        );
+        let requests = server.requests();
+        assert_eq!(requests[0].header("range"), None);
+        assert_eq!(requests[1].header("range"), Some("bytes=4-7"));
+        assert_eq!(requests[2].header("range"), Some("bytes=8-9"));
+
+        // A corrupted download: every chunk is fetched again and the differing one replaced.
+        let server = MockServer::start(vec![
+            response(b"01x3456789"),
+            response(b"0123"),
+            response(b"4567"),
+            response(b"89"),
+        ])
+        .await;
+        let url = format!("{}/asset.mp4", server.url);
+        let download = download_asset(&client, &url, &integrity).await.unwrap();
+        assert_eq!(download.data, FILE);
+        assert_eq!(
+            download.status,
+            VerificationStatus::Repaired { chunks: vec![0] }
+        );
+
+        // A chunk that still differs is reported.
+        let server = MockServer::start(vec![
+            response(b"01x3456789"),
+            response(b"01x3"),
+            response(b"4567"),
+            response(b"89"),
+        ])
+        .await;
+        let url = format!("{}/asset.mp4", server.url);
+        let download = download_asset(&client, &url, &integrity).await.unwrap();
+        assert!(matches!(
+            download.status,
+            VerificationStatus::ChecksumMismatch { .. }
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+//! the content key stores, to skip the license requests of the keys already known.
+//!
+//! The keys are stored by key id (KID). They can be imported and exported as `kid:key` lists,
+//! one pair of hexadecimal strings per line, as accepted by `mp4decrypt --key`.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A store of content keys by key id.
+pub trait KeyStore: std::fmt::Debug + Send + Sync {
+    /// Returns the content key of a key id.
+    fn get(&self, kid: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Stores the content key of a key id, replacing the previous one.
+    fn insert(&self, kid: &[u8], key: &[u8]) -> Result<()>;
+
+    /// Returns all the (key id, content key) pairs of the store.
+    fn entries(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    /// Imports a `kid:key` list. Empty lines and lines starting with `#` are skipped.
+    /// Returns the number of keys imported.
+    fn import(&self, list: &str) -> Result<usize> {
+        let keys = parse_key_list(list)?;
+        for (kid, key) in &keys {
+            self.insert(kid, key)?;
+        }
+        Ok(keys.len())
+    }
+
+    /// Exports the keys of the store as a `kid:key` list.
+    fn export(&self) -> Result<String> {
+        Ok(self
+            .entries()?
+            .iter()
+            .map(|(kid, key)| format!("{}:{}\n", hex::encode(kid), hex::encode(key)))
+            .collect())
+    }
+}
+
+/// Parses a `kid:key` list into (key id, content key) pairs.
+pub fn parse_key_list(list: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (kid, key) = line
+                .split_once(':')
+                .ok_or_else(|| Error::Decrypt(format!("Invalid kid:key pair {line}")))?;
+            let decode = |value: &str| {
+                hex::decode(value.trim().replace('-', ""))
+                    .map_err(|e| Error::Decrypt(e.to_string()))
+            };
+            Ok((decode(kid)?, decode(key)?))
+        })
+        .collect()
+}
+
+/// A key store in memory, the default key store, shared by the clones of a downloader.
+#[derive(Debug, Default)]
+pub struct MemoryKeyStore {
+    keys: Mutex<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn get(&self, kid: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.keys.lock().unwrap().get(kid).cloned())
+    }
+
+    fn insert(&self, kid: &[u8], key: &[u8]) -> Result<()> {
+        self.keys.lock().unwrap().insert(kid.to_vec(), key.to_vec());
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        Ok(self
+            .keys
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(kid, key)| (kid.clone(), key.clone()))
+            .collect())
+    }
+}
+
+/// A key store in a JSON file, an object of hexadecimal key ids to hexadecimal content keys.
+/// The file is read when the store is opened and written on every new key.
+#[derive(Debug)]
+pub struct JsonKeyStore {
+    path: PathBuf,
+    keys: MemoryKeyStore,
+}
+
+impl JsonKeyStore {
+    /// Opens the key store of a file, created on the first new key if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let keys = MemoryKeyStore::new();
+        if path.exists() {
+            let entries: BTreeMap<String, String> = serde_json::from_slice(&std::fs::read(&path)?)?;
+            for (kid, key) in entries {
+                let decode =
+                    |value: &str| hex::decode(value).map_err(|e| Error::Decrypt(e.to_string()));
+                keys.insert(&decode(&kid)?, &decode(&key)?)?;
+            }
+        }
+        Ok(Self { path, keys })
+    }
+
+    fn save(&self) -> Result<()> {
+        let entries: BTreeMap<String, String> = self
+            .keys
+            .entries()?
+            .iter()
+            .map(|(kid, key)| (hex::encode(kid), hex::encode(key)))
+            .collect();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_vec_pretty(&entries)?)?;
+        Ok(())
+    }
+}
+
+impl KeyStore for JsonKeyStore {
+    fn get(&self, kid: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.keys.get(kid)
+    }
+
+    fn insert(&self, kid: &[u8], key: &[u8]) -> Result<()> {
+        if self.keys.get(kid)?.as_deref() == Some(key) {
+            return Ok(());
+        }
+        self.keys.insert(kid, key)?;
+        self.save()
+    }
+
+    fn entries(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.keys.entries()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonKeyStore, KeyStore, MemoryKeyStore};
+
+    const LIST: &str = "# song\n\
+        eb676abbcb345e96bbcf616630f1a3da:100b6c20940f779a4589152b57d2dacb\n\
+        \n\
+        0102030405060708090a0b0c0d0e0f10:2b7e151628aed2a6abf7158809cf4f3c\n";
+
+    #[test]
+    fn test_key_stores() {
+        let store = MemoryKeyStore::new();
+        assert_eq!(store.import(LIST).unwrap(), 2);
+        assert_eq!(
+            store
+                .get(&hex::decode("eb676abbcb345e96bbcf616630f1a3da").unwrap())
+                .unwrap(),
+            Some(hex::decode("100b6c20940f779a4589152b57d2dacb").unwrap())
+        );
+        assert_eq!(store.get(&[0; 16]).unwrap(), None);
+        assert!(store.import("not a key").is_err());
+
+        let path = std::env::temp_dir().join(format!("ramdl-keys-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let json_store = JsonKeyStore::open(&path).unwrap();
+        json_store.import(&store.export().unwrap()).unwrap();
+        let reopened = JsonKeyStore::open(&path).unwrap();
+        assert_eq!(reopened.entries().unwrap(), store.entries().unwrap());
+        assert_eq!(
+            reopened.export().unwrap(),
+            "0102030405060708090a0b0c0d0e0f10:2b7e151628aed2a6abf7158809cf4f3c\n\
+            eb676abbcb345e96bbcf616630f1a3da:100b6c20940f779a4589152b57d2dacb\n"
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
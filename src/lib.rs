@@ -1,22 +1,71 @@
 //! Rust Apple Music Downloader.
 
+pub mod alac;
 pub mod api;
+pub mod cenc;
+pub mod classical;
 pub mod decrypter;
+pub mod ec3;
 pub mod error;
+pub mod flac;
+pub mod gapless;
+pub mod hls;
+pub mod integrity;
+pub mod key_store;
+pub mod loudness;
+pub mod master_playlist;
+#[cfg(test)]
+mod mock_server;
+pub mod mp4;
+pub mod naming;
+pub mod offline;
+pub mod remux;
 pub mod stream_info;
+pub mod tags;
 
 use crate::api::*;
 use crate::error::Error;
 use crate::error::Result;
+use albums::Albums;
+use artists::Artists;
+use artwork::{Artwork, ArtworkFormat};
 use base64::Engine;
+use charts::{ChartType, Charts};
+use content::{Content, Track};
+use credits::Credits;
+use curators::{AppleCurators, Curators};
+use editorial_video::{EditorialVideo, MotionArtworkVariant};
 use fancy_regex::Regex;
+use genres::Genre;
+use key_store::{KeyStore, MemoryKeyStore};
 use library_albums::LibraryAlbums;
+use library_artists::LibraryArtists;
+use library_music_video::LibraryMusicVideos;
+use library_playlists::{
+    CreationAttributes, CreationRelationships, LibraryPlaylistCreationRequest,
+    LibraryPlaylistTracksRequest, LibraryPlaylists,
+};
+use library_search::{LibrarySearchResults, LibrarySearchType};
+use library_songs::LibrarySongs;
 use serde_json::json;
 
 use lyrics::Lyrics;
-use search;
+use music_summaries::MusicSummaries;
+use music_videos::MusicVideos;
+use playlists::Playlists;
+use previews::{Preview, PreviewSource};
+use query::{Extend, Include, Query};
+use radio_shows::RadioShows;
+use recommendations::PersonalRecommendation;
+use record_labels::{RecordLabelView, RecordLabels};
+use resource::{Relationship, ResourceIdentifier};
 use songs::Songs;
-use stream_info::StreamInfo;
+use stations::Stations;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use stream_info::{Codec, StreamInfo};
+use tags::Tags;
 
 /// <https://beta.music.apple.com>
 pub const APPLE_MUSIC_HOMEPAGE_URL: &str = "https://beta.music.apple.com";
@@ -28,6 +77,11 @@ pub const WEBPLAYBACK_API_URL: &str =
 /// <https://play.itunes.apple.com/WebObjects/MZPlay.woa/wa/acquireWebPlaybackLicense>
 pub const LICENSE_API_URL: &str =
     "https://play.itunes.apple.com/WebObjects/MZPlay.woa/wa/acquireWebPlaybackLicense";
+/// The number of downloaded artworks kept by `AppleMusicDownloader::get_artwork`.
+const ARTWORK_CACHE_SIZE: usize = 8;
+
+/// The downloaded artworks by URL, the oldest first.
+type ArtworkCache = VecDeque<(String, Vec<u8>)>;
 
 /// The Apple Music downloader struct.
 #[derive(Debug, Clone)]
@@ -39,6 +93,12 @@ pub struct AppleMusicDownloader {
     headers: reqwest::header::HeaderMap,
     client: reqwest::Client,
     device: widevine::Device,
+    amp_api_url: String,
+    artwork_cache: Arc<Mutex<ArtworkCache>>,
+    key_store: Arc<dyn KeyStore>,
+    privacy_mode: bool,
+    service_certificate_url: Option<String>,
+    service_certificate: Arc<Mutex<Option<Vec<u8>>>>,
 }
 
 impl Default for AppleMusicDownloader {
@@ -52,6 +112,12 @@ impl Default for AppleMusicDownloader {
             headers: reqwest::header::HeaderMap::new(),
             client: reqwest::Client::new(),
             device,
+            amp_api_url: AMP_API_URL.to_string(),
+            artwork_cache: Arc::new(Mutex::new(VecDeque::new())),
+            key_store: Arc::new(MemoryKeyStore::new()),
+            privacy_mode: false,
+            service_certificate_url: None,
+            service_certificate: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -175,7 +241,7 @@ impl AppleMusicDownloader {
     async fn init_storefront_language(&mut self) -> Result<()> {
         let res = self
             .client
-            .get(format!("{AMP_API_URL}/v1/me/storefront"))
+            .get(format!("{}/v1/me/storefront", self.amp_api_url))
             .send()
             .await?
             .json::<serde_json::Value>()
@@ -196,13 +262,71 @@ impl AppleMusicDownloader {
         Ok(())
     }
 
-    /// Gets the song information.
+    /// Overrides the Apple Music API URL, e.g. to send requests through a proxy.
+    pub fn set_amp_api_url(&mut self, amp_api_url: &str) {
+        self.amp_api_url = amp_api_url.trim_end_matches('/').to_string();
+    }
+
+    /// Sets the storefront used by catalog requests, e.g. to compare charts across countries.
+    pub fn set_store_front(&mut self, store_front: &str) {
+        self.store_front = store_front.to_string();
+    }
+
+    /// Sets the store of the content keys, consulted before requesting a license,
+    /// e.g. a `JsonKeyStore` to keep the keys across runs. Defaults to a `MemoryKeyStore`.
+    pub fn set_key_store(&mut self, key_store: impl KeyStore + 'static) {
+        self.key_store = Arc::new(key_store);
+    }
+
+    /// Returns the store of the content keys.
+    pub fn key_store(&self) -> &dyn KeyStore {
+        self.key_store.as_ref()
+    }
+
+    /// Enables or disables the privacy mode of license requests, where the client id of the CDM
+    /// is encrypted with the service certificate. Disabled by default.
+    pub fn set_privacy_mode(&mut self, privacy_mode: bool) {
+        self.privacy_mode = privacy_mode;
+    }
+
+    /// Returns whether license requests use the privacy mode.
+    pub fn privacy_mode(&self) -> bool {
+        self.privacy_mode
+    }
+
+    /// Sets the URL of the Widevine service certificate, fetched on the first license request in
+    /// privacy mode. Defaults to the `widevine_cert_url` of the WebPlayBack song of the request.
+    pub fn set_service_certificate_url(&mut self, service_certificate_url: &str) {
+        self.service_certificate_url = Some(service_certificate_url.to_string());
+        *self.service_certificate.lock().unwrap() = None;
+    }
+
+    /// Sets the Widevine service certificate, a signed DRM certificate or a signed message holding one.
+    /// Like the fetched certificate, it is shared with the clones of the downloader.
+    pub fn set_service_certificate(&mut self, certificate: &[u8]) -> Result<()> {
+        widevine::ServiceCertificate::try_from(certificate)
+            .map_err(|e| Error::ServiceCertificate(e.to_string()))?;
+        *self.service_certificate.lock().unwrap() = Some(certificate.to_vec());
+        Ok(())
+    }
+
+    /// Gets the song information, including its albums and extended asset URLs.
     pub async fn get_songs(&self, song_id: &str) -> Result<Songs> {
+        let query = Query::new()
+            .include(Include::Albums)
+            .extend(Extend::ExtendedAssetUrls);
+        self.get_song(song_id, &query).await
+    }
+
+    /// Gets the song information with the requested relationships and extended attributes.
+    pub async fn get_song(&self, song_id: &str, query: &Query) -> Result<Songs> {
         let store_front = self.store_front.clone();
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/catalog/{store_front}/songs/{song_id}?include=albums&extend=extendedAssetUrls",
+                "{}/v1/catalog/{store_front}/songs/{song_id}?{}",
+                self.amp_api_url,
+                query.to_query_string()
             ))
             .send()
             .await?
@@ -212,13 +336,71 @@ impl AppleMusicDownloader {
         Ok(song)
     }
 
+    /// Gets the credits of a song, grouped in sections such as Performers or Production & Engineering.
+    /// Use `Credits::people` for the people and their roles, and `Tags::add_credits` to tag them.
+    pub async fn get_song_credits(&self, song_id: &str) -> Result<Vec<Credits>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/songs/{song_id}/credits",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let credits: Vec<Credits> = serde_json::from_value(res["data"].clone())?;
+        Ok(credits)
+    }
+
+    /// Gets the album information with the requested relationships, extended attributes and views.
+    pub async fn get_album(&self, album_id: &str, query: &Query) -> Result<Albums> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/albums/{album_id}?{}",
+                self.amp_api_url,
+                query.to_query_string()
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let album: Albums = serde_json::from_value(res["data"][0].clone())?;
+        Ok(album)
+    }
+
+    /// Gets the music video information with the requested relationships, extended attributes and views.
+    pub async fn get_music_video(
+        &self,
+        music_video_id: &str,
+        query: &Query,
+    ) -> Result<MusicVideos> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/music-videos/{music_video_id}?{}",
+                self.amp_api_url,
+                query.to_query_string()
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let music_video: MusicVideos = serde_json::from_value(res["data"][0].clone())?;
+        Ok(music_video)
+    }
+
     /// Gets the lyrics information.
     pub async fn get_lyrics(&self, song_id: &str) -> Result<Vec<Option<Lyrics>>> {
         let store_front = self.store_front.clone();
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/catalog/{store_front}/songs/{song_id}?include=lyrics,syllable-lyrics&extend=extendedAssetUrls",
+                "{}/v1/catalog/{store_front}/songs/{song_id}?include=lyrics,syllable-lyrics&extend=extendedAssetUrls", self.amp_api_url,
             ))
             .send()
             .await?
@@ -237,19 +419,17 @@ impl AppleMusicDownloader {
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/catalog/{store_front}/search?term={query}&types=songs,albums,artists,playlists&limit=25&offset=0",
+                "{}/v1/catalog/{store_front}/search?term={query}&types=songs,albums,artists,playlists&limit=25&offset=0", self.amp_api_url,
             ))
             .send()
             .await?
             .json::<serde_json::Value>()
             .await?;
-        let songs: Vec<search::Song> =
-            serde_json::from_value(res["results"]["songs"]["data"].clone())?;
-        let albums: Vec<search::Album> =
-            serde_json::from_value(res["results"]["albums"]["data"].clone())?;
-        let artists: Vec<search::Artist> =
+        let songs: Vec<Songs> = serde_json::from_value(res["results"]["songs"]["data"].clone())?;
+        let albums: Vec<Albums> = serde_json::from_value(res["results"]["albums"]["data"].clone())?;
+        let artists: Vec<Artists> =
             serde_json::from_value(res["results"]["artists"]["data"].clone())?;
-        let playlists: Vec<search::Playlist> =
+        let playlists: Vec<Playlists> =
             serde_json::from_value(res["results"]["playlists"]["data"].clone())?;
         Ok(search::SearchResults {
             songs,
@@ -259,49 +439,50 @@ impl AppleMusicDownloader {
         })
     }
 
-    pub async fn search_songs(&self, query: &str) -> Result<Vec<search::Song>> {
+    pub async fn search_songs(&self, query: &str) -> Result<Vec<Songs>> {
         let store_front = self.store_front.clone();
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/catalog/{store_front}/search?term={query}&types=songs&limit=25&offset=0",
+                "{}/v1/catalog/{store_front}/search?term={query}&types=songs&limit=25&offset=0",
+                self.amp_api_url,
             ))
             .send()
             .await?
             .json::<serde_json::Value>()
             .await?;
-        let songs: Vec<search::Song> =
-            serde_json::from_value(res["results"]["songs"]["data"].clone())?;
+        let songs: Vec<Songs> = serde_json::from_value(res["results"]["songs"]["data"].clone())?;
         Ok(songs)
     }
-    pub async fn search_ablums(&self, query: &str) -> Result<Vec<search::Album>> {
+    pub async fn search_ablums(&self, query: &str) -> Result<Vec<Albums>> {
         let store_front = self.store_front.clone();
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/catalog/{store_front}/search?term={query}&types=albums&limit=25&offset=0",
+                "{}/v1/catalog/{store_front}/search?term={query}&types=albums&limit=25&offset=0",
+                self.amp_api_url,
             ))
             .send()
             .await?
             .json::<serde_json::Value>()
             .await?;
-        let albums: Vec<search::Album> =
-            serde_json::from_value(res["results"]["albums"]["data"].clone())?;
+        let albums: Vec<Albums> = serde_json::from_value(res["results"]["albums"]["data"].clone())?;
         Ok(albums)
     }
 
-    pub async fn search_artists(&self, query: &str) -> Result<Vec<search::Artist>> {
+    pub async fn search_artists(&self, query: &str) -> Result<Vec<Artists>> {
         let store_front = self.store_front.clone();
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/catalog/{store_front}/search?term={query}&types=artists&limit=25&offset=0",
+                "{}/v1/catalog/{store_front}/search?term={query}&types=artists&limit=25&offset=0",
+                self.amp_api_url,
             ))
             .send()
             .await?
             .json::<serde_json::Value>()
             .await?;
-        let arists: Vec<search::Artist> =
+        let arists: Vec<Artists> =
             serde_json::from_value(res["results"]["artists"]["data"].clone())?;
         Ok(arists)
     }
@@ -314,7 +495,8 @@ impl AppleMusicDownloader {
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/me/library/albums?offset={}&limit={}",
+                "{}/v1/me/library/albums?offset={}&limit={}",
+                self.amp_api_url,
                 offset.unwrap_or(0),
                 limit.unwrap_or(25)
             ))
@@ -328,7 +510,7 @@ impl AppleMusicDownloader {
     pub async fn get_library_album(&self, id: String) -> Result<LibraryAlbums> {
         let res = self
             .client
-            .get(format!("{AMP_API_URL}/v1/me/library/albums/{id}"))
+            .get(format!("{}/v1/me/library/albums/{id}", self.amp_api_url))
             .send()
             .await?
             .json::<serde_json::Value>()
@@ -341,7 +523,8 @@ impl AppleMusicDownloader {
         let res = self
             .client
             .get(format!(
-                "{AMP_API_URL}/v1/me/library/search?types=library-albums&term={}",
+                "{}/v1/me/library/search?types=library-albums&term={}",
+                self.amp_api_url,
                 term.replace(" ", "+")
             ))
             .send()
@@ -353,6 +536,769 @@ impl AppleMusicDownloader {
         Ok(albums)
     }
 
+    /// Searches the user's library for the given resource types.
+    /// `offset` and `limit` (at most 25) paginate every requested type at once.
+    pub async fn search_library(
+        &self,
+        term: &str,
+        types: &[LibrarySearchType],
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<LibrarySearchResults> {
+        let types = types
+            .iter()
+            .map(|t| t.as_str())
+            .collect::<Vec<&str>>()
+            .join(",");
+        let res = self
+            .client
+            .get(format!("{}/v1/me/library/search", self.amp_api_url))
+            .query(&[
+                ("term", term),
+                ("types", &types),
+                ("offset", &offset.unwrap_or(0).to_string()),
+                ("limit", &limit.unwrap_or(25).to_string()),
+            ])
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let results: Option<LibrarySearchResults> = serde_json::from_value(res["results"].clone())?;
+        Ok(results.unwrap_or_default())
+    }
+
+    /// Searches the user's library for songs.
+    pub async fn search_library_songs(
+        &self,
+        term: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LibrarySongs>> {
+        let results = self
+            .search_library(term, &[LibrarySearchType::Songs], offset, limit)
+            .await?;
+        Ok(results.songs.map(|songs| songs.data).unwrap_or_default())
+    }
+
+    /// Searches the user's library for playlists.
+    pub async fn search_library_playlists(
+        &self,
+        term: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LibraryPlaylists>> {
+        let results = self
+            .search_library(term, &[LibrarySearchType::Playlists], offset, limit)
+            .await?;
+        Ok(results
+            .playlists
+            .map(|playlists| playlists.data)
+            .unwrap_or_default())
+    }
+
+    /// Searches the user's library for artists.
+    pub async fn search_library_artists(
+        &self,
+        term: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LibraryArtists>> {
+        let results = self
+            .search_library(term, &[LibrarySearchType::Artists], offset, limit)
+            .await?;
+        Ok(results
+            .artists
+            .map(|artists| artists.data)
+            .unwrap_or_default())
+    }
+
+    /// Searches the user's library for music videos.
+    pub async fn search_library_music_videos(
+        &self,
+        term: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<LibraryMusicVideos>> {
+        let results = self
+            .search_library(term, &[LibrarySearchType::MusicVideos], offset, limit)
+            .await?;
+        Ok(results
+            .music_videos
+            .map(|music_videos| music_videos.data)
+            .unwrap_or_default())
+    }
+
+    /// Gets the top-level genres of the storefront.
+    pub async fn get_genres(
+        &self,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Genre>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/genres?offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(25)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let genres: Vec<Genre> = serde_json::from_value(res["data"].clone())?;
+        Ok(genres)
+    }
+
+    /// Gets the genre information.
+    pub async fn get_genre(&self, genre_id: &str) -> Result<Genre> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/genres/{genre_id}",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let genre: Genre = serde_json::from_value(res["data"][0].clone())?;
+        Ok(genre)
+    }
+
+    /// Gets the charts of the storefront for the given resource types.
+    /// `chart` selects the chart kind, e.g. most-played, and `genre` filters by genre identifier.
+    pub async fn get_charts(
+        &self,
+        types: &[ChartType],
+        chart: Option<&str>,
+        genre: Option<&str>,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Charts> {
+        let store_front = self.store_front.clone();
+        let types = types
+            .iter()
+            .map(|t| t.as_str())
+            .collect::<Vec<&str>>()
+            .join(",");
+        let mut query = vec![
+            ("types", types),
+            ("offset", offset.unwrap_or(0).to_string()),
+            ("limit", limit.unwrap_or(20).to_string()),
+        ];
+        if let Some(chart) = chart {
+            query.push(("chart", chart.to_string()));
+        }
+        if let Some(genre) = genre {
+            query.push(("genre", genre.to_string()));
+        }
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/charts",
+                self.amp_api_url
+            ))
+            .query(&query)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let charts: Option<Charts> = serde_json::from_value(res["results"].clone())?;
+        Ok(charts.unwrap_or_default())
+    }
+
+    /// Gets the station information.
+    pub async fn get_station(&self, station_id: &str) -> Result<Stations> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/stations/{station_id}",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let station: Stations = serde_json::from_value(res["data"][0].clone())?;
+        Ok(station)
+    }
+
+    /// Gets the live radio stations of the storefront, e.g. Apple Music 1.
+    pub async fn get_live_stations(&self) -> Result<Vec<Stations>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/stations?filter[featured]=apple-music-live-radio",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let stations: Vec<Stations> = serde_json::from_value(res["data"].clone())?;
+        Ok(stations)
+    }
+
+    /// Gets the radio show information, including its latest episodes.
+    pub async fn get_radio_show(&self, radio_show_id: &str) -> Result<RadioShows> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/radio-shows/{radio_show_id}?include=episodes",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let radio_show: RadioShows = serde_json::from_value(res["data"][0].clone())?;
+        Ok(radio_show)
+    }
+
+    /// Gets the episodes of a radio show. `Stations::on_demand_id` tells the on-demand episodes
+    /// from the live ones.
+    pub async fn get_radio_show_episodes(
+        &self,
+        radio_show_id: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Stations>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/radio-shows/{radio_show_id}/episodes?offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(25)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let episodes: Vec<Stations> = serde_json::from_value(res["data"].clone())?;
+        Ok(episodes)
+    }
+
+    /// Gets the record label information.
+    pub async fn get_record_label(&self, record_label_id: &str) -> Result<RecordLabels> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/record-labels/{record_label_id}",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let record_label: RecordLabels = serde_json::from_value(res["data"][0].clone())?;
+        Ok(record_label)
+    }
+
+    /// Gets a page of the albums released by a record label, e.g. its latest releases.
+    pub async fn get_record_label_releases(
+        &self,
+        record_label_id: &str,
+        view: RecordLabelView,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Relationship<Albums>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/record-labels/{record_label_id}/view/{}?offset={}&limit={}",
+                self.amp_api_url,
+                view.as_str(),
+                offset.unwrap_or(0),
+                limit.unwrap_or(25)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let releases: Relationship<Albums> = serde_json::from_value(res)?;
+        Ok(releases)
+    }
+
+    /// Gets the curator information.
+    pub async fn get_curator(&self, curator_id: &str) -> Result<Curators> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/curators/{curator_id}",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let curator: Curators = serde_json::from_value(res["data"][0].clone())?;
+        Ok(curator)
+    }
+
+    /// Gets a page of the playlists of a curator.
+    pub async fn get_curator_playlists(
+        &self,
+        curator_id: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Relationship<Playlists>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/curators/{curator_id}/playlists?offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(25)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let playlists: Relationship<Playlists> = serde_json::from_value(res)?;
+        Ok(playlists)
+    }
+
+    /// Gets the Apple curator information.
+    pub async fn get_apple_curator(&self, apple_curator_id: &str) -> Result<AppleCurators> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/apple-curators/{apple_curator_id}",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let apple_curator: AppleCurators = serde_json::from_value(res["data"][0].clone())?;
+        Ok(apple_curator)
+    }
+
+    /// Gets a page of the playlists of an Apple curator.
+    pub async fn get_apple_curator_playlists(
+        &self,
+        apple_curator_id: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Relationship<Playlists>> {
+        let store_front = self.store_front.clone();
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/catalog/{store_front}/apple-curators/{apple_curator_id}/playlists?offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(25)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let playlists: Relationship<Playlists> = serde_json::from_value(res)?;
+        Ok(playlists)
+    }
+
+    /// Adds catalog songs, albums, playlists or music videos to the user's library.
+    /// Nothing is requested for an empty slice.
+    pub async fn add_to_library(&self, resources: &[ResourceIdentifier]) -> Result<()> {
+        if resources.is_empty() {
+            return Ok(());
+        }
+        let mut ids: Vec<(&str, Vec<&str>)> = Vec::new();
+        for resource in resources {
+            match ids.iter_mut().find(|(type_, _)| *type_ == resource.type_) {
+                Some((_, type_ids)) => type_ids.push(&resource.id),
+                None => ids.push((&resource.type_, vec![&resource.id])),
+            }
+        }
+        let query = ids
+            .iter()
+            .map(|(type_, type_ids)| format!("ids[{type_}]={}", type_ids.join(",")))
+            .collect::<Vec<String>>()
+            .join("&");
+        self.client
+            .post(format!("{}/v1/me/library?{query}", self.amp_api_url))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Creates a playlist in the user's library, optionally with initial tracks.
+    pub async fn create_library_playlist(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        tracks: &[ResourceIdentifier],
+    ) -> Result<LibraryPlaylists> {
+        let request = LibraryPlaylistCreationRequest {
+            attributes: CreationAttributes {
+                name: name.to_string(),
+                description: description.map(|description| description.to_string()),
+            },
+            relationships: (!tracks.is_empty()).then(|| CreationRelationships {
+                tracks: LibraryPlaylistTracksRequest {
+                    data: tracks.to_vec(),
+                },
+            }),
+        };
+        let res = self
+            .client
+            .post(format!("{}/v1/me/library/playlists", self.amp_api_url))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<serde_json::Value>()
+            .await?;
+        let playlist: LibraryPlaylists = serde_json::from_value(res["data"][0].clone())?;
+        Ok(playlist)
+    }
+
+    /// Appends tracks to a playlist in the user's library.
+    pub async fn add_tracks_to_library_playlist(
+        &self,
+        playlist_id: &str,
+        tracks: &[ResourceIdentifier],
+    ) -> Result<()> {
+        let request = LibraryPlaylistTracksRequest {
+            data: tracks.to_vec(),
+        };
+        self.client
+            .post(format!(
+                "{}/v1/me/library/playlists/{playlist_id}/tracks",
+                self.amp_api_url
+            ))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Gets the resources the user played recently, e.g. albums, playlists and stations.
+    pub async fn get_recently_played(
+        &self,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Content>> {
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/me/recent/played?offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(10)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let contents: Vec<Content> = serde_json::from_value(res["data"].clone())?;
+        Ok(contents)
+    }
+
+    /// Gets the songs and music videos the user played recently.
+    /// Use `Track::catalog_id` to download them.
+    pub async fn get_recently_played_tracks(
+        &self,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Track>> {
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/me/recent/played/tracks?types=songs,music-videos,library-songs,library-music-videos&offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(10)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let tracks: Vec<Track> = serde_json::from_value(res["data"].clone())?;
+        Ok(tracks)
+    }
+
+    /// Gets the resources the user has played most often recently.
+    pub async fn get_heavy_rotation(
+        &self,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Content>> {
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/me/history/heavy-rotation?offset={}&limit={}",
+                self.amp_api_url,
+                offset.unwrap_or(0),
+                limit.unwrap_or(10)
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let contents: Vec<Content> = serde_json::from_value(res["data"].clone())?;
+        Ok(contents)
+    }
+
+    /// Gets the recommendations for the user.
+    pub async fn get_recommendations(
+        &self,
+        offset: Option<usize>,
+        limit: Option<usize>,
+    ) -> Result<Vec<PersonalRecommendation>> {
+        let url = format!(
+            "{}/v1/me/recommendations?offset={}&limit={}",
+            self.amp_api_url,
+            offset.unwrap_or(0),
+            limit.unwrap_or(10)
+        );
+        let (recommendations, _) = self.get_recommendations_page(&url).await?;
+        Ok(recommendations)
+    }
+
+    /// Gets a page of recommendations, with the relative location of the next page, if any.
+    async fn get_recommendations_page(
+        &self,
+        url: &str,
+    ) -> Result<(Vec<PersonalRecommendation>, Option<String>)> {
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let recommendations: Vec<PersonalRecommendation> =
+            serde_json::from_value(res["data"].clone())?;
+        Ok((recommendations, res["next"].as_str().map(str::to_string)))
+    }
+
+    /// Gets the personal mixes of the user, e.g. Favorites Mix and New Music Mix.
+    /// The recommendations are fetched page by page, following the `next` link of each page
+    /// until a page has none or links to a page already fetched.
+    pub async fn get_personal_mixes(&self) -> Result<Vec<Playlists>> {
+        let mut mixes: Vec<Playlists> = Vec::new();
+        let mut visited = HashSet::new();
+        let mut url = Some(format!(
+            "{}/v1/me/recommendations?offset=0&limit=30",
+            self.amp_api_url
+        ));
+        while let Some(page_url) = url.take().filter(|url| visited.insert(url.clone())) {
+            let (recommendations, next) = self.get_recommendations_page(&page_url).await?;
+            let contents = recommendations
+                .into_iter()
+                .filter_map(|recommendation| recommendation.relationships?.contents)
+                .flat_map(|contents| contents.data);
+            for content in contents {
+                if let Content::Playlists(playlist) = content {
+                    let is_mix = playlist
+                        .attributes
+                        .as_ref()
+                        .is_some_and(|attributes| attributes.playlist_type == "personal-mix");
+                    if is_mix && !mixes.iter().any(|mix| mix.id == playlist.id) {
+                        mixes.push(playlist);
+                    }
+                }
+            }
+            url = next.map(|next| format!("{}{next}", self.amp_api_url));
+        }
+        Ok(mixes)
+    }
+
+    /// Gets the Apple Music Replay playlists of the user, one per year.
+    pub async fn get_replay_playlists(&self) -> Result<Vec<Playlists>> {
+        let res = self
+            .client
+            .get(format!(
+                "{}/v1/me/music-summaries/search?period=year&fields[music-summaries]=period,year&include[music-summaries]=playlist",
+                self.amp_api_url
+            ))
+            .send()
+            .await?
+            .json::<serde_json::Value>()
+            .await?;
+        let summaries: Vec<MusicSummaries> = serde_json::from_value(res["data"].clone())?;
+        Ok(summaries
+            .into_iter()
+            .filter_map(|summary| summary.relationships?.playlist)
+            .flat_map(|playlist| playlist.data)
+            .collect())
+    }
+
+    /// Downloads an artwork rendered at a size and format, e.g. to embed it as the cover with `Tags::cover`.
+    /// The size is clamped to the maximum size of the artwork. The last few downloaded images are cached by URL,
+    /// so the cover of an album is only downloaded once for all its tracks.
+    pub async fn get_artwork(
+        &self,
+        artwork: &Artwork,
+        width: u32,
+        height: u32,
+        format: ArtworkFormat,
+    ) -> Result<Vec<u8>> {
+        let url = artwork.url(width, height, format);
+        if let Some((_, image)) = self
+            .artwork_cache
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(cached_url, _)| *cached_url == url)
+        {
+            return Ok(image.clone());
+        }
+        let image = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?
+            .to_vec();
+        let mut artwork_cache = self.artwork_cache.lock().unwrap();
+        if artwork_cache.len() == ARTWORK_CACHE_SIZE {
+            artwork_cache.pop_front();
+        }
+        artwork_cache.push_back((url, image.clone()));
+        Ok(image)
+    }
+
+    /// Downloads the preview of a song or music video to `path`, tagged with `tags`.
+    /// Previews are not encrypted and can be downloaded without an Apple Music subscription.
+    pub async fn download_preview(
+        &self,
+        preview: &Preview,
+        source: PreviewSource,
+        tags: &Tags,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let data = match source {
+            PreviewSource::File => self
+                .client
+                .get(&preview.url)
+                .send()
+                .await?
+                .error_for_status()?
+                .bytes()
+                .await?
+                .to_vec(),
+            PreviewSource::Hls => {
+                let hls_url = preview
+                    .hls_url
+                    .as_ref()
+                    .ok_or_else(|| Error::Other("No HLS preview".to_string()))?;
+                let (segments, audio) =
+                    hls::download_stream_renditions(&self.client, hls_url).await?;
+                if !segments.is_fragmented_mp4()
+                    || audio
+                        .as_ref()
+                        .is_some_and(|audio| !audio.is_fragmented_mp4())
+                {
+                    return Err(Error::Other(
+                        "The HLS preview is not fragmented MP4, e.g. MPEG-TS".to_string(),
+                    ));
+                }
+                match audio {
+                    Some(audio) => remux::remux_renditions(&segments, &audio)?,
+                    None => remux::remux_segments(&segments)?,
+                }
+            }
+        };
+        let data = tags.write_mp4(&data)?;
+        if let Some(parent) = path.as_ref().parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    /// Downloads the motion artwork of an album or playlist into `dir`, usually the directory of the album.
+    /// The file is named after the variant, e.g. `square_animated_artwork.mp4`.
+    /// The album or playlist must be requested with `Extend::EditorialVideo`.
+    pub async fn download_motion_artwork(
+        &self,
+        editorial_video: &EditorialVideo,
+        variant: MotionArtworkVariant,
+        dir: impl AsRef<Path>,
+    ) -> Result<PathBuf> {
+        let motion_video = editorial_video
+            .get(variant)
+            .ok_or_else(|| Error::Other(format!("No {variant:?} motion artwork")))?;
+        let segments = hls::download_stream(&self.client, &motion_video.video).await?;
+        tokio::fs::create_dir_all(dir.as_ref()).await?;
+        let path = dir.as_ref().join(variant.file_name());
+        tokio::fs::write(&path, segments.concat()).await?;
+        Ok(path)
+    }
+
+    /// Gets the Widevine service certificate, fetched from the URL of `set_service_certificate_url`
+    /// once and then cached. The certificate is served either as is or base64-encoded.
+    pub async fn get_service_certificate(&self) -> Result<Vec<u8>> {
+        if let Some(certificate) = self.service_certificate.lock().unwrap().as_ref() {
+            return Ok(certificate.clone());
+        }
+        let url = self.service_certificate_url.as_ref().ok_or_else(|| {
+            Error::ServiceCertificate("No service certificate URL is set".to_string())
+        })?;
+        self.fetch_service_certificate(url).await
+    }
+
+    /// Gets the Widevine service certificate like `get_service_certificate`. Without a URL set,
+    /// the certificate is fetched from the `widevine_cert_url` of the WebPlayBack song of `track_id`.
+    pub async fn get_service_certificate_of(&self, track_id: &str) -> Result<Vec<u8>> {
+        if self.service_certificate_url.is_some()
+            || self.service_certificate.lock().unwrap().is_some()
+        {
+            return self.get_service_certificate().await;
+        }
+        let webplayback = self.get_webplayback(track_id).await?;
+        let song = webplayback.song_list.first().ok_or_else(|| {
+            Error::ServiceCertificate(format!("No WebPlayBack song for {track_id}"))
+        })?;
+        self.fetch_service_certificate(&song.widevine_cert_url)
+            .await
+    }
+
+    /// Fetches the Widevine service certificate from `url` and caches it.
+    async fn fetch_service_certificate(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let certificate = match widevine::ServiceCertificate::try_from(response.as_ref()) {
+            Ok(_) => response.to_vec(),
+            Err(error) => {
+                let decoded = base64::engine::general_purpose::STANDARD
+                    .decode(response.trim_ascii())
+                    .map_err(|_| Error::ServiceCertificate(error.to_string()))?;
+                widevine::ServiceCertificate::try_from(decoded.as_slice())
+                    .map_err(|e| Error::ServiceCertificate(e.to_string()))?;
+                decoded
+            }
+        };
+        *self.service_certificate.lock().unwrap() = Some(certificate.clone());
+        Ok(certificate)
+    }
+
     /// Gets the Widevine license.
     pub async fn get_widevine_license(
         &self,
@@ -408,17 +1354,91 @@ impl AppleMusicDownloader {
         Ok(response)
     }
 
-    /// Gets the decryptioin key.
+    /// Gets the stream of a song from its enhanced HLS master playlist, selecting the first codec of
+    /// `codecs` available, e.g. `[Codec::Alac, Codec::Aac]` for lossless with a lossy fallback.
+    /// The song is requested with its extended asset URLs.
+    pub async fn get_stream_info(&self, song_id: &str, codecs: &[Codec]) -> Result<StreamInfo> {
+        let song = self
+            .get_song(song_id, &Query::new().extend(Extend::ExtendedAssetUrls))
+            .await?;
+        StreamInfo::new_with_song(&song, &self.client, codecs).await
+    }
+
+    /// Gets the decryption key.
     pub async fn get_decryption_key(
         &self,
         stream_info: &StreamInfo,
         track_id: &str,
     ) -> Result<String> {
         let cdm = widevine::Cdm::new(self.device.clone());
-        let decryption_key = decrypter::get_decrypt_key(&cdm, &stream_info.pssh, track_id, self)
-            .await
-            .unwrap();
-        Ok(decryption_key)
+        decrypter::get_decrypt_key(&cdm, &stream_info.pssh, track_id, self).await
+    }
+
+    /// Gets the content keys of several PSSH URIs with one license request, by key id,
+    /// e.g. the keys of the renditions of a music video; see `cenc::decrypt_with_keys`.
+    pub async fn get_decryption_keys(
+        &self,
+        psshs: &[&str],
+        track_id: &str,
+    ) -> Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+        let cdm = widevine::Cdm::new(self.device.clone());
+        decrypter::get_decrypt_keys(&cdm, psshs, track_id, self).await
+    }
+
+    /// Downloads and decrypts the stream of a song into a progressive MP4 file. The codec boxes
+    /// of the stream are kept, e.g. `dec3` for Dolby Atmos; see `ec3::to_raw` for a raw `.ec3` file.
+    pub async fn download_stream(
+        &self,
+        stream_info: &StreamInfo,
+        track_id: &str,
+    ) -> Result<Vec<u8>> {
+        let key = self.get_decryption_key(stream_info, track_id).await?;
+        let key = hex::decode(key).map_err(|e| Error::Decrypt(e.to_string()))?;
+        let segments = hls::download_stream(&self.client, &stream_info.stream_url).await?;
+        remux::remux(&cenc::decrypt(&segments.concat(), &key)?)
+    }
+
+    /// Downloads the file of a WebPlayBack asset and verifies its size and MD5 checksum. A file that
+    /// does not match is repaired by fetching again its chunks; see `integrity::Integrity::repair`
+    /// and `integrity::verify_asset` to re-check the file later.
+    pub async fn download_asset(
+        &self,
+        asset: &webplayback::Asset,
+    ) -> Result<integrity::AssetDownload> {
+        let url = self.asset_file_url(asset).await?;
+        integrity::download_asset(&self.client, &url, &integrity::Integrity::of(asset)).await
+    }
+
+    /// Returns the URL of the file of an asset. The URL of an asset served by HLS is a media playlist
+    /// of byte ranges of a single file.
+    async fn asset_file_url(&self, asset: &webplayback::Asset) -> Result<String> {
+        let path = asset.url.split(['?', '#']).next().unwrap_or_default();
+        if !path.ends_with(".m3u8") {
+            return Ok(asset.url.clone());
+        }
+        let (media_url, media) = hls::get_media_playlist(&self.client, &asset.url).await?;
+        let mut uris = media.segments.iter().flat_map(|segment| {
+            segment
+                .map
+                .iter()
+                .map(|map| &map.uri)
+                .chain(std::iter::once(&segment.uri))
+        });
+        let uri = uris
+            .next()
+            .ok_or_else(|| Error::Other("The asset playlist has no segment".to_string()))?;
+        if uris.any(|other| other != uri) {
+            return Err(Error::Other(
+                "The asset playlist has segments of several files".to_string(),
+            ));
+        }
+        Ok(hls::resolve_uri(&media_url, uri))
+    }
+
+    /// Decrypts a stream downloaded earlier, e.g. a directory of encrypted segments, with the
+    /// content keys of the key store, without any network access; see `offline::read_segments`.
+    pub fn decrypt_local(&self, input: &Path, output: &Path) -> Result<()> {
+        offline::decrypt_path(input, output, offline::ContentKeys::Store(self.key_store()))
     }
 }
 
@@ -448,4 +1468,62 @@ mod tests {
 
         assert_eq!(decryption_key.len(), 32);
     }
+
+    #[tokio::test]
+    async fn get_service_certificate() {
+        let server = mock_server::MockServer::start(vec![mock_server::MockResponse {
+            status: 200,
+            headers: vec![],
+            body: b"not a certificate".to_vec(),
+        }])
+        .await;
+        let mut apple_music_downloader =
+            AppleMusicDownloader::new("Asc+xxx", "us", "en-US", "eyJhxxx");
+        assert!(matches!(
+            apple_music_downloader.get_service_certificate().await,
+            Err(Error::ServiceCertificate(_))
+        ));
+
+        apple_music_downloader.set_privacy_mode(true);
+        apple_music_downloader.set_service_certificate_url(&format!("{}/widevineCert", server.url));
+        assert!(matches!(
+            apple_music_downloader.get_service_certificate().await,
+            Err(Error::ServiceCertificate(_))
+        ));
+        assert_eq!(server.requests()[0].path, "/widevineCert");
+        assert!(matches!(
+            apple_music_downloader.set_service_certificate(&[0; 16]),
+            Err(Error::ServiceCertificate(_))
+        ));
+
+        // The certificate is shared with the clones, whether set or fetched.
+        let certificate = base64::engine::general_purpose::STANDARD
+            .decode(COMMON_SERVICE_CERTIFICATE)
+            .unwrap();
+        let clone = apple_music_downloader.clone();
+        apple_music_downloader
+            .set_service_certificate(&certificate)
+            .unwrap();
+        assert_eq!(clone.get_service_certificate().await.unwrap(), certificate);
+
+        let server = mock_server::MockServer::start(vec![mock_server::MockResponse {
+            status: 200,
+            headers: vec![],
+            body: COMMON_SERVICE_CERTIFICATE.as_bytes().to_vec(),
+        }])
+        .await;
+        apple_music_downloader.set_service_certificate_url(&format!("{}/widevineCert", server.url));
+        assert_eq!(
+            apple_music_downloader
+                .get_service_certificate_of("1753050648")
+                .await
+                .unwrap(),
+            certificate
+        );
+        assert_eq!(clone.get_service_certificate().await.unwrap(), certificate);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    /// The public service certificate of the Widevine license servers, base64-encoded.
+    const COMMON_SERVICE_CERTIFICATE: &str = "CAUSxwUKwQIIAxIQFwW5F8wSBIaLBjM6L3cqjBiCtIKSBSKOAjCCAQoCggEBAJntWzsyfateJO/DtiqVtZhSCtW8yzdQPgZFuBTYdrjfQFEEQa2M462xG7iMTnJaXkqeB5UpHVhYQCOn4a8OOKkSeTkwCGELbxWMh4x+Ib/7/up34QGeHleB6KRfRiY9FOYOgFioYHrc4E+shFexN6jWfM3rM3BdmDoh+07svUoQykdJDKR+ql1DghjduvHK3jOS8T1v+2RC/THhv0CwxgTRxLpMlSCkv5fuvWCSmvzu9Vu69WTi0Ods18Vcc6CCuZYSC4NZ7c4kcHCCaA1vZ8bYLErF8xNEkKdO7DevSy8BDFnoKEPiWC8La59dsPxebt9k+9MItHEbzxJQAZyfWgkCAwEAAToUbGljZW5zZS53aWRldmluZS5jb20SgAOuNHMUtag1KX8nE4j7e7jLUnfSSYI83dHaMLkzOVEes8y96gS5RLknwSE0bv296snUE5F+bsF2oQQ4RgpQO8GVK5uk5M4PxL/CCpgIqq9L/NGcHc/N9XTMrCjRtBBBbPneiAQwHL2zNMr80NQJeEI6ZC5UYT3wr8+WykqSSdhV5Cs6cD7xdn9qm9Nta/gr52u/DLpP3lnSq8x2/rZCR7hcQx+8pSJmthn8NpeVQ/ypy727+voOGlXnVaPHvOZV+WRvWCq5z3CqCLl5+Gf2Ogsrf9s2LFvE7NVV2FvKqcWTw4PIV9Sdqrd+QLeFHd/SSZiAjjWyWOddeOrAyhb3BHMEwg2T7eTo/xxvF+YkPj89qPwXCYcOxF+6gjomPwzvofcJOxkJkoMmMzcFBDopvab5tDQsyN9UPLGhGC98X/8z8QSQ+spbJTYLdgFenFoGq47gLwDS6NWYYQSqzE3Udf2W7pzk4ybyG4PHBYV3s4cyzdq8amvtE/sNSdOKReuHpfQ=";
 }
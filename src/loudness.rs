@@ -0,0 +1,349 @@
+//! the loudness measurement (EBU R128) and the ReplayGain and Sound Check metadata.
+//!
+//! The integrated loudness follows ITU-R BS.1770: the samples are K-weighted, their energy is
+//! measured over 400 ms blocks overlapping by 75%, and the blocks quieter than -70 LUFS or 10 LU
+//! below the loudness of the remaining blocks are gated out.
+//!
+//! Only ALAC tracks are decoded for a measurement, as there is no AAC decoder; the loudness of
+//! AAC variants is the one of their audio asset metadata, see `StreamInfo::loudness`.
+
+use crate::alac::{self, Pcm};
+use crate::error::Result;
+
+/// The loudness targeted by ReplayGain 2.0, in LUFS.
+pub const REPLAY_GAIN_REFERENCE: f64 = -18.0;
+
+/// The absolute gate of the gating blocks, in LUFS.
+const ABSOLUTE_GATE: f64 = -70.0;
+/// The relative gate of the gating blocks, in LU.
+const RELATIVE_GATE: f64 = -10.0;
+/// The number of 100 ms steps of a 400 ms gating block.
+const STEPS_PER_BLOCK: usize = 4;
+
+/// The loudness of a track or an album.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loudness {
+    /// The integrated loudness, in LUFS.
+    pub integrated: f64,
+    /// The peak, in dBFS: the true peak for Apple's asset metadata, the sample peak when measured.
+    pub peak: Option<f64>,
+}
+
+/// The ReplayGain of a track, with the gain of its album.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+    /// The track gain, in dB.
+    pub track_gain: f64,
+    /// The track peak, as a ratio of the full scale.
+    pub track_peak: Option<f64>,
+    /// The album gain, in dB.
+    pub album_gain: Option<f64>,
+    /// The album peak, as a ratio of the full scale.
+    pub album_peak: Option<f64>,
+}
+
+impl ReplayGain {
+    /// Returns the ReplayGain of a track from its loudness and the loudness of its album.
+    pub fn new(track: &Loudness, album: Option<&Loudness>) -> Self {
+        let peak = |loudness: &Loudness| loudness.peak.map(|peak| 10f64.powf(peak / 20.0));
+        Self {
+            track_gain: REPLAY_GAIN_REFERENCE - track.integrated,
+            track_peak: peak(track),
+            album_gain: album.map(|album| REPLAY_GAIN_REFERENCE - album.integrated),
+            album_peak: album.and_then(peak),
+        }
+    }
+
+    /// Returns the ReplayGain tags, as (name, value) pairs, e.g. ("REPLAYGAIN_TRACK_GAIN", "-3.21 dB").
+    pub fn to_tags(&self) -> Vec<(String, String)> {
+        let mut tags = vec![(
+            "REPLAYGAIN_TRACK_GAIN".to_string(),
+            format!("{:.2} dB", self.track_gain),
+        )];
+        if let Some(track_peak) = self.track_peak {
+            tags.push((
+                "REPLAYGAIN_TRACK_PEAK".to_string(),
+                format!("{track_peak:.6}"),
+            ));
+        }
+        if let Some(album_gain) = self.album_gain {
+            tags.push((
+                "REPLAYGAIN_ALBUM_GAIN".to_string(),
+                format!("{album_gain:.2} dB"),
+            ));
+        }
+        if let Some(album_peak) = self.album_peak {
+            tags.push((
+                "REPLAYGAIN_ALBUM_PEAK".to_string(),
+                format!("{album_peak:.6}"),
+            ));
+        }
+        tags
+    }
+
+    /// Returns the value of the `iTunNORM` tag, the Sound Check volume adjustment of iTunes,
+    /// from the track gain and peak.
+    pub fn to_itunnorm(&self) -> String {
+        let adjustment = |reference: f64| {
+            (reference * 10f64.powf(-self.track_gain / 10.0))
+                .round()
+                .clamp(0.0, 65534.0) as u32
+        };
+        let (adjustment_1000, adjustment_2500) = (adjustment(1000.0), adjustment(2500.0));
+        let peak = (self.track_peak.unwrap_or(1.0) * 32768.0)
+            .round()
+            .clamp(0.0, 65534.0) as u32;
+        format!(
+            " {adjustment_1000:08X} {adjustment_1000:08X} {adjustment_2500:08X} {adjustment_2500:08X} 00024CA8 00024CA8 {peak:08X} {peak:08X} 00024CA8 00024CA8"
+        )
+    }
+}
+
+/// A biquad filter, in transposed direct form II.
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.state[0];
+        self.state[0] = self.b[1] * input - self.a[0] * output + self.state[1];
+        self.state[1] = self.b[2] * input - self.a[1] * output;
+        output
+    }
+}
+
+/// Returns the two stages of the K-weighting filter at a sample rate: a high shelf modelling
+/// the acoustic effect of the head, then a high-pass filter.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = sample_rate as f64;
+    let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        state: [0.0; 2],
+    };
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        state: [0.0; 2],
+    };
+    [shelf, high_pass]
+}
+
+/// Returns the weight of a channel in the ALAC channel layouts:
+/// 1.41 for the surround channels of 5.0 and 5.1, 0 for the LFE channel of 5.1.
+fn channel_weight(channels: u8, index: usize) -> f64 {
+    match (channels, index) {
+        (5 | 6, 3 | 4) => 1.41,
+        (6, 5) => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// Converts a mean square to a loudness, in LUFS.
+fn to_loudness(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+/// An EBU R128 loudness meter, fed with decoded PCM samples.
+#[derive(Debug, Clone)]
+pub struct R128Meter {
+    channels: u8,
+    filters: Vec<[Biquad; 2]>,
+    weights: Vec<f64>,
+    /// The number of frames of a 100 ms step.
+    step_len: usize,
+    /// The weighted sum of squares of the current step, and its number of frames.
+    step_energy: f64,
+    step_frames: usize,
+    /// The mean squares of the complete steps.
+    steps: Vec<f64>,
+    /// The highest absolute sample, as a ratio of the full scale.
+    peak: f64,
+}
+
+impl R128Meter {
+    pub fn new(sample_rate: u32, channels: u8) -> Self {
+        Self {
+            channels,
+            filters: vec![k_weighting(sample_rate); channels as usize],
+            weights: (0..channels as usize)
+                .map(|index| channel_weight(channels, index))
+                .collect(),
+            step_len: (sample_rate as usize / 10).max(1),
+            step_energy: 0.0,
+            step_frames: 0,
+            steps: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    /// Measures the samples, which follow the samples already measured.
+    /// The sample rate and channels must be those of the meter.
+    pub fn add(&mut self, pcm: &Pcm) {
+        if self.channels == 0 {
+            return;
+        }
+        let channels = self.channels as usize;
+        let scale = 1.0 / (1u64 << pcm.bits_per_sample.clamp(1, 32).saturating_sub(1)) as f64;
+        for frame in pcm.samples.chunks_exact(channels) {
+            for (index, sample) in frame.iter().enumerate() {
+                let sample = *sample as f64 * scale;
+                self.peak = self.peak.max(sample.abs());
+                let [shelf, high_pass] = &mut self.filters[index];
+                let weighted = high_pass.process(shelf.process(sample));
+                self.step_energy += self.weights[index] * weighted * weighted;
+            }
+            self.step_frames += 1;
+            if self.step_frames == self.step_len {
+                self.steps.push(self.step_energy / self.step_len as f64);
+                self.step_energy = 0.0;
+                self.step_frames = 0;
+            }
+        }
+    }
+
+    /// Returns the mean squares of the gating blocks measured so far.
+    fn blocks(&self) -> impl Iterator<Item = f64> + '_ {
+        self.steps
+            .windows(STEPS_PER_BLOCK)
+            .map(|steps| steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64)
+    }
+
+    /// Returns the integrated loudness and the sample peak of the samples measured so far,
+    /// or `None` when they are shorter than a gating block or silent.
+    pub fn loudness(&self) -> Option<Loudness> {
+        Self::album_loudness(std::slice::from_ref(self))
+    }
+
+    /// Returns the integrated loudness and the sample peak of the tracks of an album,
+    /// gating the blocks of all the tracks together.
+    pub fn album_loudness(meters: &[R128Meter]) -> Option<Loudness> {
+        let blocks: Vec<f64> = meters
+            .iter()
+            .flat_map(|meter| meter.blocks())
+            .filter(|energy| to_loudness(*energy) > ABSOLUTE_GATE)
+            .collect();
+        if blocks.is_empty() {
+            return None;
+        }
+        let threshold =
+            to_loudness(blocks.iter().sum::<f64>() / blocks.len() as f64) + RELATIVE_GATE;
+        let gated: Vec<f64> = blocks
+            .into_iter()
+            .filter(|energy| to_loudness(*energy) > threshold)
+            .collect();
+        let peak = meters.iter().map(|meter| meter.peak).fold(0.0, f64::max);
+        Some(Loudness {
+            integrated: to_loudness(gated.iter().sum::<f64>() / gated.len() as f64),
+            peak: Some(20.0 * peak.log10()),
+        })
+    }
+}
+
+/// Measures the integrated loudness and the sample peak of PCM samples.
+pub fn measure(pcm: &Pcm) -> Option<Loudness> {
+    let mut meter = R128Meter::new(pcm.sample_rate, pcm.channels);
+    meter.add(pcm);
+    meter.loudness()
+}
+
+/// Decodes an ALAC MP4 file, e.g. a decrypted Apple Music song, and measures its loudness.
+/// Tracks of other codecs, e.g. AAC, are an `Error::Alac`.
+pub fn measure_alac_mp4(mp4: &[u8]) -> Result<Option<Loudness>> {
+    Ok(measure(&alac::decode_mp4(mp4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{measure, measure_alac_mp4, Loudness, R128Meter, ReplayGain};
+    use crate::alac::Pcm;
+    use crate::error::Error;
+    use crate::mp4::fixtures::init_segment;
+
+    /// A stereo sine wave of 1 kHz at 48 kHz, with an amplitude in dBFS.
+    fn sine(amplitude: f64, seconds: usize) -> Pcm {
+        let amplitude = 10f64.powf(amplitude / 20.0) * 32767.0;
+        Pcm {
+            sample_rate: 48000,
+            channels: 2,
+            bits_per_sample: 16,
+            samples: (0..48000 * seconds)
+                .flat_map(|i| {
+                    let sample = (amplitude
+                        * (2.0 * std::f64::consts::PI * 1000.0 * i as f64 / 48000.0).sin())
+                    .round() as i32;
+                    [sample, sample]
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_measure() {
+        // A stereo 1 kHz sine at -20 dBFS measures -20 LUFS.
+        let loudness = measure(&sine(-20.0, 5)).unwrap();
+        assert!((loudness.integrated + 20.0).abs() < 0.1);
+        assert!((loudness.peak.unwrap() + 20.0).abs() < 0.01);
+        assert_eq!(measure(&sine(-100.0, 5)), None);
+
+        // The quiet track is gated out of the album by the relative gate.
+        let mut loud = R128Meter::new(48000, 2);
+        loud.add(&sine(-20.0, 5));
+        let mut quiet = R128Meter::new(48000, 2);
+        quiet.add(&sine(-40.0, 5));
+        let album = R128Meter::album_loudness(&[loud, quiet]).unwrap();
+        assert!((album.integrated + 20.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_replay_gain() {
+        let track = Loudness {
+            integrated: -8.0,
+            peak: Some(0.0),
+        };
+        let album = Loudness {
+            integrated: -9.0,
+            peak: Some(-6.0),
+        };
+        let replay_gain = ReplayGain::new(&track, Some(&album));
+        assert_eq!(
+            replay_gain.to_tags(),
+            [
+                ("REPLAYGAIN_TRACK_GAIN", "-10.00 dB"),
+                ("REPLAYGAIN_TRACK_PEAK", "1.000000"),
+                ("REPLAYGAIN_ALBUM_GAIN", "-9.00 dB"),
+                ("REPLAYGAIN_ALBUM_PEAK", "0.501187"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+        assert_eq!(
+            replay_gain.to_itunnorm(),
+            " 00002710 00002710 000061A8 000061A8 00024CA8 00024CA8 00008000 00008000 00024CA8 00024CA8"
+        );
+    }
+
+    #[test]
+    fn test_measure_aac() {
+        let aac = init_segment(b"cenc", None);
+        match measure_alac_mp4(&aac) {
+            Err(Error::Alac(message)) => assert_eq!(message, "The track is mp4a, not ALAC"),
+            result => panic!("Unexpected result {result:?}"),
+        }
+    }
+}
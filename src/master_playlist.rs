@@ -0,0 +1,314 @@
+//! the typed information of an HLS master playlist of Apple Music.
+
+use crate::error::{Error, Result};
+use crate::hls::resolve_uri;
+use base64::Engine;
+use m3u8_rs::{MasterPlaylist, SessionDataField};
+use std::collections::{BTreeMap, HashMap};
+
+/// The KEYFORMAT of Widevine.
+pub const WIDEVINE_KEY_FORMAT: &str = "urn:uuid:edef8ba9-79d6-4ace-a3c8-27dcd51d21ed";
+/// The KEYFORMAT of PlayReady.
+pub const PLAYREADY_KEY_FORMAT: &str = "com.microsoft.playready";
+/// The KEYFORMAT of FairPlay.
+pub const FAIRPLAY_KEY_FORMAT: &str = "com.apple.streamingkeydelivery";
+
+/// The session data id of the keys of the master playlist.
+const AUDIO_SESSION_KEY_INFO: &str = "com.apple.hls.AudioSessionKeyInfo";
+/// The session data id of the audio metadata of the master playlist.
+const AUDIO_ASSET_METADATA: &str = "com.apple.hls.audioAssetMetadata";
+
+/// The typed information of a master playlist: its variants, renditions, keys and audio metadata.
+#[derive(Debug, Clone)]
+pub struct MasterPlaylistInfo {
+    /// The variants (`EXT-X-STREAM-INF`), in playlist order.
+    pub variants: Vec<Variant>,
+    /// The renditions (`EXT-X-MEDIA`), in playlist order.
+    pub renditions: Vec<Rendition>,
+    /// The keys of the session (`com.apple.hls.AudioSessionKeyInfo`), by key id then by KEYFORMAT,
+    /// i.e. the DRM system, e.g. `WIDEVINE_KEY_FORMAT`.
+    pub session_keys: BTreeMap<String, HashMap<String, SessionKey>>,
+    /// The audio metadata (`com.apple.hls.audioAssetMetadata`), by stable variant id.
+    pub asset_metadata: HashMap<String, AudioAssetMetadata>,
+}
+
+/// A variant of a master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    /// The URL of the media playlist, resolved against the URL of the master playlist.
+    pub uri: String,
+    pub bandwidth: u64,
+    pub average_bandwidth: Option<u64>,
+    /// The codecs, e.g. `alac`, `mp4a.40.2` or `ec-3`.
+    pub codecs: Option<String>,
+    /// The group id of the audio renditions of the variant.
+    pub audio: Option<String>,
+    /// The stable id of the variant, the key of its audio metadata.
+    pub stable_variant_id: Option<String>,
+}
+
+/// A rendition of a master playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rendition {
+    /// The type of the rendition, e.g. AUDIO.
+    pub media_type: String,
+    /// The URL of the media playlist, resolved against the URL of the master playlist.
+    pub uri: Option<String>,
+    pub group_id: String,
+    pub name: String,
+    pub language: Option<String>,
+    pub default: bool,
+    pub autoselect: bool,
+    /// The channels, e.g. `2` or `16/JOC`.
+    pub channels: Option<String>,
+    /// The attributes not covered above, e.g. STABLE-RENDITION-ID.
+    pub other_attributes: HashMap<String, String>,
+}
+
+/// A key of the session, for one DRM system.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionKey {
+    #[serde(rename = "METHOD")]
+    pub method: Option<String>,
+    /// The URI of the key: a PSSH data URI for Widevine and PlayReady, a `skd://` URI for FairPlay.
+    #[serde(rename = "URI")]
+    pub uri: Option<String>,
+    #[serde(rename = "KEYFORMAT")]
+    pub key_format: Option<String>,
+    #[serde(rename = "KEYFORMATVERSIONS")]
+    pub key_format_versions: Option<String>,
+}
+
+/// The audio metadata of a variant.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+pub struct AudioAssetMetadata {
+    /// The ids of the session keys of the variant.
+    #[serde(default)]
+    pub audio_session_key_ids: Vec<String>,
+    pub bit_depth: Option<u32>,
+    /// The bit rate, in kbit/s.
+    pub bit_rate: Option<u32>,
+    /// The sample rate, in Hz.
+    pub sample_rate: Option<u32>,
+    /// The channel count, e.g. `2` or `16`.
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub channel_count: Option<String>,
+    pub is_atmos: Option<bool>,
+    /// The measured loudness, in LUFS.
+    #[serde(alias = "MEASURED-LOUDNESS", alias = "INTEGRATED-LOUDNESS")]
+    pub loudness: Option<f64>,
+    /// The measured true peak, in dBTP.
+    #[serde(alias = "MEASURED-PEAK", alias = "TRUE-PEAK")]
+    pub peak: Option<f64>,
+    /// The fields not covered above.
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(value)) => Some(value),
+            Some(serde_json::Value::Number(value)) => Some(value.to_string()),
+            _ => None,
+        },
+    )
+}
+
+impl MasterPlaylistInfo {
+    /// Parses a master playlist. `base_uri` is the URL of the playlist, used to resolve the URIs.
+    pub fn new(m3u8: &[u8], base_uri: &str) -> Result<Self> {
+        let (_, master) =
+            m3u8_rs::parse_master_playlist(m3u8).map_err(|e| Error::Other(e.to_string()))?;
+        Self::from_master_playlist(&master, base_uri)
+    }
+
+    /// Creates a `MasterPlaylistInfo` from a parsed master playlist.
+    /// Missing session data results in empty `session_keys` or `asset_metadata`.
+    pub fn from_master_playlist(master: &MasterPlaylist, base_uri: &str) -> Result<Self> {
+        let variants = master
+            .variants
+            .iter()
+            .filter(|variant| !variant.is_i_frame)
+            .map(|variant| Variant {
+                uri: resolve_uri(base_uri, &variant.uri),
+                bandwidth: variant.bandwidth,
+                average_bandwidth: variant.average_bandwidth,
+                codecs: variant.codecs.clone(),
+                audio: variant.audio.clone(),
+                stable_variant_id: variant
+                    .other_attributes
+                    .as_ref()
+                    .and_then(|attributes| attributes.get("STABLE-VARIANT-ID"))
+                    .map(|id| id.as_str().to_string()),
+            })
+            .collect();
+        let renditions = master
+            .alternatives
+            .iter()
+            .map(|media| Rendition {
+                media_type: media.media_type.to_string(),
+                uri: media.uri.as_ref().map(|uri| resolve_uri(base_uri, uri)),
+                group_id: media.group_id.clone(),
+                name: media.name.clone(),
+                language: media.language.clone(),
+                default: media.default,
+                autoselect: media.autoselect,
+                channels: media.channels.clone(),
+                other_attributes: media
+                    .other_attributes
+                    .iter()
+                    .flatten()
+                    .map(|(key, value)| (key.clone(), value.as_str().to_string()))
+                    .collect(),
+            })
+            .collect();
+        Ok(Self {
+            variants,
+            renditions,
+            session_keys: session_data(master, AUDIO_SESSION_KEY_INFO)?.unwrap_or_default(),
+            asset_metadata: session_data(master, AUDIO_ASSET_METADATA)?.unwrap_or_default(),
+        })
+    }
+
+    /// Returns the renditions grouped by group id.
+    pub fn groups(&self) -> BTreeMap<&str, Vec<&Rendition>> {
+        let mut groups: BTreeMap<&str, Vec<&Rendition>> = BTreeMap::new();
+        for rendition in &self.renditions {
+            groups
+                .entry(rendition.group_id.as_str())
+                .or_default()
+                .push(rendition);
+        }
+        groups
+    }
+
+    /// Returns the audio metadata of a variant.
+    pub fn metadata(&self, variant: &Variant) -> Option<&AudioAssetMetadata> {
+        self.asset_metadata
+            .get(variant.stable_variant_id.as_deref()?)
+    }
+
+    /// Returns the session keys of a variant for a DRM system, e.g. `WIDEVINE_KEY_FORMAT`.
+    pub fn keys(&self, variant: &Variant, key_format: &str) -> Vec<&SessionKey> {
+        let Some(metadata) = self.metadata(variant) else {
+            return Vec::new();
+        };
+        metadata
+            .audio_session_key_ids
+            .iter()
+            .filter_map(|id| self.session_keys.get(id)?.get(key_format))
+            .collect()
+    }
+
+    /// Returns the Widevine PSSH URI of a variant.
+    /// The key id "1" is the key shared by the previews and is skipped.
+    pub fn widevine_pssh(&self, variant: &Variant) -> Option<&str> {
+        let metadata = self.metadata(variant)?;
+        metadata
+            .audio_session_key_ids
+            .iter()
+            .filter(|id| id.as_str() != "1")
+            .find_map(|id| {
+                self.session_keys
+                    .get(id)?
+                    .get(WIDEVINE_KEY_FORMAT)?
+                    .uri
+                    .as_deref()
+            })
+    }
+}
+
+/// Decodes the base64 JSON value of a session data.
+fn session_data<T: serde::de::DeserializeOwned>(
+    master: &MasterPlaylist,
+    data_id: &str,
+) -> Result<Option<T>> {
+    let Some(session_data) = master
+        .session_data
+        .iter()
+        .find(|data| data.data_id == data_id)
+    else {
+        return Ok(None);
+    };
+    match &session_data.field {
+        SessionDataField::Value(value) => {
+            let value = base64::engine::general_purpose::STANDARD.decode(value)?;
+            Ok(Some(serde_json::from_slice(&value)?))
+        }
+        SessionDataField::Uri(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MasterPlaylistInfo, FAIRPLAY_KEY_FORMAT, WIDEVINE_KEY_FORMAT};
+    use base64::Engine;
+
+    #[test]
+    fn test_master_playlist_info() {
+        let encode = |value: serde_json::Value| {
+            base64::engine::general_purpose::STANDARD.encode(value.to_string())
+        };
+        let keys = encode(serde_json::json!({
+            "1": {
+                WIDEVINE_KEY_FORMAT: { "METHOD": "SAMPLE-AES", "URI": "data:text/plain;base64,cHJldmlldw==", "KEYFORMAT": WIDEVINE_KEY_FORMAT, "KEYFORMATVERSIONS": "1" }
+            },
+            "2": {
+                WIDEVINE_KEY_FORMAT: { "METHOD": "SAMPLE-AES", "URI": "data:text/plain;base64,a2V5", "KEYFORMAT": WIDEVINE_KEY_FORMAT, "KEYFORMATVERSIONS": "1" },
+                FAIRPLAY_KEY_FORMAT: { "METHOD": "SAMPLE-AES", "URI": "skd://itunes.apple.com/P000000000/s1/e2", "KEYFORMAT": FAIRPLAY_KEY_FORMAT, "KEYFORMATVERSIONS": "1" }
+            }
+        }));
+        let metadata = encode(serde_json::json!({
+            "alac-96": { "AUDIO-SESSION-KEY-IDS": ["1", "2"], "BIT-DEPTH": 24, "SAMPLE-RATE": 96000, "CHANNEL-COUNT": "2", "IS-ATMOS": false, "MEASURED-LOUDNESS": -9.5, "FIRST-SEGMENT-DURATION": 2.5 },
+            "atmos": { "AUDIO-SESSION-KEY-IDS": ["2"], "BIT-RATE": 768, "CHANNEL-COUNT": 16, "IS-ATMOS": true }
+        }));
+        let m3u8 = format!(
+            "#EXTM3U\n\
+            #EXT-X-SESSION-DATA:DATA-ID=\"com.apple.hls.AudioSessionKeyInfo\",VALUE=\"{keys}\"\n\
+            #EXT-X-SESSION-DATA:DATA-ID=\"com.apple.hls.audioAssetMetadata\",VALUE=\"{metadata}\"\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio-alac-stereo-96000-24\",NAME=\"Apple Lossless\",DEFAULT=YES,AUTOSELECT=YES,CHANNELS=\"2\",STABLE-RENDITION-ID=\"alac\"\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio-atmos-768\",NAME=\"Dolby Atmos\",CHANNELS=\"16/JOC\",URI=\"atmos.m3u8\"\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=3000000,CODECS=\"alac\",AUDIO=\"audio-alac-stereo-96000-24\",STABLE-VARIANT-ID=\"alac-96\"\n\
+            alac.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=800000,CODECS=\"ec-3\",AUDIO=\"audio-atmos-768\",STABLE-VARIANT-ID=\"atmos\"\n\
+            atmos.m3u8\n"
+        );
+        let info =
+            MasterPlaylistInfo::new(m3u8.as_bytes(), "https://example.com/a/P.m3u8").unwrap();
+
+        assert_eq!(info.variants.len(), 2);
+        let alac = &info.variants[0];
+        assert_eq!(alac.uri, "https://example.com/a/alac.m3u8");
+        let metadata = info.metadata(alac).unwrap();
+        assert_eq!(metadata.bit_depth, Some(24));
+        assert_eq!(metadata.sample_rate, Some(96000));
+        assert_eq!(metadata.loudness, Some(-9.5));
+        assert!(metadata.other.contains_key("FIRST-SEGMENT-DURATION"));
+        assert_eq!(
+            info.widevine_pssh(alac),
+            Some("data:text/plain;base64,a2V5")
+        );
+        assert_eq!(info.keys(alac, FAIRPLAY_KEY_FORMAT).len(), 1);
+
+        let atmos = &info.variants[1];
+        assert_eq!(
+            info.metadata(atmos).unwrap().channel_count.as_deref(),
+            Some("16")
+        );
+        let groups = info.groups();
+        assert_eq!(
+            groups["audio-atmos-768"][0].channels.as_deref(),
+            Some("16/JOC")
+        );
+        assert_eq!(
+            groups["audio-alac-stereo-96000-24"][0].other_attributes["STABLE-RENDITION-ID"],
+            "alac"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! a minimal HTTP server for testing requests without the network.
+
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// A request received by the `MockServer`.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    /// The path including the query string.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).unwrap()
+    }
+}
+
+/// A response sent by the `MockServer`.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+/// A server answering each connection with the next queued response, then closing it.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+}
+
+impl MockServer {
+    /// Starts a server answering with `responses`, in order.
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            for MockResponse {
+                status,
+                headers,
+                body,
+            } in responses
+            {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = BufReader::new(stream);
+                let mut request_line = String::new();
+                stream.read_line(&mut request_line).await.unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+                let mut request_headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    stream.read_line(&mut line).await.unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((key, value)) = line.split_once(':') {
+                        request_headers.push((key.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+                let content_length = request_headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, value)| value.parse::<usize>().ok())
+                    .unwrap_or(0);
+                let mut request_body = vec![0; content_length];
+                stream.read_exact(&mut request_body).await.unwrap();
+                recorded.lock().unwrap().push(MockRequest {
+                    method,
+                    path,
+                    headers: request_headers,
+                    body: request_body,
+                });
+
+                let mut response = format!(
+                    "HTTP/1.1 {status} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    body.len()
+                );
+                for (key, value) in headers {
+                    response.push_str(&format!("{key}: {value}\r\n"));
+                }
+                response.push_str("\r\n");
+                let stream = stream.get_mut();
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+                stream.shutdown().await.ok();
+            }
+        });
+        Self { url, requests }
+    }
+
+    /// Starts a server answering with JSON bodies.
+    pub async fn start_json(responses: Vec<(u16, serde_json::Value)>) -> Self {
+        Self::start(
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    let body = if body.is_null() {
+                        Vec::new()
+                    } else {
+                        body.to_string().into_bytes()
+                    };
+                    MockResponse {
+                        status,
+                        headers: vec![("Content-Type", "application/json".to_string())],
+                        body,
+                    }
+                })
+                .collect(),
+        )
+        .await
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
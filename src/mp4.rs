@@ -0,0 +1,356 @@
+//! the MP4 box reading and writing functions.
+
+use crate::error::{Error, Result};
+
+/// The boxes whose payload is a list of boxes.
+const CONTAINERS: [&[u8; 4]; 12] = [
+    b"moov", b"trak", b"mdia", b"minf", b"stbl", b"udta", b"edts", b"dinf", b"mvex", b"moof",
+    b"traf", b"sinf",
+];
+
+/// A box of an MP4 file.
+#[derive(Debug, Clone, Copy)]
+pub struct Mp4Box<'a> {
+    /// The type of the box, e.g. `moov`.
+    pub name: [u8; 4],
+    /// The offset of the box in the parsed buffer.
+    pub offset: usize,
+    /// The whole box, including its header.
+    pub data: &'a [u8],
+    /// The length of the header, 8 or 16 bytes.
+    pub header_len: usize,
+}
+
+impl<'a> Mp4Box<'a> {
+    /// Returns the payload of the box, without its header.
+    pub fn payload(&self) -> &'a [u8] {
+        &self.data[self.header_len..]
+    }
+
+    /// Returns the child boxes of a container box.
+    /// The 4-byte version and flags of full boxes such as `meta` are skipped.
+    pub fn children(&self) -> Result<Vec<Mp4Box<'a>>> {
+        let payload = self.payload();
+        let skip = if &self.name == b"meta" { 4 } else { 0 };
+        parse_boxes(payload.get(skip..).unwrap_or_default())
+    }
+
+    /// Returns the first child box of a type.
+    pub fn child(&self, name: &[u8; 4]) -> Result<Option<Mp4Box<'a>>> {
+        Ok(self.children()?.into_iter().find(|b| &b.name == name))
+    }
+}
+
+/// Parses a list of consecutive boxes.
+pub fn parse_boxes(data: &[u8]) -> Result<Vec<Mp4Box<'_>>> {
+    let mut boxes = Vec::new();
+    let mut offset = 0;
+    while offset + 8 <= data.len() {
+        let size = u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as u64;
+        let name: [u8; 4] = data[offset + 4..offset + 8].try_into().unwrap();
+        let (size, header_len) = match size {
+            0 => ((data.len() - offset) as u64, 8),
+            1 => {
+                let large_size = data
+                    .get(offset + 8..offset + 16)
+                    .ok_or_else(|| Error::Mp4("Truncated box header".to_string()))?;
+                (u64::from_be_bytes(large_size.try_into().unwrap()), 16)
+            }
+            size => (size, 8),
+        };
+        if size < header_len as u64 || offset as u64 + size > data.len() as u64 {
+            return Err(Error::Mp4(format!(
+                "Invalid size of box {}",
+                String::from_utf8_lossy(&name)
+            )));
+        }
+        let end = offset + size as usize;
+        boxes.push(Mp4Box {
+            name,
+            offset,
+            data: &data[offset..end],
+            header_len,
+        });
+        offset = end;
+    }
+    Ok(boxes)
+}
+
+/// Returns the first box found at a path of box types, e.g. `[b"moov", b"trak"]`.
+pub fn find_box<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Result<Option<Mp4Box<'a>>> {
+    let Some((first, rest)) = path.split_first() else {
+        return Ok(None);
+    };
+    let mut current = match parse_boxes(data)?.into_iter().find(|b| &b.name == *first) {
+        Some(current) => current,
+        None => return Ok(None),
+    };
+    for name in rest {
+        current = match current.child(name)? {
+            Some(child) => child,
+            None => return Ok(None),
+        };
+    }
+    Ok(Some(current))
+}
+
+/// Serializes a box with a 32-bit size.
+pub fn mp4_box(name: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 8);
+    data.extend(((payload.len() + 8) as u32).to_be_bytes());
+    data.extend(name);
+    data.extend(payload);
+    data
+}
+
+/// Serializes a full box, with a version and flags.
+pub fn mp4_full_box(name: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 4);
+    data.extend(((version as u32) << 24 | (flags & 0xffffff)).to_be_bytes());
+    data.extend(payload);
+    mp4_box(name, &data)
+}
+
+/// Adds `delta` to the chunk offsets (`stco` and `co64`) of all tracks in a `moov` box,
+/// e.g. after the `moov` box in front of the `mdat` box has grown.
+pub fn shift_chunk_offsets(moov: &mut [u8], delta: i64) -> Result<()> {
+    let boxes: Vec<(usize, usize, [u8; 4], usize)> = parse_boxes(moov)?
+        .iter()
+        .map(|b| (b.offset, b.data.len(), b.name, b.header_len))
+        .collect();
+    for (offset, len, name, header_len) in boxes {
+        let payload = &mut moov[offset + header_len..offset + len];
+        match &name {
+            name if CONTAINERS.contains(&name) => shift_chunk_offsets(payload, delta)?,
+            b"stco" | b"co64" => {
+                let entry_size = if &name == b"stco" { 4 } else { 8 };
+                let count = payload
+                    .get(4..8)
+                    .map(|count| u32::from_be_bytes(count.try_into().unwrap()) as usize)
+                    .ok_or_else(|| Error::Mp4("Truncated chunk offset box".to_string()))?;
+                if payload.len() < 8 + count * entry_size {
+                    return Err(Error::Mp4("Truncated chunk offset box".to_string()));
+                }
+                for entry in payload[8..8 + count * entry_size].chunks_exact_mut(entry_size) {
+                    if entry_size == 4 {
+                        let value = u32::from_be_bytes(entry.try_into().unwrap()) as i64 + delta;
+                        let value = u32::try_from(value)
+                            .map_err(|_| Error::Mp4("Chunk offset out of range".to_string()))?;
+                        entry.copy_from_slice(&value.to_be_bytes());
+                    } else {
+                        let value = u64::from_be_bytes(entry.try_into().unwrap()) as i64 + delta;
+                        entry.copy_from_slice(&(value as u64).to_be_bytes());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the `udta` box of the `moov` box of an MP4 file, keeping the other boxes.
+/// The chunk offsets are shifted when the `moov` box is in front of the media data.
+pub fn replace_udta(mp4: &[u8], udta: &[u8]) -> Result<Vec<u8>> {
+    let moov =
+        find_box(mp4, &[b"moov"])?.ok_or_else(|| Error::Mp4("moov box not found".to_string()))?;
+    let mut payload = Vec::with_capacity(moov.data.len() + udta.len());
+    for child in moov.children()? {
+        if &child.name != b"udta" {
+            payload.extend(child.data);
+        }
+    }
+    payload.extend(udta);
+    replace_moov(mp4, &payload)
+}
+
+/// Replaces the payload of the `moov` box of an MP4 file, keeping the other boxes.
+/// The chunk offsets of `payload` are shifted when the `moov` box is in front of the media data.
+pub fn replace_moov(mp4: &[u8], payload: &[u8]) -> Result<Vec<u8>> {
+    let boxes = parse_boxes(mp4)?;
+    let moov = boxes
+        .iter()
+        .find(|b| &b.name == b"moov")
+        .ok_or_else(|| Error::Mp4("moov box not found".to_string()))?;
+    let mut new_moov = mp4_box(b"moov", payload);
+    let delta = new_moov.len() as i64 - moov.data.len() as i64;
+    let media_after_moov = boxes
+        .iter()
+        .any(|b| &b.name == b"mdat" && b.offset > moov.offset);
+    if delta != 0 && media_after_moov {
+        shift_chunk_offsets(&mut new_moov[8..], delta)?;
+    }
+    let mut data = Vec::with_capacity(mp4.len() + new_moov.len() - moov.data.len());
+    data.extend(&mp4[..moov.offset]);
+    data.extend(new_moov);
+    data.extend(&mp4[moov.offset + moov.data.len()..]);
+    Ok(data)
+}
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    //! fragmented MP4 files shared by the tests of the remuxing and decryption modules.
+
+    use super::{mp4_box, mp4_full_box};
+
+    /// An init segment with an AAC audio track at 44.1 kHz, 1024 samples per frame, protected with
+    /// `scheme`. The `schi` box holds the `tenc` box, when given.
+    pub(crate) fn init_segment(scheme: &[u8; 4], tenc: Option<Vec<u8>>) -> Vec<u8> {
+        init_segment_entries(&[(scheme, tenc)])
+    }
+
+    /// An init segment like `init_segment`, with a protected sample entry per scheme and `tenc`.
+    pub(crate) fn init_segment_entries(entries: &[(&[u8; 4], Option<Vec<u8>>)]) -> Vec<u8> {
+        let mvhd = mp4_full_box(
+            b"mvhd",
+            0,
+            0,
+            &[&[0; 8][..], &1000u32.to_be_bytes(), &[0; 84]].concat(),
+        );
+        let tkhd = mp4_full_box(
+            b"tkhd",
+            0,
+            7,
+            &[&[0; 8][..], &1u32.to_be_bytes(), &[0; 68]].concat(),
+        );
+        let mdhd = mp4_full_box(
+            b"mdhd",
+            0,
+            0,
+            &[&[0; 8][..], &44100u32.to_be_bytes(), &[0; 8]].concat(),
+        );
+        let hdlr = mp4_full_box(b"hdlr", 0, 0, &[&[0; 4][..], b"soun", &[0; 13]].concat());
+        let mut audio_fields = vec![
+            0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 0,
+        ];
+        audio_fields.extend((44100u32 << 16).to_be_bytes());
+        let mut stsd = (entries.len() as u32).to_be_bytes().to_vec();
+        for (scheme, tenc) in entries {
+            let mut sinf = [
+                mp4_box(b"frma", b"mp4a"),
+                mp4_full_box(b"schm", 0, 0, &[&scheme[..], &[0, 1, 0, 0]].concat()),
+            ]
+            .concat();
+            if let Some(tenc) = tenc {
+                sinf.extend(mp4_box(b"schi", tenc));
+            }
+            stsd.extend(mp4_box(
+                b"enca",
+                &[
+                    audio_fields.clone(),
+                    mp4_full_box(b"esds", 0, 0, &[3; 10]),
+                    mp4_box(b"sinf", &sinf),
+                ]
+                .concat(),
+            ));
+        }
+        let stsd = mp4_full_box(b"stsd", 0, 0, &stsd);
+        let empty = 0u32.to_be_bytes();
+        let stbl = mp4_box(
+            b"stbl",
+            &[
+                stsd,
+                mp4_full_box(b"stts", 0, 0, &empty),
+                mp4_full_box(b"stsc", 0, 0, &empty),
+                mp4_full_box(b"stsz", 0, 0, &[0; 8]),
+                mp4_full_box(b"stco", 0, 0, &empty),
+            ]
+            .concat(),
+        );
+        let minf = mp4_box(
+            b"minf",
+            &[mp4_full_box(b"smhd", 0, 0, &[0; 4]), stbl].concat(),
+        );
+        let mdia = mp4_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = mp4_box(b"trak", &[tkhd, mdia].concat());
+        let trex = mp4_full_box(
+            b"trex",
+            0,
+            0,
+            &[1u32, 1, 1024, 0, 0].map(u32::to_be_bytes).concat(),
+        );
+        let moov = mp4_box(b"moov", &[mvhd, trak, mp4_box(b"mvex", &trex)].concat());
+        [mp4_box(b"ftyp", b"M4A \0\0\0\0isomiso2"), moov].concat()
+    }
+
+    /// A `moof`/`mdat` pair with the samples of track 1, and the `senc` box, when given.
+    pub(crate) fn fragment(sequence: u32, samples: &[&[u8]], senc: Option<Vec<u8>>) -> Vec<u8> {
+        fragment_of_entry(sequence, None, samples, senc)
+    }
+
+    /// A fragment like `fragment`, with the sample description index of its `tfhd` box, when given.
+    pub(crate) fn fragment_of_entry(
+        sequence: u32,
+        sample_description_index: Option<u32>,
+        samples: &[&[u8]],
+        senc: Option<Vec<u8>>,
+    ) -> Vec<u8> {
+        let moof = |data_offset: u32| {
+            let tfhd = match sample_description_index {
+                Some(index) => mp4_full_box(
+                    b"tfhd",
+                    0,
+                    0x20002,
+                    &[1u32.to_be_bytes(), index.to_be_bytes()].concat(),
+                ),
+                None => mp4_full_box(b"tfhd", 0, 0x20000, &1u32.to_be_bytes()),
+            };
+            let mut trun = (samples.len() as u32).to_be_bytes().to_vec();
+            trun.extend(data_offset.to_be_bytes());
+            samples
+                .iter()
+                .for_each(|s| trun.extend((s.len() as u32).to_be_bytes()));
+            let trun = mp4_full_box(b"trun", 0, 0x201, &trun);
+            let traf = [
+                tfhd,
+                mp4_full_box(b"tfdt", 0, 0, &[0; 4]),
+                trun,
+                senc.clone().unwrap_or_default(),
+            ]
+            .concat();
+            mp4_box(
+                b"moof",
+                &[
+                    mp4_full_box(b"mfhd", 0, 0, &sequence.to_be_bytes()),
+                    mp4_box(b"traf", &traf),
+                ]
+                .concat(),
+            )
+        };
+        let moof_len = moof(0).len() as u32;
+        [moof(moof_len + 8), mp4_box(b"mdat", &samples.concat())].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_box, mp4_box, mp4_full_box, replace_udta};
+
+    #[test]
+    fn test_replace_udta() {
+        let mut stco = 1u32.to_be_bytes().to_vec();
+        stco.extend(100u32.to_be_bytes());
+        let stbl = mp4_box(b"stbl", &mp4_full_box(b"stco", 0, 0, &stco));
+        let minf = mp4_box(b"minf", &stbl);
+        let mdia = mp4_box(b"mdia", &minf);
+        let trak = mp4_box(b"trak", &mdia);
+        let mut mp4 = mp4_box(b"ftyp", b"M4A \0\0\0\0");
+        mp4.extend(mp4_box(b"moov", &trak));
+        mp4.extend(mp4_box(b"mdat", b"audio"));
+
+        let udta = mp4_box(b"udta", &[0; 12]);
+        let tagged = replace_udta(&mp4, &udta).unwrap();
+        assert_eq!(tagged.len(), mp4.len() + udta.len());
+        assert!(find_box(&tagged, &[b"moov", b"udta"]).unwrap().is_some());
+        let stco = find_box(
+            &tagged,
+            &[b"moov", b"trak", b"mdia", b"minf", b"stbl", b"stco"],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            stco.payload()[8..12],
+            (100 + udta.len() as u32).to_be_bytes()
+        );
+    }
+}
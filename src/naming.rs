@@ -0,0 +1,120 @@
+//! the naming templates of output files.
+//!
+//! A template is a path where `{field}` is replaced with a tag value, e.g.
+//! `{album_artist}/{album}/{disc_number}-{track_number:02} {title}`.
+//! Numbers accept a zero-padded width, e.g. `{movement_number:02}`.
+//! Unknown fields are kept as is and missing values are replaced with an empty string.
+
+use crate::tags::Tags;
+
+/// Renders a naming template with the values of the tags.
+/// Characters that are not allowed in file names are replaced with `_` in the values;
+/// `/` in the template itself separates directories.
+pub fn render(template: &str, tags: &Tags) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..start + end];
+        let (field, width) = match placeholder.split_once(':') {
+            Some((field, width)) => (field, width.parse::<usize>().ok()),
+            None => (placeholder, None),
+        };
+        match field_value(field, tags) {
+            Some(Value::Text(text)) => result.push_str(&sanitize(&text)),
+            Some(Value::Number(number)) => {
+                result.push_str(&format!("{:0width$}", number, width = width.unwrap_or(0)))
+            }
+            Some(Value::Missing) => {}
+            None => result.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+enum Value {
+    Text(String),
+    Number(u32),
+    Missing,
+}
+
+fn field_value(field: &str, tags: &Tags) -> Option<Value> {
+    let text = |value: &Option<String>| value.clone().map_or(Value::Missing, Value::Text);
+    let number = |value: Option<u32>| value.map_or(Value::Missing, Value::Number);
+    Some(match field {
+        "title" => text(&tags.title),
+        "artist" => text(&tags.artist),
+        "album" => text(&tags.album),
+        "album_artist" => text(&tags.album_artist.clone().or_else(|| tags.artist.clone())),
+        "composer" => text(&tags.composer),
+        "genre" => text(&tags.genre),
+        "release_date" => text(&tags.release_date),
+        "year" => text(
+            &tags
+                .release_date
+                .as_ref()
+                .map(|date| date.chars().take(4).collect()),
+        ),
+        "isrc" => text(&tags.isrc),
+        "track_number" => number(tags.track_number),
+        "track_count" => number(tags.track_count),
+        "disc_number" => number(tags.disc_number),
+        "disc_count" => number(tags.disc_count),
+        "work" => text(&tags.work),
+        "movement_name" => text(&tags.movement_name),
+        "movement_number" => number(tags.movement_number),
+        "movement_count" => number(tags.movement_count),
+        _ => return None,
+    })
+}
+
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_end_matches(['.', ' '])
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::tags::Tags;
+
+    #[test]
+    fn test_render() {
+        let tags = Tags {
+            title: Some("Symphony No. 5: I. Allegro con brio".to_string()),
+            artist: Some("Berliner Philharmoniker".to_string()),
+            album: Some("Beethoven: Symphonies".to_string()),
+            composer: Some("Ludwig van Beethoven".to_string()),
+            release_date: Some("2016-05-13".to_string()),
+            track_number: Some(1),
+            disc_number: Some(2),
+            work: Some("Symphony No. 5 in C Minor, Op. 67".to_string()),
+            movement_name: Some("I. Allegro con brio".to_string()),
+            movement_number: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            render("{album_artist}/{album} ({year})/{disc_number}-{track_number:02} {title}", &tags),
+            "Berliner Philharmoniker/Beethoven_ Symphonies (2016)/2-01 Symphony No. 5_ I. Allegro con brio"
+        );
+        assert_eq!(
+            render("{composer}/{work}/{movement_number:02}. {movement_name}{genre}{unknown}", &tags),
+            "Ludwig van Beethoven/Symphony No. 5 in C Minor, Op. 67/01. I. Allegro con brio{unknown}"
+        );
+        assert_eq!(render("abc{def", &tags), "abc{def");
+        assert_eq!(render("{track_number} {title", &tags), "1 {title");
+    }
+}
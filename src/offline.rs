@@ -0,0 +1,328 @@
+//! the offline decryption of already downloaded encrypted streams.
+//!
+//! The encrypted segments of earlier downloads are decrypted with known content keys, e.g. from
+//! the key store of the downloader, without any license request nor other network access.
+
+use crate::error::{Error, Result};
+use crate::hls::{byte_range_offset, Segments};
+use crate::key_store::{parse_key_list, KeyStore};
+use crate::{cenc, remux};
+use m3u8_rs::Playlist;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// The content keys of an offline decryption.
+#[derive(Debug, Clone, Copy)]
+pub enum ContentKeys<'a> {
+    /// A single content key, used for all the protected tracks.
+    Key(&'a [u8]),
+    /// The content keys by key id, e.g. from `key_store::parse_key_list`.
+    Keys(&'a BTreeMap<Vec<u8>, Vec<u8>>),
+    /// A key store, e.g. `AppleMusicDownloader::key_store`.
+    Store(&'a dyn KeyStore),
+}
+
+/// Parses a content key argument: a hexadecimal key, or a `kid:key` list.
+pub fn parse_keys(value: &str) -> Result<BTreeMap<Vec<u8>, Vec<u8>>> {
+    if value.contains(':') {
+        return Ok(parse_key_list(value)?.into_iter().collect());
+    }
+    let key = hex::decode(value.trim()).map_err(|e| Error::Decrypt(e.to_string()))?;
+    // A single key has no key id; it applies to every track.
+    Ok(BTreeMap::from([(Vec::new(), key)]))
+}
+
+/// The extensions of the segment files of a directory without a playlist.
+const SEGMENT_EXTENSIONS: [&str; 3] = ["mp4", "m4s", "m4a"];
+
+/// Reads the encrypted segments of a local stream.
+///
+/// The path is either a fragmented MP4 file, or a directory. The segments of a directory are
+/// those of its media playlist (`.m3u8`), with the segment URIs relative to the directory;
+/// without a playlist, they are the `.mp4`, `.m4s` and `.m4a` files of the directory, ordered by
+/// the numbers in their names (`segment2` before `segment10`), the initialization segment being
+/// the file with a `moov` box.
+pub fn read_segments(path: &Path) -> Result<Segments> {
+    if !path.is_dir() {
+        return Ok(Segments {
+            init: None,
+            segments: vec![std::fs::read(path)?],
+        });
+    }
+    let files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|file| file.is_file())
+        .collect();
+    if let Some(playlist) = files
+        .iter()
+        .filter(|file| file.extension().is_some_and(|ext| ext == "m3u8"))
+        .min()
+    {
+        return read_playlist_segments(path, playlist);
+    }
+
+    let mut files: Vec<&PathBuf> = files
+        .iter()
+        .filter(|file| {
+            file.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| SEGMENT_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .collect();
+    files.sort_by_cached_key(|file| {
+        natural_key(&file.file_name().unwrap_or_default().to_string_lossy())
+    });
+    let mut segments = Segments::default();
+    for file in files {
+        let data = std::fs::read(file)?;
+        if segments.init.is_none() && crate::mp4::find_box(&data, &[b"moov"])?.is_some() {
+            segments.init = Some(data);
+        } else {
+            segments.segments.push(data);
+        }
+    }
+    if segments.segments.is_empty() && segments.init.is_none() {
+        return Err(Error::Other(format!(
+            "No segment found in {}",
+            path.display()
+        )));
+    }
+    Ok(segments)
+}
+
+/// A part of a file name: a run of digits, compared by value, or of other characters.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NamePart {
+    Number(u64),
+    Text(String),
+}
+
+/// Returns the sort key of a file name, ordering the numbers in the name by value.
+fn natural_key(name: &str) -> Vec<NamePart> {
+    let mut parts = Vec::new();
+    let mut rest = name;
+    while let Some(first) = rest.chars().next() {
+        let is_digit = first.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != is_digit)
+            .unwrap_or(rest.len());
+        let (part, remaining) = rest.split_at(end);
+        parts.push(match part.parse() {
+            Ok(number) if is_digit => NamePart::Number(number),
+            _ => NamePart::Text(part.to_string()),
+        });
+        rest = remaining;
+    }
+    parts
+}
+
+/// Reads the segments of a local media playlist.
+fn read_playlist_segments(dir: &Path, playlist: &Path) -> Result<Segments> {
+    let media = match m3u8_rs::parse_playlist_res(&std::fs::read(playlist)?) {
+        Ok(Playlist::MediaPlaylist(media)) => media,
+        Ok(Playlist::MasterPlaylist(_)) => {
+            return Err(Error::Other(format!(
+                "{} is not a media playlist",
+                playlist.display()
+            )))
+        }
+        Err(e) => return Err(Error::Other(e.to_string())),
+    };
+    // The segments of a single-file playlist are byte ranges of the same file, read once.
+    let mut files = HashMap::new();
+    let mut segments = Segments::default();
+    let mut last_range: Option<(String, u64)> = None;
+    for segment in &media.segments {
+        if segments.init.is_none() {
+            if let Some(map) = &segment.map {
+                let range = map
+                    .byte_range
+                    .as_ref()
+                    .map(|range| (range.offset.unwrap_or(0), range.length));
+                segments.init = Some(read_file(&mut files, dir, &map.uri, range)?);
+            }
+        }
+        let range = segment.byte_range.as_ref().map(|range| {
+            let offset = byte_range_offset(range, &segment.uri, &last_range);
+            (offset, range.length)
+        });
+        if let Some((offset, length)) = range {
+            last_range = Some((segment.uri.clone(), offset + length));
+        }
+        segments
+            .segments
+            .push(read_file(&mut files, dir, &segment.uri, range)?);
+    }
+    Ok(segments)
+}
+
+/// Reads a segment file, or a byte range of it. Only the file name of the URI is used, as the
+/// segments are stored next to the playlist. The files are kept in `files` by name.
+fn read_file<'a>(
+    files: &mut HashMap<&'a str, Vec<u8>>,
+    dir: &Path,
+    uri: &'a str,
+    range: Option<(u64, u64)>,
+) -> Result<Vec<u8>> {
+    let uri = uri.split(['?', '#']).next().unwrap_or(uri);
+    let name = uri.rsplit('/').next().unwrap_or(uri);
+    let data = match files.entry(name) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(std::fs::read(dir.join(name))?),
+    };
+    match range {
+        Some((offset, length)) => data
+            .get(offset as usize..(offset + length) as usize)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| Error::Other(format!("Byte range {offset}@{length} out of {name}"))),
+        None => Ok(data.clone()),
+    }
+}
+
+/// Decrypts the segments of a stream into a progressive MP4 file.
+pub fn decrypt_segments(segments: &Segments, keys: ContentKeys) -> Result<Vec<u8>> {
+    let data = segments.concat();
+    let decrypted = match keys {
+        ContentKeys::Key(key) => cenc::decrypt(&data, key)?,
+        ContentKeys::Keys(keys) => match keys.get(&Vec::new()) {
+            Some(key) => cenc::decrypt(&data, key)?,
+            None => cenc::decrypt_with_keys(&data, keys)?,
+        },
+        ContentKeys::Store(store) => {
+            let mut keys = BTreeMap::new();
+            for encryption in cenc::track_encryptions(&data)? {
+                if let Some(key) = store.get(&encryption.kid)? {
+                    keys.insert(encryption.kid.to_vec(), key);
+                }
+            }
+            cenc::decrypt_with_keys(&data, &keys)?
+        }
+    };
+    remux::remux(&decrypted)
+}
+
+/// Decrypts a local stream, see `read_segments`, into a progressive MP4 file at `output`.
+pub fn decrypt_path(input: &Path, output: &Path, keys: ContentKeys) -> Result<()> {
+    let decrypted = decrypt_segments(&read_segments(input)?, keys)?;
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(output, decrypted)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decrypt_path, parse_keys, read_segments, ContentKeys};
+    use crate::key_store::{KeyStore, MemoryKeyStore};
+    use crate::mp4::{find_box, fixtures, mp4_full_box};
+
+    const KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const KID: &str = "07070707070707070707070707070707";
+    // The AES-128-CTR test vectors of NIST SP 800-38A, F.5.1.
+    const IV: &str = "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff";
+    const CIPHERTEXT: &str = "874d6191b620e3261bef6864990db6ce";
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172a";
+
+    /// An init segment with an audio track encrypted with the `cenc` scheme.
+    fn init_segment() -> Vec<u8> {
+        let tenc = [&[0, 0, 1, 16][..], &hex::decode(KID).unwrap()].concat();
+        fixtures::init_segment(b"cenc", Some(mp4_full_box(b"tenc", 0, 0, &tenc)))
+    }
+
+    /// A media segment with one encrypted sample.
+    fn fragment(sequence: u32) -> Vec<u8> {
+        let senc = [&1u32.to_be_bytes()[..], &hex::decode(IV).unwrap()].concat();
+        fixtures::fragment(
+            sequence,
+            &[&hex::decode(CIPHERTEXT).unwrap()],
+            Some(mp4_full_box(b"senc", 0, 0, &senc)),
+        )
+    }
+
+    /// Returns the media data of a decrypted file.
+    fn mdat(path: &std::path::Path) -> Vec<u8> {
+        let output = std::fs::read(path).unwrap();
+        find_box(&output, &[b"mdat"])
+            .unwrap()
+            .unwrap()
+            .payload()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_decrypt_path() {
+        let dir = std::env::temp_dir().join(format!("ramdl-offline-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let plaintext = hex::decode(PLAINTEXT).unwrap();
+        let expected = [&plaintext[..], &plaintext].concat();
+
+        // The segments of a directory without a playlist, with a single key.
+        let segments_dir = dir.join("segments");
+        std::fs::create_dir_all(&segments_dir).unwrap();
+        std::fs::write(segments_dir.join("init.mp4"), init_segment()).unwrap();
+        for sequence in 1..=11 {
+            let name = format!("segment{sequence}.m4s");
+            std::fs::write(segments_dir.join(name), fragment(sequence)).unwrap();
+        }
+        std::fs::write(segments_dir.join("notes.txt"), b"not a segment").unwrap();
+        let segments = read_segments(&segments_dir).unwrap();
+        assert_eq!(segments.init, Some(init_segment()));
+        let sequences: Vec<Vec<u8>> = (1..=11).map(fragment).collect();
+        assert_eq!(segments.segments, sequences);
+        let key = hex::decode(KEY).unwrap();
+        let output = dir.join("out").join("song.m4a");
+        decrypt_path(&segments_dir, &output, ContentKeys::Key(&key)).unwrap();
+        assert_eq!(mdat(&output), plaintext.repeat(11));
+
+        // The byte ranges of a media playlist, with a kid:key list.
+        let playlist_dir = dir.join("playlist");
+        std::fs::create_dir_all(&playlist_dir).unwrap();
+        let init = init_segment();
+        let (first, second) = (fragment(1), fragment(2));
+        std::fs::write(
+            playlist_dir.join("stream.mp4"),
+            [&init[..], &first, &second].concat(),
+        )
+        .unwrap();
+        let playlist = format!(
+            "#EXTM3U\n\
+            #EXT-X-TARGETDURATION:6\n\
+            #EXT-X-MAP:URI=\"stream.mp4\",BYTERANGE=\"{}@0\"\n\
+            #EXTINF:6.0,\n\
+            #EXT-X-BYTERANGE:{}@{}\n\
+            stream.mp4\n\
+            #EXTINF:6.0,\n\
+            #EXT-X-BYTERANGE:{}\n\
+            stream.mp4\n\
+            #EXT-X-ENDLIST\n",
+            init.len(),
+            first.len(),
+            init.len(),
+            second.len()
+        );
+        std::fs::write(playlist_dir.join("stream.m3u8"), playlist).unwrap();
+        let segments = read_segments(&playlist_dir).unwrap();
+        assert_eq!(segments.init, Some(init));
+        assert_eq!(segments.segments, [first, second]);
+        let keys = parse_keys(&format!("{KID}:{KEY}")).unwrap();
+        decrypt_path(&playlist_dir, &output, ContentKeys::Keys(&keys)).unwrap();
+        assert_eq!(mdat(&output), expected);
+
+        // A single fragmented MP4 file, with the keys of a key store.
+        let file = playlist_dir.join("stream.mp4");
+        let store = MemoryKeyStore::new();
+        assert!(decrypt_path(&file, &output, ContentKeys::Store(&store)).is_err());
+        store.import(&format!("{KID}:{KEY}")).unwrap();
+        decrypt_path(&file, &output, ContentKeys::Store(&store)).unwrap();
+        assert_eq!(mdat(&output), expected);
+        let keys = parse_keys(KEY).unwrap();
+        decrypt_path(&file, &output, ContentKeys::Keys(&keys)).unwrap();
+        assert_eq!(mdat(&output), expected);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1039 @@
+//! the remuxing of fragmented MP4 files into progressive MP4 files.
+//!
+//! A fragmented MP4 file, as served by HLS, is an initialization segment (`ftyp` and a `moov` box
+//! without samples) followed by `moof`/`mdat` pairs. The remuxer collects the samples of every
+//! fragment and writes a single `moov` box with complete sample tables in front of one `mdat` box.
+
+use crate::error::{Error, Result};
+use crate::hls::Segments;
+use crate::mp4::{find_box, mp4_box, mp4_full_box, parse_boxes, Mp4Box};
+
+/// `tfhd` flags.
+const TFHD_BASE_DATA_OFFSET: u32 = 0x1;
+const TFHD_SAMPLE_DESCRIPTION_INDEX: u32 = 0x2;
+const TFHD_DEFAULT_SAMPLE_DURATION: u32 = 0x8;
+const TFHD_DEFAULT_SAMPLE_SIZE: u32 = 0x10;
+const TFHD_DEFAULT_SAMPLE_FLAGS: u32 = 0x20;
+const TFHD_DEFAULT_BASE_IS_MOOF: u32 = 0x20000;
+/// `trun` flags.
+const TRUN_DATA_OFFSET: u32 = 0x1;
+const TRUN_FIRST_SAMPLE_FLAGS: u32 = 0x4;
+const TRUN_SAMPLE_DURATION: u32 = 0x100;
+const TRUN_SAMPLE_SIZE: u32 = 0x200;
+const TRUN_SAMPLE_FLAGS: u32 = 0x400;
+const TRUN_SAMPLE_COMPOSITION_TIME_OFFSET: u32 = 0x800;
+/// The `sample_is_non_sync_sample` bit of the sample flags.
+const SAMPLE_IS_NON_SYNC: u32 = 0x10000;
+
+/// Remuxes the segments of an HLS media playlist, e.g. from `hls::download_stream`,
+/// into a progressive MP4 file.
+pub fn remux_segments(segments: &Segments) -> Result<Vec<u8>> {
+    remux(&segments.concat())
+}
+
+/// Remuxes the segments of a stream and of its separate audio rendition, e.g. from
+/// `hls::download_stream_renditions`, into a progressive MP4 file with the tracks of both.
+/// The tracks of the audio rendition are numbered after those of the stream.
+pub fn remux_renditions(segments: &Segments, audio: &Segments) -> Result<Vec<u8>> {
+    let data = segments.concat();
+    let audio_data = audio.concat();
+    let boxes = parse_boxes(&data)?;
+    let audio_boxes = parse_boxes(&audio_data)?;
+    let moov = find_moov(&boxes)?;
+    let audio_moov = find_moov(&audio_boxes)?;
+
+    let first_audio_id = read_tracks(&moov)?
+        .iter()
+        .map(|track| track.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let audio_ids: Vec<u32> = read_tracks(&audio_moov)?
+        .iter()
+        .map(|track| track.id)
+        .collect();
+    let renumber = |id: u32| -> Result<u32> {
+        audio_ids
+            .iter()
+            .position(|&audio_id| audio_id == id)
+            .map(|index| first_audio_id + index as u32)
+            .ok_or_else(|| Error::Mp4(format!("Track {id} not found")))
+    };
+    let next_track_id = first_audio_id + audio_ids.len() as u32;
+
+    let mut trex_boxes = Vec::new();
+    let mut payload = Vec::with_capacity(moov.data.len() + audio_moov.data.len());
+    for child in moov.children()? {
+        match &child.name {
+            b"mvhd" => {
+                let mut mvhd = child.payload().to_vec();
+                let len = mvhd.len();
+                mvhd.get_mut(len.saturating_sub(4)..)
+                    .filter(|id| id.len() == 4)
+                    .ok_or_else(|| Error::Mp4("Truncated mvhd box".to_string()))?
+                    .copy_from_slice(&next_track_id.to_be_bytes());
+                payload.extend(mp4_box(b"mvhd", &mvhd));
+            }
+            b"mvex" => trex_boxes.extend(child.payload()),
+            _ => payload.extend(child.data),
+        }
+    }
+    for child in audio_moov.children()? {
+        match &child.name {
+            b"trak" => {
+                let mut trak = Vec::with_capacity(child.data.len());
+                for trak_child in child.children()? {
+                    if &trak_child.name == b"tkhd" {
+                        let mut tkhd = trak_child.payload().to_vec();
+                        let offset = if tkhd.first() == Some(&1) { 20 } else { 12 };
+                        let id = renumber(read_u32(&tkhd, offset)?)?;
+                        tkhd[offset..offset + 4].copy_from_slice(&id.to_be_bytes());
+                        trak.extend(mp4_box(b"tkhd", &tkhd));
+                    } else {
+                        trak.extend(trak_child.data);
+                    }
+                }
+                payload.extend(mp4_box(b"trak", &trak));
+            }
+            b"mvex" => {
+                for trex in child.children()? {
+                    let mut trex_payload = trex.payload().to_vec();
+                    if &trex.name == b"trex" {
+                        let id = renumber(read_u32(&trex_payload, 4)?)?;
+                        trex_payload[4..8].copy_from_slice(&id.to_be_bytes());
+                    }
+                    trex_boxes.extend(mp4_box(&trex.name, &trex_payload));
+                }
+            }
+            _ => {}
+        }
+    }
+    if !trex_boxes.is_empty() {
+        payload.extend(mp4_box(b"mvex", &trex_boxes));
+    }
+
+    // The boxes keep their size, so that the data offsets of the fragments stay valid.
+    let mut output = Vec::with_capacity(data.len() + audio_data.len());
+    for b in &boxes {
+        match &b.name {
+            b"moov" => output.extend(mp4_box(b"moov", &payload)),
+            _ => output.extend(b.data),
+        }
+    }
+    for b in &audio_boxes {
+        match &b.name {
+            b"moof" => {
+                let mut moof = Vec::with_capacity(b.data.len());
+                for child in b.children()? {
+                    if &child.name != b"traf" {
+                        moof.extend(child.data);
+                        continue;
+                    }
+                    let mut traf = Vec::with_capacity(child.data.len());
+                    for traf_child in child.children()? {
+                        if &traf_child.name == b"tfhd" {
+                            let mut tfhd = traf_child.payload().to_vec();
+                            let id = renumber(read_u32(&tfhd, 4)?)?;
+                            tfhd[4..8].copy_from_slice(&id.to_be_bytes());
+                            traf.extend(mp4_box(b"tfhd", &tfhd));
+                        } else {
+                            traf.extend(traf_child.data);
+                        }
+                    }
+                    moof.extend(mp4_box(b"traf", &traf));
+                }
+                output.extend(mp4_box(b"moof", &moof));
+            }
+            b"mdat" => output.extend(b.data),
+            _ => {}
+        }
+    }
+    remux(&output)
+}
+
+fn find_moov<'a>(boxes: &[Mp4Box<'a>]) -> Result<Mp4Box<'a>> {
+    boxes
+        .iter()
+        .find(|b| &b.name == b"moov")
+        .copied()
+        .ok_or_else(|| Error::Mp4("moov box not found".to_string()))
+}
+
+/// Remuxes a fragmented MP4 file into a progressive MP4 file with a single `moov` box
+/// in front of the media data. Files without fragments are rewritten the same way.
+pub fn remux(data: &[u8]) -> Result<Vec<u8>> {
+    let boxes = parse_boxes(data)?;
+    let moov = boxes
+        .iter()
+        .find(|b| &b.name == b"moov")
+        .ok_or_else(|| Error::Mp4("moov box not found".to_string()))?;
+    let mvhd = moov
+        .child(b"mvhd")?
+        .ok_or_else(|| Error::Mp4("mvhd box not found".to_string()))?;
+    let movie_timescale = read_u32(mvhd.payload(), timescale_offset(mvhd.payload()))?;
+
+    let mut tracks = read_tracks(moov)?;
+    for moof in boxes.iter().filter(|b| &b.name == b"moof") {
+        read_fragment(data, moof, &mut tracks)?;
+    }
+
+    // The media data is laid out track by track, one chunk per run of samples.
+    let mdat_len: u64 = tracks
+        .iter()
+        .flat_map(|track| &track.chunks)
+        .flat_map(|chunk| &chunk.samples)
+        .map(|sample| sample.size as u64)
+        .sum();
+    let mdat_header_len = if mdat_len + 8 > u32::MAX as u64 {
+        16
+    } else {
+        8
+    };
+    let ftyp = boxes
+        .iter()
+        .find(|b| &b.name == b"ftyp")
+        .map(|b| b.data.to_vec())
+        .unwrap_or_else(|| mp4_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41"));
+
+    let moov_len = build_moov(moov, &tracks, movie_timescale, 0, false)?.len() as u64;
+    let end = ftyp.len() as u64 + moov_len + mdat_header_len + mdat_len;
+    let use_co64 = end > u32::MAX as u64;
+    let moov_len = if use_co64 {
+        build_moov(moov, &tracks, movie_timescale, 0, true)?.len() as u64
+    } else {
+        moov_len
+    };
+    let base_offset = ftyp.len() as u64 + moov_len + mdat_header_len;
+    let new_moov = build_moov(moov, &tracks, movie_timescale, base_offset, use_co64)?;
+
+    let mut output = Vec::with_capacity((base_offset + mdat_len) as usize);
+    output.extend(ftyp);
+    output.extend(new_moov);
+    if mdat_header_len == 16 {
+        output.extend(1u32.to_be_bytes());
+        output.extend(b"mdat");
+        output.extend((mdat_len + 16).to_be_bytes());
+    } else {
+        output.extend(((mdat_len + 8) as u32).to_be_bytes());
+        output.extend(b"mdat");
+    }
+    for sample in tracks
+        .iter()
+        .flat_map(|track| &track.chunks)
+        .flat_map(|chunk| &chunk.samples)
+    {
+        let sample = data
+            .get(sample.offset..sample.offset + sample.size as usize)
+            .ok_or_else(|| Error::Mp4("Sample out of the file".to_string()))?;
+        output.extend(sample);
+    }
+    Ok(output)
+}
+
+/// Returns the track fragments (`traf` boxes) of a fragmented MP4 file, in file order.
+pub(crate) fn track_fragments(data: &[u8]) -> Result<Vec<TrackFragment<'_>>> {
+    let boxes = parse_boxes(data)?;
+    let moov = boxes
+        .iter()
+        .find(|b| &b.name == b"moov")
+        .ok_or_else(|| Error::Mp4("moov box not found".to_string()))?;
+    let mut tracks = read_tracks(moov)?;
+    let mut fragments = Vec::new();
+    for moof in boxes.iter().filter(|b| &b.name == b"moof") {
+        fragments.extend(read_fragment(data, moof, &mut tracks)?);
+    }
+    Ok(fragments)
+}
+
+/// The samples of a track fragment.
+pub(crate) struct TrackFragment<'a> {
+    pub track_id: u32,
+    /// The 1-based index of the sample entry of the samples.
+    pub sample_description_index: u32,
+    pub traf: Mp4Box<'a>,
+    /// The offset and size of each sample in the file.
+    pub samples: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SampleDefaults {
+    sample_description_index: u32,
+    duration: u32,
+    size: u32,
+    flags: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// The offset of the sample in the input.
+    offset: usize,
+    size: u32,
+    duration: u32,
+    composition_offset: i64,
+    is_sync: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    sample_description_index: u32,
+    samples: Vec<Sample>,
+}
+
+struct Track<'a> {
+    id: u32,
+    trak: Mp4Box<'a>,
+    defaults: SampleDefaults,
+    chunks: Vec<Chunk>,
+}
+
+impl<'a> Track<'a> {
+    fn new(trak: Mp4Box<'a>, trex_boxes: &[Mp4Box]) -> Result<Self> {
+        let tkhd = trak
+            .child(b"tkhd")?
+            .ok_or_else(|| Error::Mp4("tkhd box not found".to_string()))?;
+        let tkhd = tkhd.payload();
+        let id = read_u32(tkhd, if tkhd.first() == Some(&1) { 20 } else { 12 })?;
+        let mut defaults = SampleDefaults {
+            sample_description_index: 1,
+            duration: 0,
+            size: 0,
+            flags: 0,
+        };
+        for trex in trex_boxes {
+            let trex = trex.payload();
+            if read_u32(trex, 4)? == id {
+                defaults = SampleDefaults {
+                    sample_description_index: read_u32(trex, 8)?,
+                    duration: read_u32(trex, 12)?,
+                    size: read_u32(trex, 16)?,
+                    flags: read_u32(trex, 20)?,
+                };
+            }
+        }
+        // Samples already in the sample tables of a non-fragmented file.
+        let chunks = read_sample_tables(&trak)?;
+        Ok(Self {
+            id,
+            trak,
+            defaults,
+            chunks,
+        })
+    }
+
+    fn media_duration(&self) -> u64 {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| &chunk.samples)
+            .map(|sample| sample.duration as u64)
+            .sum()
+    }
+}
+
+/// Reads the tracks of a `moov` box, with the defaults of their fragments.
+fn read_tracks<'a>(moov: &Mp4Box<'a>) -> Result<Vec<Track<'a>>> {
+    let trex_boxes: Vec<Mp4Box> = match moov.child(b"mvex")? {
+        Some(mvex) => mvex
+            .children()?
+            .into_iter()
+            .filter(|b| &b.name == b"trex")
+            .collect(),
+        None => Vec::new(),
+    };
+    moov.children()?
+        .into_iter()
+        .filter(|b| &b.name == b"trak")
+        .map(|trak| Track::new(trak, &trex_boxes))
+        .collect()
+}
+
+/// Reads the samples of a `moof` box into the chunks of their tracks.
+/// Returns the samples of each track fragment.
+fn read_fragment<'a>(
+    data: &[u8],
+    moof: &Mp4Box<'a>,
+    tracks: &mut [Track],
+) -> Result<Vec<TrackFragment<'a>>> {
+    let mut fragments = Vec::new();
+    let mut previous_end: Option<u64> = None;
+    for traf in moof.children()?.into_iter().filter(|b| &b.name == b"traf") {
+        let tfhd = traf
+            .child(b"tfhd")?
+            .ok_or_else(|| Error::Mp4("tfhd box not found".to_string()))?;
+        let tfhd = tfhd.payload();
+        let tfhd_flags = read_u32(tfhd, 0)? & 0xffffff;
+        let track_id = read_u32(tfhd, 4)?;
+        let track = tracks
+            .iter_mut()
+            .find(|track| track.id == track_id)
+            .ok_or_else(|| Error::Mp4(format!("Track {track_id} not found")))?;
+        let mut position = 8;
+        let mut defaults = track.defaults;
+        let mut base_data_offset = None;
+        if tfhd_flags & TFHD_BASE_DATA_OFFSET != 0 {
+            base_data_offset = Some(read_u64(tfhd, position)?);
+            position += 8;
+        }
+        if tfhd_flags & TFHD_SAMPLE_DESCRIPTION_INDEX != 0 {
+            defaults.sample_description_index = read_u32(tfhd, position)?;
+            position += 4;
+        }
+        if tfhd_flags & TFHD_DEFAULT_SAMPLE_DURATION != 0 {
+            defaults.duration = read_u32(tfhd, position)?;
+            position += 4;
+        }
+        if tfhd_flags & TFHD_DEFAULT_SAMPLE_SIZE != 0 {
+            defaults.size = read_u32(tfhd, position)?;
+            position += 4;
+        }
+        if tfhd_flags & TFHD_DEFAULT_SAMPLE_FLAGS != 0 {
+            defaults.flags = read_u32(tfhd, position)?;
+        }
+        // Without an explicit base, the first track fragment starts at the moof box and
+        // the next ones continue after the data of the previous track fragment.
+        let base = match base_data_offset {
+            Some(base) => base,
+            None if tfhd_flags & TFHD_DEFAULT_BASE_IS_MOOF != 0 => moof.offset as u64,
+            None => previous_end.unwrap_or(moof.offset as u64),
+        };
+        let mut next_offset = base;
+        let mut fragment_samples = Vec::new();
+
+        for trun in traf.children()?.into_iter().filter(|b| &b.name == b"trun") {
+            let trun = trun.payload();
+            let version_flags = read_u32(trun, 0)?;
+            let version = version_flags >> 24;
+            let flags = version_flags & 0xffffff;
+            let sample_count = read_u32(trun, 4)?;
+            let mut position = 8;
+            let mut offset = next_offset;
+            if flags & TRUN_DATA_OFFSET != 0 {
+                let data_offset = read_u32(trun, position)? as i32;
+                offset = (base as i64 + data_offset as i64) as u64;
+                position += 4;
+            }
+            let mut first_sample_flags = None;
+            if flags & TRUN_FIRST_SAMPLE_FLAGS != 0 {
+                first_sample_flags = Some(read_u32(trun, position)?);
+                position += 4;
+            }
+            let mut samples = Vec::with_capacity(sample_count as usize);
+            for index in 0..sample_count {
+                let mut field = |present: bool, default: u32| -> Result<u32> {
+                    if !present {
+                        return Ok(default);
+                    }
+                    let value = read_u32(trun, position)?;
+                    position += 4;
+                    Ok(value)
+                };
+                let duration = field(flags & TRUN_SAMPLE_DURATION != 0, defaults.duration)?;
+                let size = field(flags & TRUN_SAMPLE_SIZE != 0, defaults.size)?;
+                let sample_flags = field(flags & TRUN_SAMPLE_FLAGS != 0, defaults.flags)?;
+                let composition_offset =
+                    field(flags & TRUN_SAMPLE_COMPOSITION_TIME_OFFSET != 0, 0)?;
+                let sample_flags = match first_sample_flags {
+                    Some(first_sample_flags) if index == 0 => first_sample_flags,
+                    _ => sample_flags,
+                };
+                if offset + size as u64 > data.len() as u64 {
+                    return Err(Error::Mp4("Sample out of the file".to_string()));
+                }
+                samples.push(Sample {
+                    offset: offset as usize,
+                    size,
+                    duration,
+                    composition_offset: if version == 0 {
+                        composition_offset as i64
+                    } else {
+                        composition_offset as i32 as i64
+                    },
+                    is_sync: sample_flags & SAMPLE_IS_NON_SYNC == 0,
+                });
+                offset += size as u64;
+            }
+            next_offset = offset;
+            fragment_samples.extend(samples.iter().map(|s| (s.offset, s.size as usize)));
+            if !samples.is_empty() {
+                track.chunks.push(Chunk {
+                    sample_description_index: defaults.sample_description_index,
+                    samples,
+                });
+            }
+        }
+        previous_end = Some(next_offset);
+        fragments.push(TrackFragment {
+            track_id,
+            sample_description_index: defaults.sample_description_index,
+            traf,
+            samples: fragment_samples,
+        });
+    }
+    Ok(fragments)
+}
+
+/// Returns the samples of the first track of a progressive MP4 file, e.g. from `remux`,
+/// in decoding order.
+pub fn track_samples(data: &[u8]) -> Result<Vec<&[u8]>> {
+    let trak = find_box(data, &[b"moov", b"trak"])?
+        .ok_or_else(|| Error::Mp4("trak box not found".to_string()))?;
+    read_sample_tables(&trak)?
+        .iter()
+        .flat_map(|chunk| &chunk.samples)
+        .map(|sample| {
+            data.get(sample.offset..sample.offset + sample.size as usize)
+                .ok_or_else(|| Error::Mp4("Sample out of the file".to_string()))
+        })
+        .collect()
+}
+
+/// Reads the samples described by the sample tables of a track, in chunks.
+fn read_sample_tables(trak: &Mp4Box) -> Result<Vec<Chunk>> {
+    let Some(stbl) = find_box(trak.payload(), &[b"mdia", b"minf", b"stbl"])? else {
+        return Ok(Vec::new());
+    };
+    let table =
+        |name: &[u8; 4]| -> Result<Option<&[u8]>> { Ok(stbl.child(name)?.map(|b| b.payload())) };
+    let (Some(stsz), Some(stsc), Some(stts)) = (table(b"stsz")?, table(b"stsc")?, table(b"stts")?)
+    else {
+        return Ok(Vec::new());
+    };
+    let chunk_offsets: Vec<u64> = match (table(b"stco")?, table(b"co64")?) {
+        (Some(stco), _) => (0..read_u32(stco, 4)? as usize)
+            .map(|i| read_u32(stco, 8 + i * 4).map(|o| o as u64))
+            .collect::<Result<_>>()?,
+        (None, Some(co64)) => (0..read_u32(co64, 4)? as usize)
+            .map(|i| read_u64(co64, 8 + i * 8))
+            .collect::<Result<_>>()?,
+        (None, None) => return Ok(Vec::new()),
+    };
+    let sample_count = read_u32(stsz, 8)? as usize;
+    let uniform_size = read_u32(stsz, 4)?;
+    let sizes: Vec<u32> = (0..sample_count)
+        .map(|i| match uniform_size {
+            0 => read_u32(stsz, 12 + i * 4),
+            size => Ok(size),
+        })
+        .collect::<Result<_>>()?;
+    let mut durations = Vec::with_capacity(sample_count);
+    for i in 0..read_u32(stts, 4)? as usize {
+        let count = read_u32(stts, 8 + i * 8)?;
+        let delta = read_u32(stts, 12 + i * 8)?;
+        durations.extend(std::iter::repeat_n(delta, count as usize));
+    }
+    let mut composition_offsets = vec![0i64; sample_count];
+    if let Some(ctts) = table(b"ctts")? {
+        let mut index = 0;
+        for i in 0..read_u32(ctts, 4)? as usize {
+            let count = read_u32(ctts, 8 + i * 8)?;
+            let offset = read_u32(ctts, 12 + i * 8)?;
+            for _ in 0..count {
+                if let Some(value) = composition_offsets.get_mut(index) {
+                    *value = if ctts[0] == 0 {
+                        offset as i64
+                    } else {
+                        offset as i32 as i64
+                    };
+                }
+                index += 1;
+            }
+        }
+    }
+    let sync_samples: Option<Vec<u32>> = table(b"stss")?
+        .map(|stss| {
+            (0..read_u32(stss, 4)? as usize)
+                .map(|i| read_u32(stss, 8 + i * 4))
+                .collect::<Result<_>>()
+        })
+        .transpose()?;
+
+    let stsc_entries: Vec<(u32, u32, u32)> = (0..read_u32(stsc, 4)? as usize)
+        .map(|i| {
+            Ok((
+                read_u32(stsc, 8 + i * 12)?,
+                read_u32(stsc, 12 + i * 12)?,
+                read_u32(stsc, 16 + i * 12)?,
+            ))
+        })
+        .collect::<Result<_>>()?;
+    let mut chunks = Vec::with_capacity(chunk_offsets.len());
+    let mut sample_index = 0;
+    for (chunk_index, chunk_offset) in chunk_offsets.iter().enumerate() {
+        let chunk_number = chunk_index as u32 + 1;
+        let Some(&(_, samples_per_chunk, sample_description_index)) = stsc_entries
+            .iter()
+            .rev()
+            .find(|(first_chunk, _, _)| *first_chunk <= chunk_number)
+        else {
+            continue;
+        };
+        let mut offset = *chunk_offset as usize;
+        let mut samples = Vec::with_capacity(samples_per_chunk as usize);
+        for _ in 0..samples_per_chunk {
+            let (Some(&size), Some(&duration)) =
+                (sizes.get(sample_index), durations.get(sample_index))
+            else {
+                break;
+            };
+            samples.push(Sample {
+                offset,
+                size,
+                duration,
+                composition_offset: composition_offsets[sample_index],
+                is_sync: sync_samples
+                    .as_ref()
+                    .is_none_or(|sync| sync.binary_search(&(sample_index as u32 + 1)).is_ok()),
+            });
+            offset += size as usize;
+            sample_index += 1;
+        }
+        chunks.push(Chunk {
+            sample_description_index,
+            samples,
+        });
+    }
+    Ok(chunks)
+}
+
+/// Builds the `moov` box of the output. `base_offset` is the offset of the media data in the output.
+fn build_moov(
+    moov: &Mp4Box,
+    tracks: &[Track],
+    movie_timescale: u32,
+    base_offset: u64,
+    use_co64: bool,
+) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    let mut movie_duration = 0;
+    let mut offset = base_offset;
+    let mut traks = Vec::new();
+    for track in tracks {
+        let (trak, duration) = build_trak(track, movie_timescale, offset, use_co64)?;
+        movie_duration = movie_duration.max(duration);
+        offset += track
+            .chunks
+            .iter()
+            .flat_map(|chunk| &chunk.samples)
+            .map(|sample| sample.size as u64)
+            .sum::<u64>();
+        traks.push(trak);
+    }
+    let mut traks = traks.into_iter();
+    for child in moov.children()? {
+        match &child.name {
+            b"mvhd" => payload.extend(patch_duration(&child, 16, 24, movie_duration)?),
+            b"trak" => payload.extend(traks.next().unwrap_or_default()),
+            b"mvex" => {}
+            _ => payload.extend(child.data),
+        }
+    }
+    Ok(mp4_box(b"moov", &payload))
+}
+
+/// Builds a `trak` box with the sample tables of the track.
+/// Returns the box and the duration of the track in the movie timescale.
+fn build_trak(
+    track: &Track,
+    movie_timescale: u32,
+    base_offset: u64,
+    use_co64: bool,
+) -> Result<(Vec<u8>, u64)> {
+    let mdia = track
+        .trak
+        .child(b"mdia")?
+        .ok_or_else(|| Error::Mp4("mdia box not found".to_string()))?;
+    let mdhd = mdia
+        .child(b"mdhd")?
+        .ok_or_else(|| Error::Mp4("mdhd box not found".to_string()))?;
+    let media_timescale = read_u32(mdhd.payload(), timescale_offset(mdhd.payload()))?;
+    let media_duration = track.media_duration();
+
+    // Keep the presentation of the original edit list, e.g. without the priming samples of AAC
+    // at the start and the padding at the end.
+    let (segment_duration, media_time) = match track.trak.child(b"edts")? {
+        Some(edts) => match edts.child(b"elst")? {
+            Some(elst) => first_edit(elst.payload())?,
+            None => (0, 0),
+        },
+        None => (0, 0),
+    };
+    let presentation_duration = media_duration.saturating_sub(media_time.max(0) as u64);
+    let mut duration = if media_timescale == 0 {
+        0
+    } else {
+        presentation_duration * movie_timescale as u64 / media_timescale as u64
+    };
+    // Fragmented files usually have an empty segment duration, the duration being unknown.
+    if segment_duration > 0 {
+        duration = duration.min(segment_duration);
+    }
+
+    let mut payload = Vec::new();
+    for child in track.trak.children()? {
+        match &child.name {
+            b"tkhd" => {
+                payload.extend(patch_duration(&child, 20, 28, duration)?);
+                payload.extend(build_edts(duration, media_time));
+            }
+            b"edts" => {}
+            b"mdia" => payload.extend(build_mdia(
+                track,
+                &child,
+                media_duration,
+                base_offset,
+                use_co64,
+            )?),
+            _ => payload.extend(child.data),
+        }
+    }
+    Ok((mp4_box(b"trak", &payload), duration))
+}
+
+fn build_mdia(
+    track: &Track,
+    mdia: &Mp4Box,
+    media_duration: u64,
+    base_offset: u64,
+    use_co64: bool,
+) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    for child in mdia.children()? {
+        match &child.name {
+            b"mdhd" => payload.extend(patch_duration(&child, 16, 24, media_duration)?),
+            b"minf" => {
+                let mut minf = Vec::new();
+                for child in child.children()? {
+                    if &child.name == b"stbl" {
+                        minf.extend(build_stbl(track, &child, base_offset, use_co64)?);
+                    } else {
+                        minf.extend(child.data);
+                    }
+                }
+                payload.extend(mp4_box(b"minf", &minf));
+            }
+            _ => payload.extend(child.data),
+        }
+    }
+    Ok(mp4_box(b"mdia", &payload))
+}
+
+fn build_stbl(track: &Track, stbl: &Mp4Box, base_offset: u64, use_co64: bool) -> Result<Vec<u8>> {
+    let stsd = stbl
+        .child(b"stsd")?
+        .ok_or_else(|| Error::Mp4("stsd box not found".to_string()))?;
+    let samples: Vec<&Sample> = track.chunks.iter().flat_map(|c| &c.samples).collect();
+
+    let mut stts = Vec::new();
+    let mut stts_count = 0u32;
+    for run in runs(samples.iter().map(|s| s.duration as i64)) {
+        stts.extend(run.0.to_be_bytes());
+        stts.extend((run.1 as u32).to_be_bytes());
+        stts_count += 1;
+    }
+
+    let mut payload = unprotect_stsd(&stsd)?;
+    payload.extend(mp4_full_box(
+        b"stts",
+        0,
+        0,
+        &[&stts_count.to_be_bytes()[..], &stts].concat(),
+    ));
+
+    if samples.iter().any(|s| s.composition_offset != 0) {
+        let version = samples.iter().any(|s| s.composition_offset < 0) as u8;
+        let mut ctts = Vec::new();
+        let mut ctts_count = 0u32;
+        for run in runs(samples.iter().map(|s| s.composition_offset)) {
+            ctts.extend(run.0.to_be_bytes());
+            ctts.extend((run.1 as i32).to_be_bytes());
+            ctts_count += 1;
+        }
+        payload.extend(mp4_full_box(
+            b"ctts",
+            version,
+            0,
+            &[&ctts_count.to_be_bytes()[..], &ctts].concat(),
+        ));
+    }
+
+    if samples.iter().any(|s| !s.is_sync) {
+        let sync: Vec<u32> = samples
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_sync)
+            .map(|(i, _)| i as u32 + 1)
+            .collect();
+        let mut stss = (sync.len() as u32).to_be_bytes().to_vec();
+        sync.iter().for_each(|n| stss.extend(n.to_be_bytes()));
+        payload.extend(mp4_full_box(b"stss", 0, 0, &stss));
+    }
+
+    let mut stsz = 0u32.to_be_bytes().to_vec();
+    stsz.extend((samples.len() as u32).to_be_bytes());
+    samples
+        .iter()
+        .for_each(|s| stsz.extend(s.size.to_be_bytes()));
+    payload.extend(mp4_full_box(b"stsz", 0, 0, &stsz));
+
+    let mut stsc = Vec::new();
+    let mut stsc_count = 0u32;
+    let mut previous: Option<(usize, u32)> = None;
+    for (index, chunk) in track.chunks.iter().enumerate() {
+        let entry = (chunk.samples.len(), chunk.sample_description_index);
+        if previous != Some(entry) {
+            stsc.extend((index as u32 + 1).to_be_bytes());
+            stsc.extend((entry.0 as u32).to_be_bytes());
+            stsc.extend(entry.1.to_be_bytes());
+            stsc_count += 1;
+            previous = Some(entry);
+        }
+    }
+    payload.extend(mp4_full_box(
+        b"stsc",
+        0,
+        0,
+        &[&stsc_count.to_be_bytes()[..], &stsc].concat(),
+    ));
+
+    let mut offsets = (track.chunks.len() as u32).to_be_bytes().to_vec();
+    let mut offset = base_offset;
+    for chunk in &track.chunks {
+        if use_co64 {
+            offsets.extend(offset.to_be_bytes());
+        } else {
+            offsets.extend((offset as u32).to_be_bytes());
+        }
+        offset += chunk.samples.iter().map(|s| s.size as u64).sum::<u64>();
+    }
+    payload.extend(mp4_full_box(
+        if use_co64 { b"co64" } else { b"stco" },
+        0,
+        0,
+        &offsets,
+    ));
+    Ok(mp4_box(b"stbl", &payload))
+}
+
+/// Builds an edit list presenting the track from `media_time`, for `duration` in the movie timescale.
+pub(crate) fn build_edts(duration: u64, media_time: i64) -> Vec<u8> {
+    let mut elst = 1u32.to_be_bytes().to_vec();
+    let version = if duration > u32::MAX as u64 || media_time > i32::MAX as i64 {
+        elst.extend(duration.to_be_bytes());
+        elst.extend(media_time.to_be_bytes());
+        1
+    } else {
+        elst.extend((duration as u32).to_be_bytes());
+        elst.extend((media_time as i32).to_be_bytes());
+        0
+    };
+    elst.extend(0x00010000u32.to_be_bytes());
+    mp4_box(b"edts", &mp4_full_box(b"elst", version, 0, &elst))
+}
+
+/// Returns the segment duration and media time of the first non-empty edit of an `elst` box.
+pub(crate) fn first_edit(elst: &[u8]) -> Result<(u64, i64)> {
+    let version = elst.first().copied().unwrap_or_default();
+    let entry_size = if version == 1 { 20 } else { 12 };
+    for i in 0..read_u32(elst, 4)? as usize {
+        let entry = 8 + i * entry_size;
+        let (segment_duration, media_time) = if version == 1 {
+            (read_u64(elst, entry)?, read_u64(elst, entry + 8)? as i64)
+        } else {
+            (
+                read_u32(elst, entry)? as u64,
+                read_u32(elst, entry + 4)? as i32 as i64,
+            )
+        };
+        if media_time >= 0 {
+            return Ok((segment_duration, media_time));
+        }
+    }
+    Ok((0, 0))
+}
+
+/// Replaces the sample entries protected with Common Encryption (`enca`, `encv`) with their
+/// original format, e.g. `mp4a` or `alac`, and removes their `sinf` box.
+pub fn unprotect_stsd(stsd: &Mp4Box) -> Result<Vec<u8>> {
+    let payload = stsd.payload();
+    let mut new_payload = payload
+        .get(..8)
+        .ok_or_else(|| Error::Mp4("Truncated stsd box".to_string()))?
+        .to_vec();
+    for entry in parse_boxes(&payload[8..])? {
+        let fields_len = match &entry.name {
+            b"enca" | b"encv" => sample_entry_fields_len(&entry)?,
+            _ => {
+                new_payload.extend(entry.data);
+                continue;
+            }
+        };
+        let fields = entry
+            .payload()
+            .get(..fields_len)
+            .ok_or_else(|| Error::Mp4("Truncated sample entry".to_string()))?;
+        let mut original_format = None;
+        let mut children = Vec::new();
+        for child in parse_boxes(&entry.payload()[fields_len..])? {
+            if &child.name == b"sinf" {
+                original_format = child
+                    .child(b"frma")?
+                    .and_then(|frma| frma.payload().get(..4))
+                    .map(|name| <[u8; 4]>::try_from(name).unwrap());
+            } else {
+                children.extend(child.data);
+            }
+        }
+        let name = original_format.ok_or_else(|| Error::Mp4("frma box not found".to_string()))?;
+        new_payload.extend(mp4_box(&name, &[fields, &children].concat()));
+    }
+    Ok(mp4_box(b"stsd", &new_payload))
+}
+
+/// Returns the length of the fields of a protected sample entry, in front of its child boxes.
+pub(crate) fn sample_entry_fields_len(entry: &Mp4Box) -> Result<usize> {
+    Ok(match &entry.name {
+        b"encv" => 78,
+        // The version of the audio sample entry.
+        _ => match read_u16(entry.payload(), 8)? {
+            1 => 44,
+            2 => 64,
+            _ => 28,
+        },
+    })
+}
+
+/// Returns the runs of equal values as (count, value) pairs.
+fn runs(values: impl Iterator<Item = i64>) -> Vec<(u32, i64)> {
+    let mut runs: Vec<(u32, i64)> = Vec::new();
+    for value in values {
+        match runs.last_mut() {
+            Some((count, last)) if *last == value => *count += 1,
+            _ => runs.push((1, value)),
+        }
+    }
+    runs
+}
+
+/// Rewrites the duration of a `mvhd`, `tkhd` or `mdhd` box, at `v0_offset` or `v1_offset`
+/// of the payload depending on the version of the box.
+pub(crate) fn patch_duration(
+    full_box: &Mp4Box,
+    v0_offset: usize,
+    v1_offset: usize,
+    duration: u64,
+) -> Result<Vec<u8>> {
+    let mut payload = full_box.payload().to_vec();
+    if payload.first() == Some(&1) {
+        payload
+            .get_mut(v1_offset..v1_offset + 8)
+            .ok_or_else(|| Error::Mp4("Truncated header box".to_string()))?
+            .copy_from_slice(&duration.to_be_bytes());
+    } else {
+        let duration = u32::try_from(duration)
+            .map_err(|_| Error::Mp4("Duration too long for a version 0 box".to_string()))?;
+        payload
+            .get_mut(v0_offset..v0_offset + 4)
+            .ok_or_else(|| Error::Mp4("Truncated header box".to_string()))?
+            .copy_from_slice(&duration.to_be_bytes());
+    }
+    Ok(mp4_box(&full_box.name, &payload))
+}
+
+/// Returns the offset of the timescale in the payload of a `mvhd` or `mdhd` box.
+pub(crate) fn timescale_offset(payload: &[u8]) -> usize {
+    if payload.first() == Some(&1) {
+        20
+    } else {
+        12
+    }
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| Error::Mp4("Unexpected end of box".to_string()))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| Error::Mp4("Unexpected end of box".to_string()))
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| Error::Mp4("Unexpected end of box".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::remux_segments;
+    use crate::hls::Segments;
+    use crate::mp4::fixtures::{fragment, init_segment};
+    use crate::mp4::{find_box, parse_boxes};
+
+    #[test]
+    fn test_remux_segments() {
+        let segments = Segments {
+            init: Some(init_segment(b"cbcs", None)),
+            segments: vec![
+                fragment(1, &[b"aaa", b"bbbb"], None),
+                fragment(2, &[b"ccccc", b"dd"], None),
+            ],
+        };
+        let output = remux_segments(&segments).unwrap();
+        let names: Vec<[u8; 4]> = parse_boxes(&output)
+            .unwrap()
+            .iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names, [*b"ftyp", *b"moov", *b"mdat"]);
+        assert!(find_box(&output, &[b"moov", b"mvex"]).unwrap().is_none());
+
+        let stbl = [b"moov", b"trak", b"mdia", b"minf", b"stbl"];
+        let table = |name: &[u8; 4]| {
+            find_box(&output, &[&stbl[..], &[name]].concat())
+                .unwrap()
+                .unwrap()
+                .payload()
+                .to_vec()
+        };
+        let stsd = table(b"stsd");
+        assert_eq!(&stsd[12..16], b"mp4a");
+        assert!(!stsd.windows(4).any(|w| w == b"sinf"));
+        assert_eq!(
+            table(b"stts")[4..],
+            [1u32, 4, 1024].map(u32::to_be_bytes).concat()
+        );
+        assert_eq!(
+            table(b"stsz")[4..],
+            [0u32, 4, 3, 4, 5, 2].map(u32::to_be_bytes).concat()
+        );
+        assert_eq!(
+            table(b"stsc")[4..],
+            [1u32, 1, 2, 1].map(u32::to_be_bytes).concat()
+        );
+        let stco = table(b"stco");
+        let first = u32::from_be_bytes(stco[8..12].try_into().unwrap()) as usize;
+        let second = u32::from_be_bytes(stco[12..16].try_into().unwrap()) as usize;
+        assert_eq!(&output[first..first + 7], b"aaabbbb");
+        assert_eq!(&output[second..second + 7], b"cccccdd");
+
+        let mdhd = find_box(&output, &[b"moov", b"trak", b"mdia", b"mdhd"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(mdhd.payload()[16..20], 4096u32.to_be_bytes());
+        let mvhd = find_box(&output, &[b"moov", b"mvhd"]).unwrap().unwrap();
+        assert_eq!(mvhd.payload()[16..20], 92u32.to_be_bytes());
+        let elst = find_box(&output, &[b"moov", b"trak", b"edts", b"elst"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            elst.payload()[4..],
+            [1u32, 92, 0, 0x10000].map(u32::to_be_bytes).concat()
+        );
+
+        // A progressive file is rewritten to the same tables.
+        assert_eq!(super::remux(&output).unwrap(), output);
+    }
+}
@@ -1,7 +1,10 @@
 //! the stream info struct and functions.
 
-use crate::error::Result;
-use crate::{api::*, decrypter};
+use crate::api::songs::Songs;
+use crate::api::*;
+use crate::error::{Error, Result};
+use crate::loudness::Loudness;
+use crate::master_playlist::{MasterPlaylistInfo, Variant};
 
 /// A struct representing the stream information.
 #[derive(Debug, Clone)]
@@ -9,42 +12,179 @@ pub struct StreamInfo {
     pub stream_url: String,
     pub pssh: String,
     pub codec: String,
+    /// The channel layout of the variant, when the master playlist describes it.
+    pub channel_layout: Option<ChannelLayout>,
+    /// The loudness of the variant, when its audio metadata measures it.
+    pub loudness: Option<Loudness>,
+}
+
+/// The channel layout of a stream, from the CHANNELS attribute of its audio rendition,
+/// e.g. `2` for stereo or `16/JOC` for Dolby Atmos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelLayout {
+    /// The number of channels, or of objects with Joint Object Coding.
+    pub channels: u32,
+    /// Whether the channels are coded with Joint Object Coding (JOC), i.e. Dolby Atmos.
+    pub joint_object_coding: bool,
+}
+
+impl ChannelLayout {
+    /// Parses the CHANNELS attribute of a rendition, e.g. `6` or `16/JOC`.
+    pub fn parse(channels: &str) -> Option<Self> {
+        let mut parameters = channels.split('/');
+        let count = parameters.next()?.trim().parse().ok()?;
+        Some(Self {
+            channels: count,
+            joint_object_coding: parameters.any(|parameter| parameter == "JOC"),
+        })
+    }
+}
+
+impl std::fmt::Display for ChannelLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.channels)?;
+        if self.joint_object_coding {
+            write!(f, "/JOC")?;
+        }
+        Ok(())
+    }
+}
+
+/// The audio codecs of the variants of an enhanced HLS master playlist, used to select a variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    /// Apple Lossless, up to 24-bit/192 kHz.
+    Alac,
+    /// Dolby Atmos, in E-AC-3 with JOC.
+    Atmos,
+    /// Dolby Digital, in AC-3.
+    Ac3,
+    /// AAC-LC at 256 kbit/s.
+    Aac,
+    /// HE-AAC at 64 kbit/s.
+    AacHe,
+    /// AAC-LC, binaural rendering of the Dolby Atmos mix.
+    AacBinaural,
+    /// AAC-LC, stereo downmix of the Dolby Atmos mix.
+    AacDownmix,
+}
+
+impl Codec {
+    /// Returns the codec of a variant, from its CODECS and AUDIO group id.
+    pub fn of(variant: &Variant) -> Option<Self> {
+        let codecs = variant.codecs.as_deref()?;
+        let group = variant.audio.as_deref().unwrap_or_default();
+        if codecs.starts_with("alac") {
+            Some(Codec::Alac)
+        } else if codecs.starts_with("ec-3") {
+            Some(Codec::Atmos)
+        } else if codecs.starts_with("ac-3") {
+            Some(Codec::Ac3)
+        } else if codecs.starts_with("mp4a.40.5") {
+            Some(Codec::AacHe)
+        } else if codecs.starts_with("mp4a.40.2") {
+            if group.contains("binaural") {
+                Some(Codec::AacBinaural)
+            } else if group.contains("downmix") {
+                Some(Codec::AacDownmix)
+            } else {
+                Some(Codec::Aac)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the variant with the highest bandwidth of the first codec of `codecs` available.
+pub fn select_variant<'a>(info: &'a MasterPlaylistInfo, codecs: &[Codec]) -> Option<&'a Variant> {
+    codecs.iter().find_map(|codec| {
+        info.variants
+            .iter()
+            .filter(|variant| Codec::of(variant) == Some(*codec))
+            .max_by_key(|variant| variant.bandwidth)
+    })
 }
 
 impl StreamInfo {
     /// Creates a new `StreamInfo` instance from the provided M3U8 data and base URI.
+    /// The first variant of the master playlist is used.
     pub fn new(m3u8: Vec<u8>, base_uri: &str) -> Result<Self> {
-        let (_, m3u8_data) = m3u8_rs::parse_master_playlist(&m3u8)
-            .map_err(|e| crate::error::Error::Decrypt(e.to_string()))?;
-        let drm_infos = decrypter::get_drm_info(&m3u8_data)?;
-        let assert_info = decrypter::get_assert_info(&m3u8_data)?;
-        let base_uri = match base_uri.rfind('/') {
-            Some(index) => &base_uri[..index + 1],
-            None => base_uri,
-        };
-        let stream_url = format!("{}{}", base_uri, m3u8_data.variants[0].uri.clone());
-        let variant_id = m3u8_data.variants[0]
-            .other_attributes
+        let info = MasterPlaylistInfo::new(&m3u8, base_uri)?;
+        let variant = info
+            .variants
+            .first()
+            .ok_or_else(|| Error::Decrypt("No variant found".to_string()))?;
+        Self::from_variant(&info, variant)
+    }
+
+    /// Creates a new `StreamInfo` instance from the variant of the first codec of `codecs`
+    /// available in the master playlist. Among the variants of the codec, the variant with
+    /// the highest bandwidth is selected.
+    pub fn new_with_codecs(info: &MasterPlaylistInfo, codecs: &[Codec]) -> Result<Self> {
+        let variant = select_variant(info, codecs)
+            .ok_or_else(|| Error::Other(format!("None of the codecs {codecs:?} is available")))?;
+        Self::from_variant(info, variant)
+    }
+
+    /// Creates a new `StreamInfo` instance from the enhanced HLS master playlist of a song.
+    /// The song must be requested with `Extend::ExtendedAssetUrls`; see `new_with_codecs` for the codec selection.
+    pub async fn new_with_song(
+        song: &Songs,
+        client: &reqwest::Client,
+        codecs: &[Codec],
+    ) -> Result<Self> {
+        let m3u8_url = &song
+            .attributes
             .as_ref()
-            .and_then(|attrs| attrs.get("STABLE-VARIANT-ID").cloned())
-            .expect("STABLE-VARIANT-ID not found");
-        let drm_ids = &assert_info[variant_id.as_str()]["AUDIO-SESSION-KEY-IDS"];
-        let pssh = decrypter::get_pssh(&drm_infos, drm_ids).unwrap().clone();
-        let codec = m3u8_data.variants[0].codecs.as_ref().unwrap().clone();
+            .and_then(|attributes| attributes.extended_asset_urls.as_ref())
+            .ok_or_else(|| Error::Other("The song has no extended asset URLs".to_string()))?
+            .enhanced_hls;
+        let m3u8 = client
+            .get(m3u8_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        let info = MasterPlaylistInfo::new(&m3u8, m3u8_url)?;
+        Self::new_with_codecs(&info, codecs)
+    }
+
+    fn from_variant(info: &MasterPlaylistInfo, variant: &Variant) -> Result<Self> {
+        let pssh = info
+            .widevine_pssh(variant)
+            .ok_or_else(|| Error::Decrypt("PSSH not found".to_string()))?;
+        // The CHANNELS of the audio rendition, else the channel count of the audio metadata.
+        let channels = info
+            .renditions
+            .iter()
+            .filter(|rendition| rendition.media_type == "AUDIO")
+            .filter(|rendition| Some(&rendition.group_id) == variant.audio.as_ref())
+            .find_map(|rendition| rendition.channels.as_deref());
+        let channel_layout = match channels {
+            Some(channels) => ChannelLayout::parse(channels),
+            None => info.metadata(variant).and_then(|metadata| {
+                let mut layout = ChannelLayout::parse(metadata.channel_count.as_deref()?)?;
+                layout.joint_object_coding |= metadata.is_atmos == Some(true);
+                Some(layout)
+            }),
+        };
+        let loudness = info.metadata(variant).and_then(|metadata| {
+            Some(Loudness {
+                integrated: metadata.loudness?,
+                peak: metadata.peak,
+            })
+        });
         Ok(Self {
-            stream_url,
-            pssh,
-            codec,
+            stream_url: variant.uri.clone(),
+            pssh: pssh.to_string(),
+            codec: variant.codecs.clone().unwrap_or_default(),
+            channel_layout,
+            loudness,
         })
     }
 
-    // pub async fn new_with_song(song: Song, client: &reqwest::Client) -> Result<Self> {
-    //     let m3u8_url = song.attributes.extended_asset_urls.enhanced_hls;
-    //     let m3u8 = client.get(&m3u8_url).send().await?.bytes().await?.to_vec();
-    //     let stream_info = Self::new(m3u8, &m3u8_url);
-    //     stream_info
-    // }
-
     /// Creates a new `StreamInfo` instance from a `WebPlayBack` instance.
     pub async fn new_with_webplayback(webplayback: &webplayback::WebPlayBack) -> Result<Self> {
         let webplayback = webplayback
@@ -76,9 +216,139 @@ impl StreamInfo {
                 stream_url: webplayback.url.clone(),
                 pssh: pssh.clone(),
                 codec: String::new(),
+                channel_layout: None,
+                loudness: None,
             })
         } else {
             Err(crate::error::Error::Other("Source not exists".to_string()))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelLayout, Codec, StreamInfo};
+    use crate::api::songs::Songs;
+    use crate::master_playlist::MasterPlaylistInfo;
+    use crate::mock_server::{MockResponse, MockServer};
+    use base64::Engine;
+
+    fn master_playlist() -> String {
+        let encode = |value: serde_json::Value| {
+            base64::engine::general_purpose::STANDARD.encode(value.to_string())
+        };
+        let widevine = crate::master_playlist::WIDEVINE_KEY_FORMAT;
+        let keys = encode(serde_json::json!({
+            "1": { widevine: { "URI": "data:text/plain;base64,cHJldmlldw==" } },
+            "2": { widevine: { "URI": "data:text/plain;base64,YWFj" } },
+            "3": { widevine: { "URI": "data:text/plain;base64,YWxhYw==" } }
+        }));
+        let metadata = encode(serde_json::json!({
+            "aac": { "AUDIO-SESSION-KEY-IDS": ["1", "2"] },
+            "alac-44": { "AUDIO-SESSION-KEY-IDS": ["1", "3"] },
+            "alac-96": { "AUDIO-SESSION-KEY-IDS": ["1", "3"] }
+        }));
+        format!(
+            "#EXTM3U\n\
+            #EXT-X-SESSION-DATA:DATA-ID=\"com.apple.hls.AudioSessionKeyInfo\",VALUE=\"{keys}\"\n\
+            #EXT-X-SESSION-DATA:DATA-ID=\"com.apple.hls.audioAssetMetadata\",VALUE=\"{metadata}\"\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=300000,CODECS=\"mp4a.40.2\",AUDIO=\"audio-stereo-256\",STABLE-VARIANT-ID=\"aac\"\n\
+            aac.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=1200000,CODECS=\"alac\",AUDIO=\"audio-alac-stereo-44100-16\",STABLE-VARIANT-ID=\"alac-44\"\n\
+            alac-44.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=4000000,CODECS=\"alac\",AUDIO=\"audio-alac-stereo-96000-24\",STABLE-VARIANT-ID=\"alac-96\"\n\
+            alac-96.m3u8\n"
+        )
+    }
+
+    #[tokio::test]
+    async fn test_new_with_song() {
+        let response = || MockResponse {
+            status: 200,
+            headers: vec![],
+            body: master_playlist().into_bytes(),
+        };
+        let server = MockServer::start(vec![response(), response(), response()]).await;
+        let song: Songs = serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "type": "songs",
+            "href": "/v1/catalog/us/songs/1",
+            "attributes": {
+                "artistName": "Artist",
+                "artwork": { "width": 3000, "height": 3000, "url": "https://example.com/{w}x{h}bb.jpg" },
+                "discNumber": 1,
+                "durationInMillis": 200000,
+                "genreNames": [],
+                "name": "Song",
+                "url": "https://music.apple.com/us/song/1",
+                "extendedAssetUrls": {
+                    "plus": "", "lightweight": "", "superLightweight": "", "lightweightPlus": "",
+                    "enhancedHls": format!("{}/hls/P.m3u8", server.url)
+                }
+            }
+        }))
+        .unwrap();
+        let client = reqwest::Client::new();
+
+        let stream_info = StreamInfo::new_with_song(&song, &client, &[Codec::Atmos, Codec::Alac])
+            .await
+            .unwrap();
+        assert_eq!(
+            stream_info.stream_url,
+            format!("{}/hls/alac-96.m3u8", server.url)
+        );
+        assert_eq!(stream_info.pssh, "data:text/plain;base64,YWxhYw==");
+        assert_eq!(stream_info.codec, "alac");
+
+        let stream_info = StreamInfo::new_with_song(&song, &client, &[Codec::Aac])
+            .await
+            .unwrap();
+        assert_eq!(stream_info.pssh, "data:text/plain;base64,YWFj");
+        assert!(StreamInfo::new_with_song(&song, &client, &[Codec::Atmos])
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_channel_layout() {
+        let encode = |value: serde_json::Value| {
+            base64::engine::general_purpose::STANDARD.encode(value.to_string())
+        };
+        let widevine = crate::master_playlist::WIDEVINE_KEY_FORMAT;
+        let keys = encode(serde_json::json!({
+            "4": { widevine: { "URI": "data:text/plain;base64,YXRtb3M=" } }
+        }));
+        let metadata = encode(serde_json::json!({
+            "atmos": { "AUDIO-SESSION-KEY-IDS": ["4"] },
+            "aac": { "AUDIO-SESSION-KEY-IDS": ["4"], "CHANNEL-COUNT": "2", "LOUDNESS": -7.5, "PEAK": -0.4 }
+        }));
+        let m3u8 = format!(
+            "#EXTM3U\n\
+            #EXT-X-SESSION-DATA:DATA-ID=\"com.apple.hls.AudioSessionKeyInfo\",VALUE=\"{keys}\"\n\
+            #EXT-X-SESSION-DATA:DATA-ID=\"com.apple.hls.audioAssetMetadata\",VALUE=\"{metadata}\"\n\
+            #EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio-atmos-2768\",NAME=\"Dolby Atmos\",CHANNELS=\"16/JOC\"\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=3000000,CODECS=\"ec-3\",AUDIO=\"audio-atmos-2768\",STABLE-VARIANT-ID=\"atmos\"\n\
+            atmos.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=300000,CODECS=\"mp4a.40.2\",AUDIO=\"audio-stereo-256\",STABLE-VARIANT-ID=\"aac\"\n\
+            aac.m3u8\n"
+        );
+        let info = MasterPlaylistInfo::new(m3u8.as_bytes(), "https://example.com/P.m3u8").unwrap();
+
+        let stream_info = StreamInfo::new_with_codecs(&info, &[Codec::Atmos]).unwrap();
+        assert_eq!(stream_info.codec, "ec-3");
+        let layout = stream_info.channel_layout.unwrap();
+        assert_eq!(
+            layout,
+            ChannelLayout {
+                channels: 16,
+                joint_object_coding: true
+            }
+        );
+        assert_eq!(layout.to_string(), "16/JOC");
+
+        let stream_info = StreamInfo::new_with_codecs(&info, &[Codec::Aac]).unwrap();
+        assert_eq!(stream_info.channel_layout.unwrap().to_string(), "2");
+        let loudness = stream_info.loudness.unwrap();
+        assert_eq!((loudness.integrated, loudness.peak), (-7.5, Some(-0.4)));
+    }
+}
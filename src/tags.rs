@@ -0,0 +1,442 @@
+//! the metadata written to output files.
+
+use crate::api::credits::Credits;
+use crate::api::{music_videos, songs, webplayback};
+use crate::error::Result;
+use crate::gapless::GaplessInfo;
+use crate::loudness::ReplayGain;
+use crate::mp4::{mp4_box, mp4_full_box};
+
+/// The format-neutral metadata of an output file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
+    pub genre: Option<String>,
+    /// The release date, in YYYY-MM-DD or YYYY format.
+    pub release_date: Option<String>,
+    pub copyright: Option<String>,
+    pub lyrics: Option<String>,
+    pub isrc: Option<String>,
+    pub track_number: Option<u32>,
+    pub track_count: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub disc_count: Option<u32>,
+    pub compilation: Option<bool>,
+    /// Whether the track is part of a gapless album.
+    pub gapless: Option<bool>,
+    /// The encoder delay and padding, written as the `iTunSMPB` tag and the edit list of MP4 files.
+    pub gapless_info: Option<GaplessInfo>,
+    /// The ReplayGain of the track, also written as the Sound Check (`iTunNORM`) tag of MP4 files.
+    pub replay_gain: Option<ReplayGain>,
+    /// The content rating: 0 for none, 1 for explicit and 2 for clean.
+    pub rating: Option<u8>,
+    pub sort_title: Option<String>,
+    pub sort_artist: Option<String>,
+    pub sort_album: Option<String>,
+    pub sort_composer: Option<String>,
+    /// (Classical music only) The name of the work.
+    pub work: Option<String>,
+    /// (Classical music only) The name of the movement.
+    pub movement_name: Option<String>,
+    /// (Classical music only) The number of the movement within the work.
+    pub movement_number: Option<u32>,
+    /// (Classical music only) The number of movements of the work.
+    pub movement_count: Option<u32>,
+    /// Whether players should show the work and movement instead of the title.
+    pub show_work_movement: Option<bool>,
+    /// The cover image, JPEG or PNG.
+    pub cover: Option<Vec<u8>>,
+    /// Free-form tags as (name, value) pairs, e.g. ("PRODUCER", "…"). A name may appear more than once.
+    pub freeform: Vec<(String, String)>,
+}
+
+impl Tags {
+    /// Creates `Tags` from the catalog attributes of a song.
+    pub fn from_song(attributes: &songs::Attributes) -> Self {
+        Self {
+            title: Some(attributes.name.clone()),
+            artist: Some(attributes.artist_name.clone()),
+            album: attributes.album_name.clone(),
+            composer: attributes.composer_name.clone(),
+            genre: attributes.genre_names.first().cloned(),
+            release_date: attributes.release_date.clone(),
+            isrc: attributes.isrc.clone(),
+            track_number: attributes.track_number.map(|n| n as u32),
+            disc_number: Some(attributes.disc_number),
+            work: attributes.work_name.clone(),
+            movement_name: attributes.movement_name.clone(),
+            movement_number: attributes.movement_number.map(|n| n as u32),
+            movement_count: attributes.movement_count.map(|n| n as u32),
+            show_work_movement: attributes.work_name.as_ref().map(|_| true),
+            rating: attributes
+                .content_rating
+                .as_deref()
+                .map(|rating| match rating {
+                    "explicit" => 1,
+                    "clean" => 2,
+                    _ => 0,
+                }),
+            ..Default::default()
+        }
+    }
+
+    /// Creates `Tags` from the metadata of a WebPlayBack asset.
+    pub fn from_webplayback(metadata: &webplayback::Metadata) -> Self {
+        Self {
+            title: Some(metadata.item_name.clone()),
+            artist: Some(metadata.artist_name.clone()),
+            album: Some(metadata.playlist_name.clone()),
+            album_artist: Some(metadata.playlist_artist_name.clone()),
+            composer: Some(metadata.composer_name.clone()).filter(|c| !c.is_empty()),
+            genre: Some(metadata.genre.clone()),
+            release_date: Some(metadata.release_date.clone()),
+            copyright: Some(metadata.copyright.clone()),
+            track_number: Some(metadata.track_number),
+            track_count: Some(metadata.track_count),
+            disc_number: Some(metadata.disc_number),
+            disc_count: Some(metadata.disc_count),
+            compilation: Some(metadata.compilation),
+            gapless: Some(metadata.gapless),
+            rating: Some(metadata.explicit),
+            sort_title: Some(metadata.sort_name.clone()),
+            sort_artist: Some(metadata.sort_artist.clone()),
+            sort_album: Some(metadata.sort_album.clone()),
+            sort_composer: Some(metadata.sort_composer.clone()).filter(|c| !c.is_empty()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates `Tags` from the catalog attributes of a music video.
+    pub fn from_music_video(attributes: &music_videos::Attributes) -> Self {
+        Self {
+            title: Some(attributes.name.clone()),
+            artist: Some(attributes.artist_name.clone()),
+            album: attributes.album_name.clone(),
+            genre: attributes.genre_names.first().cloned(),
+            release_date: attributes.release_date.clone(),
+            isrc: attributes.isrc.clone(),
+            track_number: attributes.track_number.map(|n| n as u32),
+            work: attributes.work_name.clone(),
+            rating: attributes
+                .content_rating
+                .as_deref()
+                .map(|rating| match rating {
+                    "explicit" => 1,
+                    "clean" => 2,
+                    _ => 0,
+                }),
+            ..Default::default()
+        }
+    }
+
+    /// Adds the people of the song credits.
+    /// Writers fill the composer (©wrt) when it is unknown; producers, engineers and
+    /// other roles become free-form tags, e.g. PRODUCER or MIXER.
+    pub fn add_credits(&mut self, credits: &[Credits]) {
+        let mut writers: Vec<String> = Vec::new();
+        for credit in credits.iter().flat_map(|credits| credits.people()) {
+            for role in &credit.roles {
+                let name = credit_tag_name(role);
+                if name == "PERFORMER" {
+                    let value = format!("{} ({role})", credit.name);
+                    self.freeform.push((name.to_string(), value));
+                    continue;
+                }
+                if (name == "COMPOSER" || name == "LYRICIST") && !writers.contains(&credit.name) {
+                    writers.push(credit.name.clone());
+                }
+                let tag = (name.to_string(), credit.name.clone());
+                if !self.freeform.contains(&tag) {
+                    self.freeform.push(tag);
+                }
+            }
+        }
+        if self.composer.is_none() && !writers.is_empty() {
+            self.composer = Some(writers.join(", "));
+        }
+    }
+
+    /// Serializes the tags as an MP4 `ilst` box, the iTunes metadata item list.
+    pub fn to_mp4_ilst(&self) -> Vec<u8> {
+        let mut items = Vec::new();
+        let text_items = [
+            (b"\xa9nam", &self.title),
+            (b"\xa9ART", &self.artist),
+            (b"\xa9alb", &self.album),
+            (b"aART", &self.album_artist),
+            (b"\xa9wrt", &self.composer),
+            (b"\xa9gen", &self.genre),
+            (b"\xa9day", &self.release_date),
+            (b"cprt", &self.copyright),
+            (b"\xa9lyr", &self.lyrics),
+            (b"sonm", &self.sort_title),
+            (b"soar", &self.sort_artist),
+            (b"soal", &self.sort_album),
+            (b"soco", &self.sort_composer),
+            (b"\xa9wrk", &self.work),
+            (b"\xa9mvn", &self.movement_name),
+        ];
+        for (name, value) in text_items {
+            if let Some(value) = value {
+                items.extend(mp4_data_item(name, DATA_TYPE_UTF8, value.as_bytes()));
+            }
+        }
+        if self.track_number.is_some() || self.track_count.is_some() {
+            let payload = number_pair(self.track_number, self.track_count, true);
+            items.extend(mp4_data_item(b"trkn", DATA_TYPE_IMPLICIT, &payload));
+        }
+        if self.disc_number.is_some() || self.disc_count.is_some() {
+            let payload = number_pair(self.disc_number, self.disc_count, false);
+            items.extend(mp4_data_item(b"disk", DATA_TYPE_IMPLICIT, &payload));
+        }
+        if let Some(compilation) = self.compilation {
+            items.extend(mp4_data_item(
+                b"cpil",
+                DATA_TYPE_INTEGER,
+                &[compilation as u8],
+            ));
+        }
+        if let Some(gapless) = self.gapless {
+            items.extend(mp4_data_item(b"pgap", DATA_TYPE_INTEGER, &[gapless as u8]));
+        }
+        if let Some(movement_number) = self.movement_number {
+            let payload = (movement_number as u16).to_be_bytes();
+            items.extend(mp4_data_item(b"\xa9mvi", DATA_TYPE_INTEGER, &payload));
+        }
+        if let Some(movement_count) = self.movement_count {
+            let payload = (movement_count as u16).to_be_bytes();
+            items.extend(mp4_data_item(b"\xa9mvc", DATA_TYPE_INTEGER, &payload));
+        }
+        if let Some(show_work_movement) = self.show_work_movement {
+            let payload = [show_work_movement as u8];
+            items.extend(mp4_data_item(b"shwm", DATA_TYPE_INTEGER, &payload));
+        }
+        if let Some(rating) = self.rating {
+            items.extend(mp4_data_item(b"rtng", DATA_TYPE_INTEGER, &[rating]));
+        }
+        if let Some(cover) = &self.cover {
+            let data_type = if cover.starts_with(b"\x89PNG") {
+                DATA_TYPE_PNG
+            } else {
+                DATA_TYPE_JPEG
+            };
+            items.extend(mp4_data_item(b"covr", data_type, cover));
+        }
+        if let Some(isrc) = &self.isrc {
+            items.extend(mp4_freeform_item("ISRC", isrc));
+        }
+        if let Some(gapless_info) = &self.gapless_info {
+            items.extend(mp4_freeform_item("iTunSMPB", &gapless_info.to_itunsmpb()));
+        }
+        if let Some(replay_gain) = &self.replay_gain {
+            for (name, value) in replay_gain.to_tags() {
+                items.extend(mp4_freeform_item(&name.to_lowercase(), &value));
+            }
+            items.extend(mp4_freeform_item("iTunNORM", &replay_gain.to_itunnorm()));
+        }
+        for (name, value) in &self.freeform {
+            items.extend(mp4_freeform_item(name, value));
+        }
+        mp4_box(b"ilst", &items)
+    }
+
+    /// Serializes the tags as an MP4 `udta` box, holding the `meta` box of the `ilst` box.
+    pub fn to_mp4_udta(&self) -> Vec<u8> {
+        let mut hdlr = vec![0; 4];
+        hdlr.extend(b"mdirappl");
+        hdlr.extend([0; 9]);
+        let mut meta = mp4_full_box(b"hdlr", 0, 0, &hdlr);
+        meta.extend(self.to_mp4_ilst());
+        mp4_box(b"udta", &mp4_full_box(b"meta", 0, 0, &meta))
+    }
+
+    /// Writes the tags into an MP4 file, replacing its existing metadata.
+    /// With `gapless_info`, the edit list of the track is rewritten to match it.
+    pub fn write_mp4(&self, mp4: &[u8]) -> Result<Vec<u8>> {
+        let mp4 = crate::mp4::replace_udta(mp4, &self.to_mp4_udta())?;
+        match &self.gapless_info {
+            Some(gapless_info) => gapless_info.write_edit_list(&mp4),
+            None => Ok(mp4),
+        }
+    }
+
+    /// Returns the tags as Vorbis comments, as (field name, value) pairs, e.g. for FLAC files.
+    /// The cover is not included; it is written as a separate picture block.
+    pub fn to_vorbis_comments(&self) -> Vec<(String, String)> {
+        let mut comments = Vec::new();
+        let text_fields = [
+            ("TITLE", &self.title),
+            ("ARTIST", &self.artist),
+            ("ALBUM", &self.album),
+            ("ALBUMARTIST", &self.album_artist),
+            ("COMPOSER", &self.composer),
+            ("GENRE", &self.genre),
+            ("DATE", &self.release_date),
+            ("COPYRIGHT", &self.copyright),
+            ("LYRICS", &self.lyrics),
+            ("ISRC", &self.isrc),
+            ("TITLESORT", &self.sort_title),
+            ("ARTISTSORT", &self.sort_artist),
+            ("ALBUMSORT", &self.sort_album),
+            ("COMPOSERSORT", &self.sort_composer),
+            ("WORK", &self.work),
+            ("MOVEMENTNAME", &self.movement_name),
+        ];
+        for (name, value) in text_fields {
+            if let Some(value) = value {
+                comments.push((name.to_string(), value.clone()));
+            }
+        }
+        let number_fields = [
+            ("TRACKNUMBER", self.track_number),
+            ("TRACKTOTAL", self.track_count),
+            ("DISCNUMBER", self.disc_number),
+            ("DISCTOTAL", self.disc_count),
+            ("MOVEMENT", self.movement_number),
+            ("MOVEMENTTOTAL", self.movement_count),
+        ];
+        for (name, value) in number_fields {
+            if let Some(value) = value {
+                comments.push((name.to_string(), value.to_string()));
+            }
+        }
+        let flag_fields = [
+            ("COMPILATION", self.compilation),
+            ("SHOWMOVEMENT", self.show_work_movement),
+        ];
+        for (name, value) in flag_fields {
+            if let Some(value) = value {
+                comments.push((name.to_string(), (value as u8).to_string()));
+            }
+        }
+        if let Some(rating) = self.rating.filter(|rating| *rating != 0) {
+            comments.push(("ITUNESADVISORY".to_string(), rating.to_string()));
+        }
+        if let Some(replay_gain) = &self.replay_gain {
+            comments.extend(replay_gain.to_tags());
+        }
+        comments.extend(self.freeform.iter().cloned());
+        comments
+    }
+}
+
+/// Returns the free-form tag name for a credit role.
+fn credit_tag_name(role: &str) -> &'static str {
+    let role = role.to_lowercase();
+    if role.contains("lyricist") || role.contains("lyrics") {
+        "LYRICIST"
+    } else if role.contains("composer") || role.contains("songwriter") || role.contains("writer") {
+        "COMPOSER"
+    } else if role.contains("producer") {
+        "PRODUCER"
+    } else if role.contains("mixing") {
+        "MIXER"
+    } else if role.contains("engineer") {
+        "ENGINEER"
+    } else if role.contains("arranger") {
+        "ARRANGER"
+    } else if role.contains("conductor") {
+        "CONDUCTOR"
+    } else {
+        "PERFORMER"
+    }
+}
+
+const DATA_TYPE_IMPLICIT: u32 = 0;
+const DATA_TYPE_UTF8: u32 = 1;
+const DATA_TYPE_JPEG: u32 = 13;
+const DATA_TYPE_PNG: u32 = 14;
+const DATA_TYPE_INTEGER: u32 = 21;
+
+fn mp4_data_item(name: &[u8; 4], data_type: u32, value: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(value.len() + 8);
+    data.extend(data_type.to_be_bytes());
+    data.extend(0u32.to_be_bytes());
+    data.extend(value);
+    mp4_box(name, &mp4_box(b"data", &data))
+}
+
+fn mp4_freeform_item(name: &str, value: &str) -> Vec<u8> {
+    let mut mean = 0u32.to_be_bytes().to_vec();
+    mean.extend(b"com.apple.iTunes");
+    let mut name_payload = 0u32.to_be_bytes().to_vec();
+    name_payload.extend(name.as_bytes());
+    let mut data = DATA_TYPE_UTF8.to_be_bytes().to_vec();
+    data.extend(0u32.to_be_bytes());
+    data.extend(value.as_bytes());
+    let mut payload = mp4_box(b"mean", &mean);
+    payload.extend(mp4_box(b"name", &name_payload));
+    payload.extend(mp4_box(b"data", &data));
+    mp4_box(b"----", &payload)
+}
+
+fn number_pair(number: Option<u32>, count: Option<u32>, trailing_padding: bool) -> Vec<u8> {
+    let mut data = vec![0, 0];
+    data.extend((number.unwrap_or(0) as u16).to_be_bytes());
+    data.extend((count.unwrap_or(0) as u16).to_be_bytes());
+    if trailing_padding {
+        data.extend([0, 0]);
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tags;
+    use crate::api::credits::Credits;
+
+    #[test]
+    fn test_credits_to_tags() {
+        let credits: Vec<Credits> = serde_json::from_value(serde_json::json!([
+            {
+                "id": "performers",
+                "type": "credits",
+                "href": "/v1/catalog/jp/songs/1214782673/credits/performers",
+                "attributes": { "title": "Performers", "kind": "performer" },
+                "relationships": { "credit-artists": { "data": [
+                    { "id": "1", "type": "credit-artists", "href": "", "attributes": { "name": "Hatsune Miku", "roleNames": ["Vocals"] } }
+                ] } }
+            },
+            {
+                "id": "production",
+                "type": "credits",
+                "href": "/v1/catalog/jp/songs/1214782673/credits/production",
+                "attributes": { "title": "Composition & Lyrics" },
+                "relationships": { "credit-artists": { "data": [
+                    { "id": "2", "type": "credit-artists", "href": "", "attributes": { "name": "TORIENA", "roleNames": ["Composer", "Lyricist", "Producer"] } },
+                    { "id": "3", "type": "credit-artists", "href": "", "attributes": { "name": "Someone", "roleNames": ["Mixing Engineer"] } }
+                ] } }
+            }
+        ]))
+        .unwrap();
+        let mut tags = Tags::default();
+        tags.add_credits(&credits);
+        assert_eq!(tags.composer.as_deref(), Some("TORIENA"));
+        assert_eq!(
+            tags.freeform,
+            [
+                ("PERFORMER", "Hatsune Miku (Vocals)"),
+                ("COMPOSER", "TORIENA"),
+                ("LYRICIST", "TORIENA"),
+                ("PRODUCER", "TORIENA"),
+                ("MIXER", "Someone"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+
+        let ilst = tags.to_mp4_ilst();
+        assert_eq!(&ilst[4..8], b"ilst");
+        assert_eq!(
+            u32::from_be_bytes(ilst[0..4].try_into().unwrap()) as usize,
+            ilst.len()
+        );
+        let wrt = ilst.windows(4).position(|w| w == b"\xa9wrt").unwrap();
+        assert_eq!(&ilst[wrt + 8..wrt + 12], b"data");
+        assert_eq!(&ilst[wrt + 20..wrt + 27], b"TORIENA");
+        assert!(ilst.windows(8).any(|w| w == b"PRODUCER"));
+    }
+}